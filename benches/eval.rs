@@ -0,0 +1,54 @@
+/*
+ * Benchmark for the hand evaluator, run it with `cargo bench`.
+ * Measures how many random seven card hands are evaluated per second.
+ */
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::hint::black_box;
+use std::time::Instant;
+use texas_holdem::card::CardSet;
+use texas_holdem::deck::Deck;
+use texas_holdem::eval::{evaluate, evaluate_cards};
+
+const HANDS: usize = 1_000_000;
+const ROUNDS: usize = 10;
+
+fn main() {
+    // Deal the same random hands every run
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut hands = Vec::with_capacity(HANDS);
+    for _ in 0..HANDS {
+        let mut deck = Deck::new();
+        deck.cards.shuffle(&mut rng);
+        hands.push(deck.cards[..7].to_vec());
+    }
+    let sets: Vec<CardSet> = hands.iter().map(|hand| hand.iter().collect()).collect();
+
+    // Build the lookup tables before timing
+    let start = Instant::now();
+    evaluate(sets[0]);
+    println!("table build: {:.1} ms", start.elapsed().as_secs_f64() * 1000.0);
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for set in sets.iter() {
+            black_box(evaluate(black_box(*set)));
+        }
+    }
+    report("evaluate (CardSet)", start);
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for hand in hands.iter() {
+            black_box(evaluate_cards(black_box(hand)));
+        }
+    }
+    report("evaluate_cards (&[Card])", start);
+}
+
+fn report(name: &str, start: Instant) {
+    let elapsed = start.elapsed().as_secs_f64();
+    let evaluations = (HANDS * ROUNDS) as f64;
+    println!("{:<26} {:>7.1} ns/hand {:>8.1} M hands/s", name, elapsed * 1e9 / evaluations, evaluations / elapsed / 1e6);
+}
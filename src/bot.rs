@@ -0,0 +1,83 @@
+use crate::engine::{ActionRequest, Controller};
+use crate::events::Action;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the kinds of computer player a seat can be given.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BotKind {
+    #[default]
+    Random,
+    Calling,
+}
+
+impl BotKind {
+    pub const ALL: [BotKind; 2] = [BotKind::Random, BotKind::Calling];
+
+    pub fn parse(name: &str) -> Option<BotKind> {
+        BotKind::ALL.into_iter().find(|kind| kind.name() == name.to_ascii_lowercase())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BotKind::Random => "random",
+            BotKind::Calling => "calling",
+        }
+    }
+
+    pub fn controller(self) -> Box<dyn Controller> {
+        match self {
+            BotKind::Random => Box::new(RandomBot),
+            BotKind::Calling => Box::new(CallingBot),
+        }
+    }
+}
+
+/*
+ * The computer player. It raises a quarter of the time, calls half of the time and folds
+ * otherwise, checking instead of folding when there is nothing to call.
+ */
+#[derive(Debug, Default)]
+pub struct RandomBot;
+
+impl Controller for RandomBot {
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        let mut rng = rand::thread_rng();
+        let bet_or_fold = rng.gen_range(1..=4);
+        if bet_or_fold == 1 && request.can_raise() {
+            // Random bet between the current bet and all in, favors lower bets
+            let fraction = (rng.gen_range(0..=25) as f64 / 100.0).powi(2);
+            let bet = request.current_bet + (fraction * (request.max_raise - request.current_bet) as f64) as i32;
+            Action::Raise(bet.max(request.min_raise))
+        } else if bet_or_fold <= 3 {
+            // Call the current bet
+            if request.can_check() {
+                Action::Check
+            } else {
+                Action::Call
+            }
+        } else {
+            request.check_or_fold()
+        }
+    }
+}
+
+/*
+ * A computer player that never raises or folds, it checks when it can and calls otherwise.
+ */
+#[derive(Debug, Default)]
+pub struct CallingBot;
+
+impl Controller for CallingBot {
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        if request.can_check() {
+            Action::Check
+        } else {
+            Action::Call
+        }
+    }
+}
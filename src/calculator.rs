@@ -0,0 +1,95 @@
+use colored::Colorize;
+use rand::rngs::StdRng;
+use rand::{thread_rng, SeedableRng};
+use texas_holdem::card::Card;
+use texas_holdem::equity::{calculate_ranges, parse_cards, DEFAULT_SAMPLES};
+use texas_holdem::range::Range;
+
+const USAGE: &str = "\
+Usage: texas-holdem equity <hand> <hand>... [options]
+       texas-holdem range <range>
+
+Each hand is two hole cards, e.g. AhKh, or a range of hands, e.g. \"QQ+, AKs, A5s-A2s, 76s+\".
+A hand in a range can be given a weight from 0 to 1, e.g. AQo:0.5 for half the time.
+
+Options:
+  --board <cards>    The cards on the board so far, e.g. 2h7hTc
+  --dead <cards>     Cards that are out of the deck, e.g. folded hands
+  --samples <n>      Random boards to deal when there are too many to count (default 100000)
+  --seed <n>         Seed the random boards are dealt from, to get the same answer again";
+
+/*
+ * The equity subcommand: how often each hand or range wins or ties by the river. Small boards
+ * are counted exactly, larger ones are sampled and the equities given with a margin of error.
+ */
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut players = Vec::new();
+    let mut ranges = Vec::new();
+    let mut board = Vec::new();
+    let mut dead = Vec::new();
+    let mut samples = DEFAULT_SAMPLES;
+    let mut seed = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            "--board" => board = parse_cards(&value()?)?,
+            "--dead" => dead = parse_cards(&value()?)?,
+            "--samples" => samples = value()?.parse().map_err(|_| format!("{} needs a number", arg))?,
+            "--seed" => seed = Some(value()?.parse().map_err(|_| format!("{} needs a number", arg))?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}\n{}", arg, USAGE)),
+            _ => {
+                ranges.push(Range::parse(arg)?);
+                players.push(arg.clone());
+            }
+        }
+    }
+    if samples == 0 {
+        return Err("--samples must be at least 1".to_string());
+    }
+
+    let report = match seed {
+        Some(seed) => calculate_ranges(&ranges, &board, &dead, samples, &mut StdRng::seed_from_u64(seed)),
+        None => calculate_ranges(&ranges, &board, &dead, samples, &mut thread_rng()),
+    }
+    .map_err(|error| format!("{}\n{}", error, USAGE))?;
+
+    if !board.is_empty() {
+        println!("Board: {}", codes(&board));
+    }
+    if !dead.is_empty() {
+        println!("Dead: {}", codes(&dead));
+    }
+    let width = players.iter().map(|player| player.chars().count()).max().unwrap_or(0).max(6) + 2;
+    println!("{}", format!("{:<width$}{:>9}{:>9}{:>9}", "Hand", "Win", "Tie", "Equity").bold());
+    for (player, equity) in players.iter().zip(&report.players) {
+        println!("{:<width$}{:>8.2}%{:>8.2}%{:>8.2}%", player, equity.win * 100.0, equity.tie * 100.0, equity.equity * 100.0);
+    }
+    if report.exact {
+        println!("Every one of the {} possible deals was counted", report.boards);
+    } else {
+        let margin = report.players.iter().map(|equity| equity.margin).fold(0.0, f64::max);
+        println!("{} random boards were dealt, the equities are within ±{:.2}% with 95% confidence", report.boards, margin * 100.0);
+    }
+    Ok(())
+}
+
+fn codes(cards: &[Card]) -> String {
+    cards.iter().map(|card| card.code()).collect()
+}
+
+// The range subcommand: draws a range as the grid of starting hands
+pub fn show_range(args: &[String]) -> Result<(), String> {
+    if args.is_empty() || args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let range = Range::parse(&args.join(","))?;
+    print!("{}", range.grid());
+    println!("{:.0} hands, {:.1}% of all starting hands", range.size(), range.share() * 100.0);
+    Ok(())
+}
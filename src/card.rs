@@ -1,8 +1,12 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::{BitAnd, BitOr, Not};
+
 /*
  * This is an enumeration type (Sebesta, 6.4)
  * It enumerates the four suits of a deck of cards
  */
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Suit {
     Clubs,
     Diamonds,
@@ -10,11 +14,20 @@ pub enum Suit {
     Spades,
 }
 
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    // Letter used in short card notation, e.g. the h in Ah
+    pub fn symbol(self) -> char {
+        ['c', 'd', 'h', 's'][self as usize]
+    }
+}
+
 /*
  * This is an enumeration type (Sebesta, 6.4)
  * It enumerates the ranks of a deck of cards
  */
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Rank {
     Two,
     Three,
@@ -31,12 +44,229 @@ pub enum Rank {
     Ace,
 }
 
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Two, Rank::Three, Rank::Four, Rank::Five, Rank::Six, Rank::Seven, Rank::Eight,
+        Rank::Nine, Rank::Ten, Rank::Jack, Rank::Queen, Rank::King, Rank::Ace,
+    ];
+
+    // Letter or digit used in short card notation, e.g. the A in Ah
+    pub fn symbol(self) -> char {
+        ['2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A'][self as usize]
+    }
+
+    // Plural name of the rank, e.g. "Kings" or "Sixes"
+    pub fn plural(self) -> String {
+        match self {
+            Rank::Six => "Sixes".to_string(),
+            _ => format!("{:?}s", self),
+        }
+    }
+}
+
 /*
  * This is a structure type (Sebesta, 6.4)
  * It represents a card in a deck of cards
+ * Cards are ordered by rank first and suit second.
  */
-#[derive(Clone, Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Card {
-    pub suit: Suit,
     pub rank: Rank,
-}
\ No newline at end of file
+    pub suit: Suit,
+}
+
+impl Card {
+    pub fn new(rank: Rank, suit: Suit) -> Card {
+        Card { rank, suit }
+    }
+
+    // Compact index of the card, 0 (Two of Clubs) to 51 (Ace of Spades)
+    pub fn index(self) -> u8 {
+        self.rank as u8 * 4 + self.suit as u8
+    }
+
+    // Short notation, e.g. "Ah" for the Ace of Hearts or "Td" for the Ten of Diamonds
+    pub fn code(self) -> String {
+        format!("{}{}", self.rank.symbol(), self.suit.symbol())
+    }
+
+    pub fn from_index(index: u8) -> Option<Card> {
+        if index >= 52 {
+            return None;
+        }
+        Some(Card::new(Rank::ALL[(index / 4) as usize], Suit::ALL[(index % 4) as usize]))
+    }
+
+    // Reads the short notation written by code(), e.g. "Ah"
+    pub fn from_code(code: &str) -> Option<Card> {
+        let mut chars = code.chars();
+        let (rank, suit) = (chars.next()?.to_ascii_uppercase(), chars.next()?.to_ascii_lowercase());
+        if chars.next().is_some() {
+            return None;
+        }
+        let rank = Rank::ALL.into_iter().find(|r| r.symbol() == rank)?;
+        let suit = Suit::ALL.into_iter().find(|s| s.symbol() == suit)?;
+        Some(Card::new(rank, suit))
+    }
+}
+
+// Cards are written in short notation, e.g. "Ah", so saved files stay readable
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Card, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Card::from_code(&code).ok_or_else(|| de::Error::custom(format!("invalid card \"{}\"", code)))
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Pads like a string so that cards line up in the boxed tables
+        f.pad(&format!("{:?} of {:?}", self.rank, self.suit))
+    }
+}
+
+/*
+ * A set of cards stored as a 52-bit mask, one bit per card index.
+ * Membership tests, unions and dead card removal are single bit operations.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CardSet(u64);
+
+impl CardSet {
+    const FULL_MASK: u64 = (1 << 52) - 1;
+
+    pub fn new() -> CardSet {
+        CardSet(0)
+    }
+
+    pub fn full() -> CardSet {
+        CardSet(CardSet::FULL_MASK)
+    }
+
+    pub fn from_bits(bits: u64) -> CardSet {
+        CardSet(bits & CardSet::FULL_MASK)
+    }
+
+    pub fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub fn insert(&mut self, card: Card) -> bool {
+        let present = self.contains(card);
+        self.0 |= 1 << card.index();
+        !present
+    }
+
+    pub fn remove(&mut self, card: Card) -> bool {
+        let present = self.contains(card);
+        self.0 &= !(1 << card.index());
+        present
+    }
+
+    pub fn contains(self, card: Card) -> bool {
+        self.0 & (1 << card.index()) != 0
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_disjoint(self, other: CardSet) -> bool {
+        self.0 & other.0 == 0
+    }
+
+    // 13-bit mask of the ranks held in one suit, bit 0 is the Two
+    pub fn suit_ranks(self, suit: Suit) -> u16 {
+        let mut mask = 0;
+        for rank in Rank::ALL {
+            if self.contains(Card::new(rank, suit)) {
+                mask |= 1 << rank as u16;
+            }
+        }
+        mask
+    }
+
+    // Iterates the cards from lowest index to highest
+    pub fn iter(self) -> CardSetIter {
+        CardSetIter(self.0)
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+
+    fn bitor(self, other: CardSet) -> CardSet {
+        CardSet(self.0 | other.0)
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+
+    fn bitand(self, other: CardSet) -> CardSet {
+        CardSet(self.0 & other.0)
+    }
+}
+
+impl Not for CardSet {
+    type Output = CardSet;
+
+    fn not(self) -> CardSet {
+        CardSet(!self.0 & CardSet::FULL_MASK)
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(cards: I) -> CardSet {
+        let mut set = CardSet::new();
+        for card in cards {
+            set.insert(card);
+        }
+        set
+    }
+}
+
+impl<'a> FromIterator<&'a Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = &'a Card>>(cards: I) -> CardSet {
+        cards.into_iter().copied().collect()
+    }
+}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = CardSetIter;
+
+    fn into_iter(self) -> CardSetIter {
+        self.iter()
+    }
+}
+
+pub struct CardSetIter(u64);
+
+impl Iterator for CardSetIter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.0 == 0 {
+            return None;
+        }
+        let index = self.0.trailing_zeros() as u8;
+        self.0 &= self.0 - 1;
+        Card::from_index(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
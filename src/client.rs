@@ -0,0 +1,132 @@
+use crate::card::Card;
+use crate::engine::Controller;
+use crate::events::{EventSink, GameEvent, Variant};
+use crate::protocol::{receive, send, ClientMessage, ErrorCode, ServerMessage, PROTOCOL_VERSION};
+use std::io::BufReader;
+use std::net::TcpStream;
+
+/*
+ * Follows a table as a spectator: the public events, and with a god view every player's hole
+ * cards once they are revealed, which may be in a later hand.
+ */
+pub trait Spectator: EventSink {
+    fn revealed(&mut self, hand_number: u32, seat: usize, cards: &[Card]);
+}
+
+/*
+ * A player's end of a connection to a table server. The player's decisions are made by a
+ * controller and the game is followed with an event sink, just like at a local table.
+ */
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    // The seat the server gave the player
+    pub seat: usize,
+    // Takes the seat back after losing the connection
+    pub token: String,
+    // The variant of the hand being played, which says how the hands are ranked
+    variant: Variant,
+}
+
+impl Client {
+    fn connect(address: &str) -> Result<Client, String> {
+        let stream = TcpStream::connect(address).map_err(|error| format!("Could not connect to {}: {}", address, error))?;
+        stream.set_nodelay(true).map_err(|error| error.to_string())?;
+        let reader = BufReader::new(stream.try_clone().map_err(|error| error.to_string())?);
+        Ok(Client { reader, writer: stream, seat: 0, token: String::new(), variant: Variant::default() })
+    }
+
+    // Connects to the server and asks for a seat, the error holds the server's reason if it is refused
+    pub fn join(address: &str, name: &str) -> Result<Client, String> {
+        let mut client = Client::connect(address)?;
+        client.send(&ClientMessage::Join { version: PROTOCOL_VERSION, name: name.to_string() })?;
+        client.welcome()
+    }
+
+    // Connects to the server again and takes back the seat the token was given with
+    pub fn rejoin(address: &str, token: &str) -> Result<Client, String> {
+        let mut client = Client::connect(address)?;
+        client.send(&ClientMessage::Rejoin { version: PROTOCOL_VERSION, token: token.to_string() })?;
+        client.welcome()
+    }
+
+    fn welcome(mut self) -> Result<Client, String> {
+        match self.receive()? {
+            ServerMessage::Welcome { seat, token, .. } => {
+                self.seat = seat;
+                self.token = token;
+                Ok(self)
+            }
+            ServerMessage::Error { message, .. } => Err(message),
+            message => Err(format!("expected a seat, got {:?}", message)),
+        }
+    }
+
+    // Connects to the server as a spectator, returns whether it has a god view
+    pub fn watch(address: &str) -> Result<(Client, bool), String> {
+        let mut client = Client::connect(address)?;
+        client.send(&ClientMessage::Watch { version: PROTOCOL_VERSION })?;
+        match client.receive()? {
+            ServerMessage::Watching { god_view, .. } => Ok((client, god_view)),
+            ServerMessage::Error { message, .. } => Err(message),
+            message => Err(format!("expected to watch, got {:?}", message)),
+        }
+    }
+
+    fn send(&mut self, message: &ClientMessage) -> Result<(), String> {
+        send(&mut self.writer, message).map_err(|error| format!("Lost the connection to the server: {}", error))
+    }
+
+    fn receive(&mut self) -> Result<ServerMessage, String> {
+        receive(&mut self.reader).map_err(|error| format!("Lost the connection to the server: {}", error))
+    }
+
+    /*
+     * Plays until the server closes the table, returns the reason it gives. The error starts
+     * with "Lost the connection" if the connection is lost, the seat can then be taken back.
+     */
+    pub fn play(&mut self, controller: &mut dyn Controller, sink: &mut dyn EventSink) -> Result<String, String> {
+        // The request being answered, an action the server refuses is replaced by a check or fold
+        let mut request = None;
+        loop {
+            match self.receive()? {
+                ServerMessage::Event(event) => {
+                    if let GameEvent::HandStarted { variant, .. } = event {
+                        self.variant = variant;
+                    }
+                    sink.handle(&event);
+                }
+                ServerMessage::ActionRequest { request: asked, .. } => {
+                    let action = controller.act(&asked);
+                    self.send(&ClientMessage::Act { action })?;
+                    request = Some(asked);
+                }
+                ServerMessage::ShowRequest { cards, best_five, uncontested } => {
+                    let hand = (best_five.len() == 5).then(|| self.variant.ranking().best_hand(&best_five));
+                    let show = controller.show_hand(&cards, hand.as_ref(), uncontested);
+                    self.send(&ClientMessage::Show { show })?;
+                }
+                ServerMessage::Error { code: ErrorCode::IllegalAction, .. } if request.is_some() => {
+                    let action = request.take().map(|request| request.check_or_fold());
+                    self.send(&ClientMessage::Act { action: action.unwrap() })?;
+                }
+                ServerMessage::Error { message, .. } => return Err(format!("The server reported an error: {}", message)),
+                ServerMessage::TableClosed { reason } => return Ok(reason),
+                ServerMessage::Welcome { .. } | ServerMessage::Watching { .. } | ServerMessage::Revealed { .. } => {}
+            }
+        }
+    }
+
+    // Follows the table as a spectator until the server closes it, returns the reason it gives
+    pub fn follow(&mut self, spectator: &mut dyn Spectator) -> Result<String, String> {
+        loop {
+            match self.receive()? {
+                ServerMessage::Event(event) => spectator.handle(&event),
+                ServerMessage::Revealed { hand_number, seat, cards } => spectator.revealed(hand_number, seat, &cards),
+                ServerMessage::Error { message, .. } => return Err(format!("The server reported an error: {}", message)),
+                ServerMessage::TableClosed { reason } => return Ok(reason),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::card::Card;
+use crate::engine::{ActionRequest, Controller};
+use crate::eval::BestHand;
+use crate::events::Action;
+use std::time::{Duration, Instant};
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * How long players have to act, in seconds. Every action has its own time, and a player who
+ * needs longer uses their time bank. Each bank starts full and is topped up at the start of
+ * every hand, to at most full. A player whose time runs out checks if they can and folds
+ * otherwise.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShotClock {
+    pub action: u32,
+    pub bank: u32,
+    pub top_up: u32,
+}
+
+/*
+ * A player's clock over the game: the shot clock and what is left of their time bank.
+ */
+#[derive(Debug, Clone)]
+pub struct Clock {
+    shot_clock: ShotClock,
+    bank: Duration,
+    // The last hand the bank was topped up for
+    hand_number: u32,
+    // When the action being timed was asked for
+    started: Option<Instant>,
+}
+
+impl Clock {
+    pub fn new(shot_clock: ShotClock) -> Clock {
+        Clock { shot_clock, bank: Duration::from_secs(shot_clock.bank as u64), hand_number: 0, started: None }
+    }
+
+    // Starts timing the player's action, the request is given the time they have
+    pub fn start(&mut self, request: &mut ActionRequest) {
+        // The bank is topped up for every hand since the player last acted, not just once
+        if request.hand_number > self.hand_number {
+            if self.hand_number != 0 {
+                let hands = request.hand_number - self.hand_number;
+                let full = Duration::from_secs(self.shot_clock.bank as u64);
+                self.bank = (self.bank + Duration::from_secs(self.shot_clock.top_up as u64) * hands).min(full);
+            }
+            self.hand_number = request.hand_number;
+        }
+        request.time_bank = self.bank.as_secs() as u32;
+        request.time_limit = Some(self.shot_clock.action + request.time_bank);
+        self.started = Some(Instant::now());
+    }
+
+    // Stops timing, the time taken beyond the action's own comes out of the bank
+    pub fn stop(&mut self) {
+        if let Some(started) = self.started.take() {
+            let over = started.elapsed().saturating_sub(Duration::from_secs(self.shot_clock.action as u64));
+            self.bank = self.bank.saturating_sub(over);
+        }
+    }
+
+    // What is left of the time bank
+    pub fn bank(&self) -> Duration {
+        self.bank
+    }
+
+    // The time every action has before the bank is used
+    pub fn action_time(&self) -> Duration {
+        Duration::from_secs(self.shot_clock.action as u64)
+    }
+}
+
+/*
+ * The time left to answer a request with a time limit, counted from when it arrived. The
+ * controller asked shows it to the player and acts for them once it has run out.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Countdown {
+    deadline: Instant,
+    // When the action's own time is over and the time bank is being used
+    bank_from: Instant,
+}
+
+impl Countdown {
+    // None if the request has no time limit
+    pub fn start(request: &ActionRequest) -> Option<Countdown> {
+        let now = Instant::now();
+        let limit = request.time_limit?;
+        let deadline = now + Duration::from_secs(limit as u64);
+        let bank_from = deadline - Duration::from_secs(request.time_bank.min(limit) as u64);
+        Some(Countdown { deadline, bank_from })
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    pub fn expired(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+
+    pub fn in_bank(&self) -> bool {
+        Instant::now() >= self.bank_from
+    }
+
+    // Whole seconds left, rounded up so the last second shows as 1
+    pub fn seconds_left(&self) -> u64 {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        left.as_millis().div_ceil(1000) as u64
+    }
+
+    // The time left for the player, e.g. "12 seconds to act" or "Time bank: 20 seconds"
+    pub fn describe(&self) -> String {
+        let seconds = self.seconds_left();
+        let unit = if seconds == 1 { "second" } else { "seconds" };
+        if self.in_bank() {
+            format!("Time bank: {} {}", seconds, unit)
+        } else {
+            // The bank comes after the action's own time
+            let own = self.bank_from.saturating_duration_since(Instant::now()).as_millis().div_ceil(1000);
+            format!("{} {} to act", own, if own == 1 { "second" } else { "seconds" })
+        }
+    }
+}
+
+// Times a player asked for their actions at this computer. The controller must answer by the request's time limit.
+pub struct Timed {
+    controller: Box<dyn Controller>,
+    clock: Clock,
+}
+
+impl Timed {
+    pub fn new(controller: Box<dyn Controller>, shot_clock: ShotClock) -> Timed {
+        Timed { controller, clock: Clock::new(shot_clock) }
+    }
+}
+
+impl Controller for Timed {
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        let mut request = request.clone();
+        self.clock.start(&mut request);
+        let action = self.controller.act(&request);
+        self.clock.stop();
+        action
+    }
+
+    fn show_hand(&mut self, cards: &[Card], hand: Option<&BestHand>, uncontested: bool) -> bool {
+        self.controller.show_hand(cards, hand, uncontested)
+    }
+}
@@ -0,0 +1,355 @@
+use crate::bot::BotKind;
+use crate::clock::ShotClock;
+use crate::events::{BettingStructure, Variant};
+use crate::odds::OddsAgainst;
+use serde::Deserialize;
+use std::fs;
+
+// A table can seat this many players: each needs two cards, and the board and burn cards take eight
+pub const MAX_PLAYERS: usize = 22;
+pub const MIN_CHIPS: i32 = 10;
+// The time bank of a shot clock, in seconds, and how much of it comes back every hand
+pub const DEFAULT_TIME_BANK: u32 = 30;
+pub const DEFAULT_TIME_BANK_TOP_UP: u32 = 5;
+
+pub const USAGE: &str = "\
+Usage: texas-holdem [options]
+       texas-holdem replay <event log> [hand number] [--show-cards]
+       texas-holdem server [--listen <address>] [--web <address>] [--humans <n>] [--hands <n>] [options]
+       texas-holdem connect <address> [--name <name>] [--token <token>] [--tui] [--always-show] [--no-color]
+       texas-holdem watch <address> [--no-color]
+       texas-holdem equity <hand or range> <hand or range>... [--board <cards>] [--dead <cards>] [--samples <n>] [--seed <n>]
+       texas-holdem range <range>
+       texas-holdem stats <event log>
+       texas-holdem leaderboard
+
+Options:
+  --config <file>        Read the table setup from a TOML file, other options override it
+  --players <n>          Number of players, including you (2 to 22, 11 in Omaha, 14 in short deck)
+  --chips <n>            Starting chips of each player (at least 10)
+  --blinds <sb>/<bb>     Small and big blind, e.g. 5/10, in short deck only the big blind is posted
+  --ante <n>             Ante of every player in short deck (default the small blind)
+  --structure <name>     Betting structure: no-limit, pot-limit or fixed-limit (nl, pl, fl)
+  --variant <name>       Game to play: holdem, omaha (plo) with four hole cards, omaha-hi-lo (plo8)
+                         with a split pot, or short-deck (6+) without the Twos to Fives; Omaha is
+                         pot limit unless --structure is given
+  --bots <kinds>         Computer players by seat, e.g. random,calling (the rest are random)
+  --seed <n>             Seed the decks are shuffled from, to deal the same cards again
+  --shot-clock <n>       Seconds each player has to act, then they check or fold (default no limit)
+  --time-bank <n>        Seconds of each player's time bank, used once the shot clock runs out (default 30)
+  --time-bank-top-up <n> Seconds added to every time bank each hand, up to its full size (default 5)
+  --profile <name>       Play as this profile, whose results are kept over every game
+  --always-show          Always show your hand at showdown instead of asking
+  --no-pause             Don't wait for Enter between streets
+  --no-color             Print without colors
+  --tui                  Play full screen, with the table drawn in the terminal
+  --odds <against>       Show your made hand, outs, pot odds and equity against random or estimated hands
+  --events <file>        Write every game event as JSON Lines, - for standard output
+  --csv <file>           Write every player's chips in every hand to a CSV file when the game ends
+  --resume               Continue the saved game
+  --help                 Print this help
+
+Server options:
+  --listen <address>     Address to listen on (default 0.0.0.0:7777)
+  --web <address>        Also serve the browser client at this address, e.g. 0.0.0.0:8080
+  --humans <n>           Players who connect, the other seats are computer players (default 2)
+  --hands <n>            Stop after this many hands
+  --god-view-hands <n>   Show spectators every player's cards once n hands have ended since the deal
+  --god-view-seconds <n> Show spectators every player's cards n seconds after the deal
+  --disconnect-bank <n>  Seconds a player's seat waits for them over the game while they are away (default 30)";
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * Display settings of the terminal game. A missing setting uses its default, or is asked for.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub always_show: Option<bool>,
+    // Wait for Enter between streets
+    pub pause: Option<bool>,
+    pub color: Option<bool>,
+    // Play full screen instead of printing the game line by line
+    pub tui: Option<bool>,
+    // Show the odds helper when it is the user's turn, None to leave it off
+    pub odds: Option<OddsAgainst>,
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * How to set up the table, read from a TOML file and from command-line flags, e.g.
+ *
+ *     players = 6
+ *     chips = 1000
+ *     small_blind = 10
+ *     big_blind = 20
+ *     structure = "pot-limit"
+ *     variant = "omaha"
+ *     bots = ["random", "calling"]
+ *     seed = 42
+ *     shot_clock = 30
+ *
+ *     [display]
+ *     always_show = true
+ *     odds = "estimated"
+ *
+ * Settings that are left out are None: the blinds are worked out from the chips, and the
+ * number of players, chips and always_show are asked for.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub players: Option<usize>,
+    pub chips: Option<i32>,
+    pub small_blind: Option<i32>,
+    pub big_blind: Option<i32>,
+    // Only posted in games with antes
+    pub ante: Option<i32>,
+    pub structure: Option<BettingStructure>,
+    pub variant: Option<Variant>,
+    // The computer players of seats 2, 3 and so on, seats without one are random
+    pub bots: Option<Vec<BotKind>>,
+    pub seed: Option<u64>,
+    // Seconds to act, None for no limit, and the time bank used after them
+    pub shot_clock: Option<u32>,
+    pub time_bank: Option<u32>,
+    pub time_bank_top_up: Option<u32>,
+    // The profile of the person playing, asked for if not given
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub display: DisplayConfig,
+}
+
+impl Config {
+    pub fn from_toml(text: &str) -> Result<Config, String> {
+        toml::from_str(text).map_err(|error| error.to_string().trim_end().to_string())
+    }
+
+    pub fn load(path: &str) -> Result<Config, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+        Config::from_toml(&text).map_err(|error| format!("Invalid config file {}: {}", path, error))
+    }
+
+    // Settings of other replace these, e.g. command-line flags replace the config file
+    pub fn merge(self, other: Config) -> Config {
+        Config {
+            players: other.players.or(self.players),
+            chips: other.chips.or(self.chips),
+            small_blind: other.small_blind.or(self.small_blind),
+            big_blind: other.big_blind.or(self.big_blind),
+            ante: other.ante.or(self.ante),
+            structure: other.structure.or(self.structure),
+            variant: other.variant.or(self.variant),
+            bots: other.bots.or(self.bots),
+            seed: other.seed.or(self.seed),
+            shot_clock: other.shot_clock.or(self.shot_clock),
+            time_bank: other.time_bank.or(self.time_bank),
+            time_bank_top_up: other.time_bank_top_up.or(self.time_bank_top_up),
+            profile: other.profile.or(self.profile),
+            display: DisplayConfig {
+                always_show: other.display.always_show.or(self.display.always_show),
+                pause: other.display.pause.or(self.display.pause),
+                color: other.display.color.or(self.display.color),
+                tui: other.display.tui.or(self.display.tui),
+                odds: other.display.odds.or(self.display.odds),
+            },
+        }
+    }
+
+    // Checks the settings that are given, the missing ones are checked once they are known
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(players) = self.players {
+            check_players(players, self.bot_count(), self.variant())?;
+        }
+        if let Some(chips) = self.chips {
+            check_chips(chips)?;
+        }
+        if let Some(small_blind) = self.small_blind {
+            if small_blind < 1 {
+                return Err(format!("small_blind must be at least 1, got {}", small_blind));
+            }
+        }
+        if let Some(big_blind) = self.big_blind {
+            if big_blind < 2 {
+                return Err(format!("big_blind must be at least 2, got {}", big_blind));
+            }
+        }
+        if let (Some(small_blind), Some(big_blind)) = (self.small_blind, self.big_blind) {
+            if small_blind > big_blind {
+                return Err(format!("small_blind ({}) must not be larger than big_blind ({})", small_blind, big_blind));
+            }
+        }
+        if let Some(ante) = self.ante {
+            if !self.variant().antes() {
+                return Err(format!("{} has no ante", self.variant().name()));
+            }
+            if ante < 1 {
+                return Err(format!("ante must be at least 1, got {}", ante));
+            }
+        }
+        if self.profile.as_ref().is_some_and(|name| name.trim().is_empty()) {
+            return Err("profile must be a name".to_string());
+        }
+        if self.variant() != Variant::Holdem && self.display.odds.is_some() {
+            return Err(format!("the odds helper only works in Hold'em, not {}", self.variant().name()));
+        }
+        if self.shot_clock == Some(0) {
+            return Err("shot_clock must be at least 1 second".to_string());
+        }
+        if self.shot_clock.is_none() && (self.time_bank.is_some() || self.time_bank_top_up.is_some()) {
+            return Err("a time bank needs a shot_clock".to_string());
+        }
+        Ok(())
+    }
+
+    // How long players have to act, None if there is no shot clock
+    pub fn shot_clock(&self) -> Option<ShotClock> {
+        Some(ShotClock {
+            action: self.shot_clock?,
+            bank: self.time_bank.unwrap_or(DEFAULT_TIME_BANK),
+            top_up: self.time_bank_top_up.unwrap_or(DEFAULT_TIME_BANK_TOP_UP),
+        })
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant.unwrap_or_default()
+    }
+
+    // The betting structure to play, the variant's usual one if none is given
+    pub fn structure(&self) -> BettingStructure {
+        self.structure.unwrap_or(self.variant().default_structure())
+    }
+
+    pub fn bot_count(&self) -> usize {
+        self.bots.as_ref().map_or(0, |bots| bots.len())
+    }
+
+    // The blinds to play with, a big blind of a fiftieth of the chips if neither is given
+    pub fn blinds(&self, chips: i32) -> (i32, i32) {
+        match (self.small_blind, self.big_blind) {
+            (Some(small_blind), Some(big_blind)) => (small_blind, big_blind),
+            (Some(small_blind), None) => (small_blind, 2 * small_blind),
+            (None, Some(big_blind)) => ((big_blind / 2).max(1), big_blind),
+            (None, None) => {
+                let big_blind = (chips / 50).max(2);
+                (big_blind / 2, big_blind)
+            }
+        }
+    }
+
+    // The ante in games with antes, the size of the small blind if none is given, and 0 in other games
+    pub fn ante(&self, chips: i32) -> i32 {
+        if !self.variant().antes() {
+            return 0;
+        }
+        self.ante.unwrap_or(self.blinds(chips).0)
+    }
+
+    // The kind of computer player at a seat, seat 0 is the user's
+    pub fn bot(&self, seat: usize) -> BotKind {
+        let bots = self.bots.as_deref().unwrap_or_default();
+        seat.checked_sub(1).and_then(|i| bots.get(i)).copied().unwrap_or_default()
+    }
+}
+
+// The most players depends on the variant, the deck must deal every player's hole cards
+pub fn check_players(players: usize, bots: usize, variant: Variant) -> Result<(), String> {
+    let max = variant.max_players().min(MAX_PLAYERS);
+    if !(2..=max).contains(&players) {
+        return Err(format!("players must be between 2 and {}, got {}", max, players));
+    }
+    if bots > players - 1 {
+        return Err(format!("{} bots are given but there are only {} computer players", bots, players - 1));
+    }
+    Ok(())
+}
+
+pub fn check_chips(chips: i32) -> Result<(), String> {
+    if chips < MIN_CHIPS {
+        return Err(format!("chips must be at least {}, got {}", MIN_CHIPS, chips));
+    }
+    Ok(())
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * The command-line options of the game, apart from the replay subcommand.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    pub config: Config,
+    pub config_file: Option<String>,
+    pub events: Option<String>,
+    pub csv: Option<String>,
+    pub resume: bool,
+    pub help: bool,
+}
+
+impl Options {
+    // Reads the options, args doesn't include the program name
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().cloned().ok_or(format!("{} needs a value", name));
+            let config = &mut options.config;
+            match arg.as_str() {
+                "--config" => options.config_file = Some(value(arg)?),
+                "--players" => config.players = Some(number(arg, &value(arg)?)?),
+                "--chips" => config.chips = Some(number(arg, &value(arg)?)?),
+                "--blinds" => {
+                    let blinds = value(arg)?;
+                    let (small_blind, big_blind) = blinds
+                        .split_once('/')
+                        .ok_or(format!("--blinds must be written as small/big, e.g. 5/10, got {}", blinds))?;
+                    config.small_blind = Some(number(arg, small_blind)?);
+                    config.big_blind = Some(number(arg, big_blind)?);
+                }
+                "--ante" => config.ante = Some(number(arg, &value(arg)?)?),
+                "--structure" => {
+                    let name = value(arg)?;
+                    let structure = BettingStructure::parse(&name)
+                        .ok_or(format!("unknown betting structure {}, use no-limit, pot-limit or fixed-limit", name))?;
+                    config.structure = Some(structure);
+                }
+                "--variant" => {
+                    let name = value(arg)?;
+                    let variant = Variant::parse(&name).ok_or(format!("unknown variant {}, use holdem, omaha, omaha-hi-lo or short-deck", name))?;
+                    config.variant = Some(variant);
+                }
+                "--bots" => {
+                    let names = value(arg)?;
+                    let bots = names
+                        .split(',')
+                        .map(|name| BotKind::parse(name.trim()).ok_or(format!("unknown bot {}, use random or calling", name)))
+                        .collect::<Result<Vec<BotKind>, String>>()?;
+                    config.bots = Some(bots);
+                }
+                "--seed" => config.seed = Some(number(arg, &value(arg)?)?),
+                "--shot-clock" => config.shot_clock = Some(number(arg, &value(arg)?)?),
+                "--time-bank" => config.time_bank = Some(number(arg, &value(arg)?)?),
+                "--time-bank-top-up" => config.time_bank_top_up = Some(number(arg, &value(arg)?)?),
+                "--profile" => config.profile = Some(value(arg)?),
+                "--always-show" => config.display.always_show = Some(true),
+                "--no-pause" => config.display.pause = Some(false),
+                "--no-color" => config.display.color = Some(false),
+                "--tui" => config.display.tui = Some(true),
+                "--odds" => {
+                    let name = value(arg)?;
+                    let against = OddsAgainst::parse(&name).ok_or(format!("--odds must be random or estimated, got {}", name))?;
+                    config.display.odds = Some(against);
+                }
+                "--events" => options.events = Some(value("--events (a file name, or - for standard output)")?),
+                "--csv" => options.csv = Some(value(arg)?),
+                "--resume" => options.resume = true,
+                "--help" | "-h" => options.help = true,
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("{} needs a number, got {}", flag, value))
+}
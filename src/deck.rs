@@ -1,6 +1,7 @@
-use crate::card::{Card, Rank, Suit};
+use crate::card::{Card, CardSet, Rank, Suit};
+use crate::eval::Ranking;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 /*
  * The struct type in Rust is a record type (Sebesta, 6.7)
  * It is an aggregate data type with one element named cards.
@@ -9,22 +10,47 @@ pub struct Deck {
     pub cards: Vec<Card>,
 }
 
+impl Default for Deck {
+    fn default() -> Deck {
+        Deck::new()
+    }
+}
+
 impl Deck {
     pub fn new() -> Deck {
+        Deck::for_ranking(Ranking::Standard)
+    }
+
+    // The deck hands are ranked with, from its lowest rank to the Ace, e.g. 36 cards in short deck
+    pub fn for_ranking(ranking: Ranking) -> Deck {
         let mut cards = Vec::new();
-        for suit in vec![Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs] {
-            for rank in vec![Rank::Two, Rank::Three, Rank::Four, Rank::Five, Rank::Six, Rank::Seven, Rank::Eight, Rank::Nine, Rank::Ten, Rank::Jack, Rank::Queen, Rank::King, Rank::Ace] {
-                cards.push(Card { suit: suit.clone(), rank: rank.clone() });
+        for suit in [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs] {
+            for rank in Rank::ALL.into_iter().filter(|&rank| rank >= ranking.lowest_rank()) {
+                cards.push(Card::new(rank, suit));
             }
         }
         Deck { cards }
     }
 
     pub fn shuffle(&mut self) {
-        self.cards.shuffle(&mut thread_rng());
+        self.shuffle_with(&mut thread_rng());
+    }
+
+    // Shuffles with the given random number generator, so a seeded generator deals the same cards every time
+    pub fn shuffle_with<R: Rng>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
     }
 
     pub fn deal(&mut self) -> Option<Card> {
         self.cards.pop()
     }
-}
\ No newline at end of file
+
+    // Removes known (dead) cards from the deck, e.g. cards already dealt or exposed
+    pub fn remove(&mut self, dead: CardSet) {
+        self.cards.retain(|card| !dead.contains(*card));
+    }
+
+    pub fn card_set(&self) -> CardSet {
+        self.cards.iter().collect()
+    }
+}
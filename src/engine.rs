@@ -0,0 +1,653 @@
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::eval::{BestHand, LowHand};
+use crate::events::{Action, BettingStructure, Blind, EventSink, GameEvent, PotShare, SeatInfo, Street, Variant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * A player sitting at the table, kept from hand to hand.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Seat {
+    pub name: String,
+    pub chips: i32,
+    // A player sitting out keeps their chips but is not dealt in, e.g. while they are away
+    #[serde(default)]
+    pub sitting_out: bool,
+}
+
+impl Seat {
+    pub fn new(name: &str, chips: i32) -> Seat {
+        Seat { name: name.to_string(), chips, sitting_out: false }
+    }
+
+    // Whether the player is dealt into the next hand
+    pub fn dealt_in(&self) -> bool {
+        self.chips > 0 && !self.sitting_out
+    }
+}
+
+/*
+ * A seat as the player to act sees it.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeatView {
+    pub name: String,
+    pub chips: i32,
+    // Chips bet this betting round, including blinds
+    pub bet: i32,
+    pub folded: bool,
+    // True once the player has acted this betting round
+    pub acted: bool,
+    pub all_in: bool,
+    // False for players without chips or sitting out, who are not dealt in
+    pub playing: bool,
+}
+
+/*
+ * Everything a player sees when it is their turn to bet, and the bets they may make.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionRequest {
+    pub hand_number: u32,
+    pub seat: usize,
+    pub street: Street,
+    pub hole_cards: Vec<Card>,
+    pub board: Vec<Card>,
+    pub seats: Vec<SeatView>,
+    // All chips bet this hand, including this round
+    pub pot: i32,
+    // The bet to match this round, and the player's own bet so far
+    pub current_bet: i32,
+    pub bet: i32,
+    pub chips: i32,
+    // Chips needed to call, at most the player's chips
+    pub to_call: i32,
+    // Smallest and largest total bet a raise can make, the smallest is all in if short
+    pub min_raise: i32,
+    pub max_raise: i32,
+    // The whole seconds the player has to act, None without a shot clock. The last time_bank
+    // of them come out of the player's time bank. The engine leaves these to the shot clock.
+    #[serde(default)]
+    pub time_limit: Option<u32>,
+    #[serde(default)]
+    pub time_bank: u32,
+}
+
+impl ActionRequest {
+    pub fn can_check(&self) -> bool {
+        self.to_call == 0
+    }
+
+    pub fn can_raise(&self) -> bool {
+        self.max_raise > self.current_bet
+    }
+
+    pub fn is_legal(&self, action: Action) -> bool {
+        match action {
+            Action::Fold => true,
+            Action::Check => self.can_check(),
+            Action::Call => !self.can_check(),
+            Action::Raise(to) => self.can_raise() && to >= self.min_raise && to <= self.max_raise,
+        }
+    }
+
+    // Checks if that is legal and folds otherwise
+    pub fn check_or_fold(&self) -> Action {
+        if self.can_check() {
+            Action::Check
+        } else {
+            Action::Fold
+        }
+    }
+}
+
+/*
+ * Makes the decisions for one seat, e.g. the user at the terminal or a computer player.
+ */
+pub trait Controller {
+    // Chooses an action, actions that are not legal are replaced by a check or fold
+    fn act(&mut self, request: &ActionRequest) -> Action;
+
+    /*
+     * Asked at showdown when the player may muck instead of showing: when their hand can't win,
+     * or when everyone else folded. hand is None if the board has too few cards to make one.
+     * Returns true to show the hand.
+     */
+    fn show_hand(&mut self, _cards: &[Card], _hand: Option<&BestHand>, _uncontested: bool) -> bool {
+        false
+    }
+}
+
+/*
+ * A pot and the players who can win it. Players who went all in for less than others are
+ * only eligible for the pots they could match.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: i32,
+    pub eligible: Vec<usize>,
+}
+
+/*
+ * Splits the chips put in by each player into a main pot and side pots.
+ * committed holds each player's chips put in this hand, in_hand whether they are still in it.
+ * Chips of folded players beyond what any remaining player matched go to the last pot.
+ */
+pub fn side_pots(committed: &[i32], in_hand: &[bool]) -> Vec<Pot> {
+    let mut levels: Vec<i32> = (0..committed.len()).filter(|&i| in_hand[i]).map(|i| committed[i]).collect();
+    levels.sort();
+    levels.dedup();
+
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut previous = 0;
+    for level in levels {
+        let amount = committed.iter().map(|&c| c.min(level) - c.min(previous)).sum();
+        let eligible: Vec<usize> = (0..committed.len()).filter(|&i| in_hand[i] && committed[i] >= level).collect();
+        match pots.last_mut() {
+            Some(last) if last.eligible == eligible => last.amount += amount,
+            _ => pots.push(Pot { amount, eligible }),
+        }
+        previous = level;
+    }
+    let rest = committed.iter().sum::<i32>() - pots.iter().map(|pot| pot.amount).sum::<i32>();
+    if let Some(last) = pots.last_mut() {
+        last.amount += rest;
+    }
+    pots.retain(|pot| pot.amount > 0);
+    pots
+}
+
+/*
+ * The state of the hand being played.
+ */
+struct HandState {
+    deck: Deck,
+    hole_cards: Vec<Vec<Card>>,
+    board: Vec<Card>,
+    playing: Vec<bool>,
+    folded: Vec<bool>,
+    acted: Vec<bool>,
+    // Chips bet this round and this hand
+    bets: Vec<i32>,
+    committed: Vec<i32>,
+    current_bet: i32,
+    // Size of the last full raise, the next raise must be at least as big
+    min_raise_by: i32,
+    // Bets and raises made this round, fixed limit allows four
+    raises: u32,
+    last_aggressor: Option<usize>,
+}
+
+impl HandState {
+    fn in_hand(&self, seat: usize) -> bool {
+        self.playing[seat] && !self.folded[seat]
+    }
+
+    fn remaining(&self) -> usize {
+        (0..self.playing.len()).filter(|&i| self.in_hand(i)).count()
+    }
+}
+
+/*
+ * The table: its seats, the dealer button and the blinds. It plays one hand at a time,
+ * asking each seat's controller for its actions and sending every event to the sinks.
+ * The whole table can be saved between hands and restored to carry on the same game.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Table {
+    pub seats: Vec<Seat>,
+    // The dealer button of the last hand, it moves to the next player dealt in every hand
+    pub button: usize,
+    pub small_blind: i32,
+    pub big_blind: i32,
+    // Posted by every player in games with antes, which have no small blind
+    #[serde(default)]
+    pub ante: i32,
+    pub hand_number: u32,
+    #[serde(default)]
+    pub structure: BettingStructure,
+    #[serde(default)]
+    pub variant: Variant,
+    // The deck of every hand is shuffled from the seed and the hand number, so a restored
+    // table deals the same cards it would have dealt
+    pub seed: u64,
+}
+
+impl Table {
+    pub fn new(seats: Vec<Seat>, small_blind: i32, big_blind: i32) -> Table {
+        // The first hand moves the button to the first seat
+        let button = seats.len() - 1;
+        Table {
+            seats,
+            button,
+            small_blind,
+            big_blind,
+            ante: 0,
+            hand_number: 0,
+            structure: BettingStructure::NoLimit,
+            variant: Variant::Holdem,
+            seed: rand::thread_rng().gen(),
+        }
+    }
+
+    // Players with chips, sitting out or not
+    pub fn players_with_chips(&self) -> usize {
+        self.seats.iter().filter(|seat| seat.chips > 0).count()
+    }
+
+    // Players who are dealt into the next hand
+    pub fn players_dealt_in(&self) -> usize {
+        self.seats.iter().filter(|seat| seat.dealt_in()).count()
+    }
+
+    // The next seat after seat, going clockwise, that satisfies the condition
+    fn next_seat(&self, seat: usize, condition: impl Fn(usize) -> bool) -> usize {
+        let n = self.seats.len();
+        (1..=n).map(|offset| (seat + offset) % n).find(|&i| condition(i)).unwrap_or(seat)
+    }
+
+    /*
+     * Plays one hand. controllers holds one controller per seat.
+     * Does nothing if fewer than two players are dealt in.
+     */
+    pub fn play_hand(&mut self, controllers: &mut [Box<dyn Controller>], sinks: &mut [Box<dyn EventSink>]) {
+        if self.players_dealt_in() < 2 {
+            return;
+        }
+        let n = self.seats.len();
+        let playing: Vec<bool> = self.seats.iter().map(|seat| seat.dealt_in()).collect();
+        self.hand_number += 1;
+        self.button = self.next_seat(self.button, |i| playing[i]);
+
+        let mut hand = HandState {
+            deck: Deck::for_ranking(self.variant.ranking()),
+            hole_cards: vec![Vec::new(); n],
+            board: Vec::new(),
+            playing: playing.clone(),
+            folded: vec![false; n],
+            acted: vec![false; n],
+            bets: vec![0; n],
+            committed: vec![0; n],
+            current_bet: 0,
+            min_raise_by: self.big_blind,
+            // The big blind counts as the first bet
+            raises: 1,
+            last_aggressor: None,
+        };
+        let mut rng = StdRng::seed_from_u64(self.seed ^ (self.hand_number as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        hand.deck.shuffle_with(&mut rng);
+
+        let seats = self.seats.iter().enumerate()
+            .map(|(i, seat)| SeatInfo { seat: i, name: seat.name.clone(), chips: seat.chips, playing: playing[i] })
+            .collect();
+        let (small_blind, ante) = if self.variant.antes() { (0, self.ante) } else { (self.small_blind, 0) };
+        emit(sinks, GameEvent::HandStarted {
+            hand_number: self.hand_number,
+            button: self.button,
+            small_blind,
+            big_blind: self.big_blind,
+            ante,
+            structure: self.structure,
+            variant: self.variant,
+            seats,
+        });
+
+        // Post the blinds, heads up the button posts the small blind. In games with antes everyone
+        // posts the ante, which is no bet, and the button posts the big blind and acts last.
+        let big_blind_seat = if self.variant.antes() {
+            for offset in 1..=n {
+                let seat = (self.button + offset) % n;
+                if playing[seat] {
+                    self.post_blind(&mut hand, seat, Blind::Ante, self.ante, sinks);
+                }
+            }
+            hand.bets = vec![0; n];
+            self.post_blind(&mut hand, self.button, Blind::Button, self.big_blind, sinks);
+            self.button
+        } else {
+            let heads_up = playing.iter().filter(|&&p| p).count() == 2;
+            let small_blind_seat = if heads_up { self.button } else { self.next_seat(self.button, |i| playing[i]) };
+            let big_blind_seat = self.next_seat(small_blind_seat, |i| playing[i]);
+            self.post_blind(&mut hand, small_blind_seat, Blind::Small, self.small_blind, sinks);
+            self.post_blind(&mut hand, big_blind_seat, Blind::Big, self.big_blind, sinks);
+            big_blind_seat
+        };
+        hand.current_bet = self.big_blind;
+
+        // Deal the hole cards one at a time to each player, starting left of the button
+        for _ in 0..self.variant.hole_cards() {
+            for offset in 1..=n {
+                let seat = (self.button + offset) % n;
+                if playing[seat] {
+                    hand.hole_cards[seat].push(hand.deck.deal().unwrap());
+                }
+            }
+        }
+        for (seat, cards) in hand.hole_cards.iter().enumerate() {
+            if playing[seat] {
+                emit(sinks, GameEvent::HoleCards { seat, cards: cards.clone() });
+            }
+        }
+
+        for street in [Street::Preflop, Street::Flop, Street::Turn, Street::River] {
+            if street != Street::Preflop {
+                self.deal_street(&mut hand, street, sinks);
+            }
+            let first = if street == Street::Preflop {
+                self.next_seat(big_blind_seat, |i| playing[i])
+            } else {
+                self.next_seat(self.button, |i| playing[i])
+            };
+            self.betting_round(&mut hand, street, first, controllers, sinks);
+            self.return_uncalled_bet(&mut hand, sinks);
+            let pot = hand.committed.iter().sum();
+            emit(sinks, GameEvent::BettingRoundEnded { street, pot });
+
+            if hand.remaining() == 1 {
+                break;
+            }
+            // Start the next round
+            hand.bets = vec![0; n];
+            hand.acted = vec![false; n];
+            hand.current_bet = 0;
+            hand.min_raise_by = self.big_blind;
+            hand.raises = 0;
+        }
+
+        self.showdown(&mut hand, controllers, sinks);
+        let chips = self.seats.iter().map(|seat| seat.chips).collect();
+        emit(sinks, GameEvent::HandEnded { chips });
+    }
+
+    // Moves chips from a player's stack into the pot
+    fn put_in(&mut self, hand: &mut HandState, seat: usize, amount: i32) -> i32 {
+        let amount = amount.min(self.seats[seat].chips);
+        self.seats[seat].chips -= amount;
+        hand.bets[seat] += amount;
+        hand.committed[seat] += amount;
+        amount
+    }
+
+    fn post_blind(&mut self, hand: &mut HandState, seat: usize, blind: Blind, amount: i32, sinks: &mut [Box<dyn EventSink>]) {
+        let amount = self.put_in(hand, seat, amount);
+        let all_in = self.seats[seat].chips == 0;
+        emit(sinks, GameEvent::BlindPosted { seat, blind, amount, all_in });
+    }
+
+    fn deal_street(&mut self, hand: &mut HandState, street: Street, sinks: &mut [Box<dyn EventSink>]) {
+        // Burn a card
+        hand.deck.deal();
+        let count = if street == Street::Flop { 3 } else { 1 };
+        let cards: Vec<Card> = (0..count).map(|_| hand.deck.deal().unwrap()).collect();
+        hand.board.extend_from_slice(&cards);
+        emit(sinks, GameEvent::StreetDealt { street, cards, board: hand.board.clone() });
+    }
+
+    fn request(&self, hand: &HandState, seat: usize, street: Street) -> ActionRequest {
+        let chips = self.seats[seat].chips;
+        let bet = hand.bets[seat];
+        let all_in = bet + chips;
+        let pot: i32 = hand.committed.iter().sum();
+        let (min_raise, max_raise) = match self.structure {
+            BettingStructure::NoLimit => (hand.current_bet + hand.min_raise_by, all_in),
+            // The largest raise is the size of the pot after calling
+            BettingStructure::PotLimit => {
+                let pot_after_call = pot + hand.current_bet - bet;
+                (hand.current_bet + hand.min_raise_by, hand.current_bet + pot_after_call)
+            }
+            // Every bet and raise is one fixed size, the fourth one caps the betting
+            BettingStructure::FixedLimit if hand.raises >= 4 => (hand.current_bet, hand.current_bet),
+            BettingStructure::FixedLimit => {
+                let size = if street < Street::Turn { self.big_blind } else { 2 * self.big_blind };
+                (hand.current_bet + size, hand.current_bet + size)
+            }
+        };
+        let max_raise = max_raise.min(all_in);
+        let seats = self.seats.iter().enumerate()
+            .map(|(i, s)| SeatView {
+                name: s.name.clone(),
+                chips: s.chips,
+                bet: hand.bets[i],
+                folded: hand.folded[i],
+                acted: hand.acted[i],
+                all_in: hand.in_hand(i) && s.chips == 0,
+                playing: hand.playing[i],
+            })
+            .collect();
+        ActionRequest {
+            hand_number: self.hand_number,
+            seat,
+            street,
+            hole_cards: hand.hole_cards[seat].clone(),
+            board: hand.board.clone(),
+            seats,
+            pot,
+            current_bet: hand.current_bet,
+            bet,
+            chips,
+            to_call: (hand.current_bet - bet).clamp(0, chips),
+            min_raise: min_raise.min(max_raise),
+            max_raise,
+            time_limit: None,
+            time_bank: 0,
+        }
+    }
+
+    /*
+     * Asks the players to act, starting with first, until everyone still in the hand has
+     * matched the bet or is all in.
+     */
+    fn betting_round(&mut self, hand: &mut HandState, street: Street, first: usize, controllers: &mut [Box<dyn Controller>], sinks: &mut [Box<dyn EventSink>]) {
+        let n = self.seats.len();
+        hand.last_aggressor = None;
+        let can_act = |hand: &HandState, seats: &[Seat], i: usize| hand.in_hand(i) && seats[i].chips > 0;
+        let mut to_act: Vec<bool> = (0..n).map(|i| can_act(hand, &self.seats, i)).collect();
+
+        let mut seat = first;
+        while to_act.iter().any(|&t| t) && hand.remaining() > 1 {
+            if to_act[seat] {
+                to_act[seat] = false;
+                // Nobody is left to bet against a player who has matched the bet
+                let others_can_act = (0..n).any(|i| i != seat && can_act(hand, &self.seats, i));
+                if others_can_act || hand.bets[seat] < hand.current_bet {
+                    let request = self.request(hand, seat, street);
+                    let mut action = controllers[seat].act(&request);
+                    if !request.is_legal(action) {
+                        action = request.check_or_fold();
+                    }
+                    if self.apply(hand, seat, action, sinks) {
+                        // A raise gives everyone else a chance to act again
+                        for (i, to_act) in to_act.iter_mut().enumerate() {
+                            *to_act = i != seat && can_act(hand, &self.seats, i);
+                        }
+                    }
+                }
+            }
+            seat = (seat + 1) % n;
+        }
+    }
+
+    // Carries out a legal action, returns true if it raised the bet
+    fn apply(&mut self, hand: &mut HandState, seat: usize, action: Action, sinks: &mut [Box<dyn EventSink>]) -> bool {
+        hand.acted[seat] = true;
+        let mut amount = 0;
+        let mut raised_by = 0;
+        match action {
+            Action::Fold => hand.folded[seat] = true,
+            Action::Check => {}
+            Action::Call => {
+                amount = self.put_in(hand, seat, hand.current_bet - hand.bets[seat]);
+            }
+            Action::Raise(to) => {
+                amount = self.put_in(hand, seat, to - hand.bets[seat]);
+                raised_by = hand.bets[seat] - hand.current_bet;
+                // An all in for less than a full raise doesn't change the minimum raise
+                hand.min_raise_by = hand.min_raise_by.max(raised_by);
+                hand.current_bet = hand.bets[seat];
+                hand.raises += 1;
+                hand.last_aggressor = Some(seat);
+            }
+        }
+        let all_in = amount > 0 && self.seats[seat].chips == 0;
+        emit(sinks, GameEvent::PlayerActed { seat, action, amount, bet: hand.bets[seat], raised_by, all_in });
+        raised_by > 0
+    }
+
+    // Gives back the part of the highest bet of the round that nobody matched
+    fn return_uncalled_bet(&mut self, hand: &mut HandState, sinks: &mut [Box<dyn EventSink>]) {
+        let mut order: Vec<usize> = (0..self.seats.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(hand.bets[i]));
+        let (highest, second) = (order[0], order[1]);
+        let amount = hand.bets[highest] - hand.bets[second];
+        if amount > 0 {
+            hand.bets[highest] -= amount;
+            hand.committed[highest] -= amount;
+            self.seats[highest].chips += amount;
+            emit(sinks, GameEvent::UncalledBetReturned { seat: highest, amount });
+        }
+    }
+
+    /*
+     * Reveals the hands and awards the pots. The last player to raise on the final betting round
+     * shows first, or the first player after the button if nobody raised, and play goes clockwise.
+     * A player who can't win any pot they are in may muck; when the players were all in, every
+     * hand is shown.
+     */
+    fn showdown(&mut self, hand: &mut HandState, controllers: &mut [Box<dyn Controller>], sinks: &mut [Box<dyn EventSink>]) {
+        let n = self.seats.len();
+        let in_hand: Vec<bool> = (0..n).map(|i| hand.in_hand(i)).collect();
+        let pots = side_pots(&hand.committed, &in_hand);
+
+        if hand.remaining() == 1 {
+            // Everyone else folded, the winner may show or muck
+            let winner = (0..n).find(|&i| in_hand[i]).unwrap();
+            let best_hand = self.best_hand(hand, winner);
+            if controllers[winner].show_hand(&hand.hole_cards[winner], best_hand.as_ref(), true) {
+                emit_shown(sinks, winner, &hand.hole_cards[winner], best_hand.as_ref(), self.low_hand(hand, winner).as_ref());
+            } else {
+                emit(sinks, GameEvent::HandMucked { seat: winner });
+            }
+            for (i, pot) in pots.iter().enumerate() {
+                self.seats[winner].chips += pot.amount;
+                emit(sinks, GameEvent::PotAwarded { pot: i, seat: winner, amount: pot.amount, hand: None, share: PotShare::Whole });
+            }
+            return;
+        }
+
+        emit(sinks, GameEvent::ShowdownStarted);
+        let all_in = (0..n).filter(|&i| in_hand[i] && self.seats[i].chips > 0).count() <= 1;
+        let first = match hand.last_aggressor {
+            Some(seat) if in_hand[seat] => seat,
+            _ => self.next_seat(self.button, |i| in_hand[i]),
+        };
+        let mut shown: Vec<Option<BestHand>> = vec![None; n];
+        let mut shown_low: Vec<Option<LowHand>> = vec![None; n];
+        for offset in 0..n {
+            let seat = (first + offset) % n;
+            if !in_hand[seat] {
+                continue;
+            }
+            let best_hand = self.best_hand(hand, seat).expect("the board is complete at showdown");
+            let low = self.low_hand(hand, seat);
+            // The hand can win a pot if no hand shown in that pot beats it, high or low
+            let can_win = pots.iter().filter(|pot| pot.eligible.contains(&seat)).any(|pot| {
+                let best_shown = pot.eligible.iter().filter_map(|&i| shown[i].as_ref().map(|h| h.rank)).max();
+                let best_low = pot.eligible.iter().filter_map(|&i| shown_low[i].as_ref().map(|low| low.rank)).min();
+                best_shown.is_none_or(|best| best_hand.rank >= best) || low.as_ref().is_some_and(|low| best_low.is_none_or(|best| low.rank <= best))
+            });
+            if can_win || all_in || controllers[seat].show_hand(&hand.hole_cards[seat], Some(&best_hand), false) {
+                emit_shown(sinks, seat, &hand.hole_cards[seat], Some(&best_hand), low.as_ref());
+                shown[seat] = Some(best_hand);
+                shown_low[seat] = low;
+            } else {
+                emit(sinks, GameEvent::HandMucked { seat });
+            }
+        }
+
+        // Split each pot between the best shown hands, in a split pot game half to the best high
+        // hand and half to the best low. Odd chips go to the high half, and within a half to the
+        // first winners after the button.
+        let split = self.variant.split_pot();
+        for (i, pot) in pots.iter().enumerate() {
+            let after_button = (1..=n).map(|offset| (self.button + offset) % n).filter(|seat| pot.eligible.contains(seat));
+            let best = pot.eligible.iter().filter_map(|&seat| shown[seat].as_ref().map(|h| h.rank)).max();
+            let high: Vec<usize> = after_button.clone().filter(|&seat| shown[seat].as_ref().map(|h| h.rank) == best).collect();
+            let best_low = pot.eligible.iter().filter_map(|&seat| shown_low[seat].as_ref().map(|low| low.rank)).min();
+            let low: Vec<usize> = match best_low {
+                Some(best_low) => after_button.filter(|&seat| shown_low[seat].as_ref().map(|low| low.rank) == Some(best_low)).collect(),
+                None => Vec::new(),
+            };
+            let high_name = |seat: usize| shown[seat].as_ref().map(|h| h.description());
+            let low_name = |seat: usize| shown_low[seat].as_ref().map(|low| low.description());
+            if !split {
+                self.award(i, pot.amount, &high, PotShare::Whole, high_name, sinks);
+            } else if low.is_empty() {
+                self.award(i, pot.amount, &high, PotShare::Scoop, high_name, sinks);
+            } else if high.len() == 1 && low == high {
+                let both = |seat: usize| Some(format!("{} and {}", high_name(seat)?, low_name(seat)?));
+                self.award(i, pot.amount, &high, PotShare::Scoop, both, sinks);
+            } else {
+                let low_half = pot.amount / 2;
+                self.award(i, pot.amount - low_half, &high, PotShare::High, high_name, sinks);
+                self.award(i, low_half, &low, PotShare::Low, low_name, sinks);
+            }
+        }
+    }
+
+    // Shares out chips between the winners, the odd chips to the first of them
+    fn award(&mut self, pot: usize, amount: i32, winners: &[usize], share: PotShare, hand: impl Fn(usize) -> Option<String>, sinks: &mut [Box<dyn EventSink>]) {
+        for (k, &seat) in winners.iter().enumerate() {
+            let mut chips = amount / winners.len() as i32;
+            if (k as i32) < amount % winners.len() as i32 {
+                chips += 1;
+            }
+            self.seats[seat].chips += chips;
+            emit(sinks, GameEvent::PotAwarded { pot, seat, amount: chips, hand: hand(seat), share });
+        }
+    }
+
+    // The best low a player can make, only in split pot games
+    fn low_hand(&self, hand: &HandState, seat: usize) -> Option<LowHand> {
+        match self.variant {
+            Variant::OmahaHiLo if hand.board.len() >= 3 => LowHand::omaha(&hand.hole_cards[seat], &hand.board),
+            _ => None,
+        }
+    }
+
+    fn best_hand(&self, hand: &HandState, seat: usize) -> Option<BestHand> {
+        let hole_cards = &hand.hole_cards[seat];
+        match self.variant {
+            Variant::Omaha | Variant::OmahaHiLo if hand.board.len() >= 3 => Some(BestHand::omaha(hole_cards, &hand.board)),
+            Variant::Holdem | Variant::ShortDeck if hole_cards.len() + hand.board.len() >= 5 => {
+                let mut cards = hole_cards.clone();
+                cards.extend_from_slice(&hand.board);
+                Some(self.variant.ranking().best_hand(&cards))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn emit(sinks: &mut [Box<dyn EventSink>], event: GameEvent) {
+    for sink in sinks.iter_mut() {
+        sink.handle(&event);
+    }
+}
+
+// A hand with a low is named by both, e.g. "Flush, Ace-high and 8-6-4-2-A low"
+fn emit_shown(sinks: &mut [Box<dyn EventSink>], seat: usize, cards: &[Card], hand: Option<&BestHand>, low: Option<&LowHand>) {
+    let mut description = hand.map(|h| h.description()).unwrap_or_default();
+    if let Some(low) = low {
+        description += &format!(" and {}", low.description());
+    }
+    emit(sinks, GameEvent::HandShown {
+        seat,
+        cards: cards.to_vec(),
+        best_five: hand.map(|h| h.cards.clone()).unwrap_or_default(),
+        hand: description,
+    });
+}
@@ -0,0 +1,245 @@
+use crate::card::{Card, CardSet};
+use crate::deck::Deck;
+use crate::eval::evaluate;
+use crate::range::Range;
+use rand::Rng;
+
+// Boards are counted one by one when there are at most this many left to deal, and sampled otherwise
+pub const EXACT_LIMIT: u64 = 200_000;
+pub const DEFAULT_SAMPLES: u64 = 100_000;
+// The confidence interval of sampled equities covers 95% of samples, 1.96 standard errors
+const Z_95: f64 = 1.96;
+// Sampling gives up when the players' ranges fail to fit together this many times in a row
+const MAX_REDRAWS: u32 = 100_000;
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * How a player's hand does over the boards dealt, as fractions of the boards. A tie shares the
+ * pot, so equity counts a two way tie as half a win. margin is how far the sampled equity may
+ * be from the true one with 95% confidence, 0 when every board was counted.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Equity {
+    pub win: f64,
+    pub tie: f64,
+    pub equity: f64,
+    pub margin: f64,
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * The equity of every player's hand, in the order the hands were given.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityReport {
+    pub players: Vec<Equity>,
+    // The deals of the hands and board counted or sampled, and whether they were all of them
+    pub boards: u64,
+    pub exact: bool,
+}
+
+// Wins, ties and shares of the pot over the deals, with the squared shares for the variance.
+// Each deal counts as much as its weight.
+#[derive(Default, Clone)]
+struct Tally {
+    weight: f64,
+    wins: f64,
+    ties: f64,
+    shares: f64,
+    squares: f64,
+}
+
+/*
+ * Reads cards written together or apart, e.g. "AhKh", "Ah Kh" or "Ah,Kh".
+ * The String type in Rust is a sequence of Unicode characters (Sebesta, 6.3).
+ */
+pub fn parse_cards(text: &str) -> Result<Vec<Card>, String> {
+    let letters: Vec<char> = text.chars().filter(|c| !c.is_whitespace() && *c != ',').collect();
+    if !letters.len().is_multiple_of(2) {
+        return Err(format!("{} is not a list of cards such as AhKh", text));
+    }
+    letters
+        .chunks(2)
+        .map(|pair| {
+            let code: String = pair.iter().collect();
+            Card::from_code(&code).ok_or(format!("{} is not a card", code))
+        })
+        .collect()
+}
+
+// The number of ways to choose k of n things
+fn choose(n: u64, k: u64) -> u64 {
+    (0..k).fold(1, |ways, i| ways * (n - i) / (i + 1))
+}
+
+/*
+ * The equity of two or more hands of two hole cards, on a board of zero to five cards, with the
+ * dead cards out of the deck. When few enough boards are left they are all dealt, otherwise
+ * samples random boards are dealt from the deck.
+ */
+pub fn calculate<R: Rng>(hands: &[Vec<Card>], board: &[Card], dead: &[Card], samples: u64, rng: &mut R) -> Result<EquityReport, String> {
+    if let Some(hand) = hands.iter().find(|hand| hand.len() != 2) {
+        return Err(format!("a hand has two hole cards, got {}", hand.len()));
+    }
+    let mut known = CardSet::new();
+    for &card in hands.iter().flatten().chain(board).chain(dead) {
+        if !known.insert(card) {
+            return Err(format!("{} is given more than once", card.code()));
+        }
+    }
+    let ranges: Vec<Range> = hands.iter().map(|hand| Range::from_hand(hand[0], hand[1])).collect();
+    calculate_ranges(&ranges, board, dead, samples, rng)
+}
+
+/*
+ * The equity of two or more ranges against each other, a hand being a range of one. Every
+ * player is dealt a hand of their range as often as its weight says, never one holding a card
+ * that is known or dealt to another player. When few enough deals are left they are all
+ * counted, weighted by the hands' weights, otherwise samples random deals are made.
+ */
+pub fn calculate_ranges<R: Rng>(ranges: &[Range], board: &[Card], dead: &[Card], samples: u64, rng: &mut R) -> Result<EquityReport, String> {
+    if ranges.len() < 2 {
+        return Err("equity needs the hands of at least two players".to_string());
+    }
+    if board.len() > 5 {
+        return Err(format!("a board has at most five cards, got {}", board.len()));
+    }
+    let mut known = CardSet::new();
+    for &card in board.iter().chain(dead) {
+        if !known.insert(card) {
+            return Err(format!("{} is given more than once", card.code()));
+        }
+    }
+    let missing = 5 - board.len();
+    if 52 - known.len() < 2 * ranges.len() + missing {
+        return Err("there are too few cards left to deal the board".to_string());
+    }
+    // Card removal: a hand holding a card on the board or out of the deck can't have been dealt
+    let mut combos = Vec::new();
+    for (player, range) in ranges.iter().enumerate() {
+        let left: Vec<(CardSet, f64)> = range.without(known).combos().map(|(combo, weight)| (combo.iter().copied().collect(), weight)).collect();
+        if left.is_empty() {
+            return Err(format!("player {} has no hand in their range that the known cards leave", player + 1));
+        }
+        combos.push(left);
+    }
+
+    let board: CardSet = board.iter().collect();
+    let mut tallies = vec![Tally::default(); ranges.len()];
+    let boards_per_deal = choose((52 - known.len() - 2 * ranges.len()) as u64, missing as u64);
+    let deals = combos.iter().fold(boards_per_deal, |deals, left| deals.saturating_mul(left.len() as u64));
+    let exact = deals <= EXACT_LIMIT;
+    let mut boards = 0;
+    if exact {
+        for_each_deal(&combos, known, &mut Vec::new(), 1.0, &mut |hands, dealt, weight| {
+            let mut deck = Deck::new();
+            deck.remove(dealt);
+            for_each_board(&deck.cards, missing, board, &mut |board| {
+                showdown(hands, board, weight, &mut tallies);
+                boards += 1;
+            });
+        });
+        if boards == 0 {
+            return Err("the players' hands can't all be dealt together".to_string());
+        }
+    } else {
+        // Hands are drawn by weight, and drawn again until no two players hold the same card
+        let totals: Vec<Vec<f64>> = combos
+            .iter()
+            .map(|left| left.iter().scan(0.0, |total, (_, weight)| {
+                *total += weight;
+                Some(*total)
+            }).collect())
+            .collect();
+        let mut hands = vec![CardSet::new(); ranges.len()];
+        while boards < samples {
+            let mut dealt = known;
+            let mut redraws = 0;
+            let mut player = 0;
+            while player < ranges.len() {
+                let totals = &totals[player];
+                let drawn = rng.gen::<f64>() * totals[totals.len() - 1];
+                let (hand, _) = combos[player][totals.partition_point(|&total| total <= drawn).min(totals.len() - 1)];
+                if hand.is_disjoint(dealt) {
+                    hands[player] = hand;
+                    dealt = dealt | hand;
+                    player += 1;
+                } else {
+                    (dealt, player) = (known, 0);
+                    redraws += 1;
+                    if redraws == MAX_REDRAWS {
+                        return Err("the players' hands can hardly ever be dealt together".to_string());
+                    }
+                }
+            }
+            let mut deck = Deck::new();
+            deck.remove(dealt);
+            deck.shuffle_with(rng);
+            let dealt: CardSet = deck.cards[..missing].iter().collect();
+            showdown(&hands, board | dealt, 1.0, &mut tallies);
+            boards += 1;
+        }
+    }
+
+    let players = tallies
+        .iter()
+        .map(|tally| {
+            let count = tally.weight.max(f64::MIN_POSITIVE);
+            let equity = tally.shares / count;
+            // The standard error of the mean share, nothing is left to chance when every deal was counted
+            let variance = (tally.squares / count - equity * equity).max(0.0);
+            let margin = if exact { 0.0 } else { Z_95 * (variance / count).sqrt() };
+            Equity { win: tally.wins / count, tie: tally.ties / count, equity, margin }
+        })
+        .collect();
+    Ok(EquityReport { players, boards, exact })
+}
+
+// Calls f with every way to deal each player a hand of their range, and its weight
+fn for_each_deal(combos: &[Vec<(CardSet, f64)>], dealt: CardSet, hands: &mut Vec<CardSet>, weight: f64, f: &mut impl FnMut(&[CardSet], CardSet, f64)) {
+    let Some(left) = combos.get(hands.len()) else {
+        f(hands, dealt, weight);
+        return;
+    };
+    for &(hand, hand_weight) in left {
+        if hand.is_disjoint(dealt) {
+            hands.push(hand);
+            for_each_deal(combos, dealt | hand, hands, weight * hand_weight, f);
+            hands.pop();
+        }
+    }
+}
+
+// Calls f with the board completed by every choice of the missing cards from the deck
+fn for_each_board(deck: &[Card], missing: usize, board: CardSet, f: &mut impl FnMut(CardSet)) {
+    if missing == 0 {
+        f(board);
+        return;
+    }
+    for (i, &card) in deck.iter().enumerate() {
+        let mut board = board;
+        board.insert(card);
+        for_each_board(&deck[i + 1..], missing - 1, board, f);
+    }
+}
+
+// Ranks every hand on a full board, the best hands share the pot
+fn showdown(hands: &[CardSet], board: CardSet, weight: f64, tallies: &mut [Tally]) {
+    let ranks: Vec<_> = hands.iter().map(|&hand| evaluate(hand | board)).collect();
+    let best = *ranks.iter().max().expect("there are at least two hands");
+    let winners = ranks.iter().filter(|&&rank| rank == best).count();
+    let share = 1.0 / winners as f64;
+    for (tally, &rank) in tallies.iter_mut().zip(&ranks) {
+        tally.weight += weight;
+        if rank != best {
+            continue;
+        }
+        if winners == 1 {
+            tally.wins += weight;
+        } else {
+            tally.ties += weight;
+        }
+        tally.shares += share * weight;
+        tally.squares += share * share * weight;
+    }
+}
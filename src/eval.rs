@@ -0,0 +1,503 @@
+use crate::card::{Card, CardSet, Rank};
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::sync::OnceLock;
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the possible hands in poker
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Hand {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+impl Hand {
+    pub const ALL: [Hand; 9] = [
+        Hand::HighCard, Hand::OnePair, Hand::TwoPair, Hand::ThreeOfAKind, Hand::Straight,
+        Hand::Flush, Hand::FullHouse, Hand::FourOfAKind, Hand::StraightFlush,
+    ];
+
+    // Name of the kind of hand, e.g. "Full house"
+    pub fn name(self) -> &'static str {
+        match self {
+            Hand::HighCard => "High card",
+            Hand::OnePair => "One pair",
+            Hand::TwoPair => "Two pair",
+            Hand::ThreeOfAKind => "Three of a kind",
+            Hand::Straight => "Straight",
+            Hand::Flush => "Flush",
+            Hand::FullHouse => "Full house",
+            Hand::FourOfAKind => "Four of a kind",
+            Hand::StraightFlush => "Straight flush",
+        }
+    }
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the ways hands are ranked, each with the deck it is played with. The standard
+ * ranking uses the whole deck. Short deck leaves out the Twos to Fives, so A-6-7-8-9 is the
+ * lowest straight, and a flush is harder to make than a full house and beats it.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub enum Ranking {
+    #[default]
+    Standard,
+    ShortDeck,
+}
+
+impl Ranking {
+    // The lowest rank in the deck, it also makes the lowest straight with the Ace
+    pub fn lowest_rank(self) -> Rank {
+        match self {
+            Ranking::Standard => Rank::Two,
+            Ranking::ShortDeck => Rank::Six,
+        }
+    }
+
+    // Where a kind of hand stands among the others, 0 for the weakest
+    fn strength(self, hand: Hand) -> u16 {
+        match (self, hand) {
+            (Ranking::ShortDeck, Hand::Flush) => Hand::FullHouse as u16,
+            (Ranking::ShortDeck, Hand::FullHouse) => Hand::Flush as u16,
+            _ => hand as u16,
+        }
+    }
+
+    pub fn evaluate(self, cards: CardSet) -> HandRank {
+        evaluator(self).evaluate(cards)
+    }
+
+    pub fn evaluate_cards(self, cards: &[Card]) -> HandRank {
+        self.evaluate(cards.iter().collect())
+    }
+
+    /*
+     * Finds the best five of five to seven cards.
+     * Panics if there are fewer than five or more than seven cards.
+     */
+    pub fn best_hand(self, cards: &[Card]) -> BestHand {
+        let rank = self.evaluate_cards(cards);
+        let mut best = None;
+        for_each_combination(cards, 5, &mut |five| {
+            if best.is_none() && self.evaluate_cards(five) == rank {
+                best = Some(five.to_vec());
+            }
+        });
+        let mut cards = best.expect("the best hand is one of the combinations");
+
+        // Order the cards by how many of their rank there are, then by rank
+        let count = |card: &Card, cards: &[Card]| cards.iter().filter(|c| c.rank == card.rank).count();
+        let counts: Vec<usize> = cards.iter().map(|card| count(card, &cards)).collect();
+        let mut order: Vec<usize> = (0..5).collect();
+        order.sort_by(|&a, &b| (counts[b], cards[b]).cmp(&(counts[a], cards[a])));
+        cards = order.iter().map(|&i| cards[i]).collect();
+
+        // In the lowest straight, A-2-3-4-5 or A-6-7-8-9, the Ace plays low
+        let fourth_lowest = Rank::ALL[self.lowest_rank() as usize + 3];
+        if matches!(rank.hand(), Hand::Straight | Hand::StraightFlush) && cards[0].rank == Rank::Ace && cards[1].rank == fourth_lowest {
+            cards.rotate_left(1);
+        }
+        BestHand { rank, cards }
+    }
+}
+
+/*
+ * The strength of a hand of five to seven cards.
+ * A higher value beats a lower value and equal values tie. Where the kind of hand stands in its
+ * ranking is kept in the top four bits of the value, so comparing two values compares kinds
+ * of hands first and kickers second. Only hands ranked the same way can be compared.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandRank {
+    value: u16,
+    hand: Hand,
+}
+
+impl HandRank {
+    // Lower than every hand, for a suit without five cards
+    const NONE: HandRank = HandRank { value: 0, hand: Hand::HighCard };
+
+    pub fn hand(self) -> Hand {
+        self.hand
+    }
+
+    pub fn value(self) -> u16 {
+        self.value
+    }
+}
+
+/*
+ * The best five card hand a player can make, with its strength.
+ * The cards are ordered the way the hand is read: the biggest group of ranks first, then
+ * the kickers, and straights from the high card down.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BestHand {
+    pub rank: HandRank,
+    pub cards: Vec<Card>,
+}
+
+impl BestHand {
+    // The best five of five to seven cards, ranked the standard way
+    pub fn new(cards: &[Card]) -> BestHand {
+        Ranking::Standard.best_hand(cards)
+    }
+
+    // The best Omaha hand: exactly two of the hole cards and exactly three of the board cards
+    pub fn omaha(hole_cards: &[Card], board: &[Card]) -> BestHand {
+        let (_, five) = best_omaha_five(hole_cards, board);
+        BestHand::new(&five)
+    }
+
+    // Name of the hand, e.g. "Full house, Kings full of Sevens" or "Flush, Ace-high"
+    pub fn description(&self) -> String {
+        let ranks: Vec<Rank> = self.cards.iter().map(|card| card.rank).collect();
+        match self.rank.hand() {
+            Hand::HighCard => format!("High card, {:?}", ranks[0]),
+            Hand::OnePair => format!("One pair, {}", ranks[0].plural()),
+            Hand::TwoPair => format!("Two pair, {} and {}", ranks[0].plural(), ranks[2].plural()),
+            Hand::ThreeOfAKind => format!("Three of a kind, {}", ranks[0].plural()),
+            Hand::Straight => format!("Straight, {:?}-high", ranks[0]),
+            Hand::Flush => format!("Flush, {:?}-high", ranks[0]),
+            Hand::FullHouse => format!("Full house, {} full of {}", ranks[0].plural(), ranks[3].plural()),
+            Hand::FourOfAKind => format!("Four of a kind, {}", ranks[0].plural()),
+            Hand::StraightFlush if ranks[0] == Rank::Ace => "Royal flush".to_string(),
+            Hand::StraightFlush => format!("Straight flush, {:?}-high", ranks[0]),
+        }
+    }
+}
+
+/*
+ * The strength of an ace-to-five low hand: Aces are low, and straights and flushes don't count.
+ * The five ranks are kept from the highest down with the Ace as 1, so a smaller value is a
+ * better low and 5-4-3-2-A is the best.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LowRank([u8; 5]);
+
+/*
+ * The best low a player can make, with the five cards that make it from the highest down.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowHand {
+    pub rank: LowRank,
+    pub cards: Vec<Card>,
+}
+
+impl LowHand {
+    /*
+     * The best Omaha low of Eight or better: exactly two of the hole cards and exactly three of
+     * the board cards, of five different ranks from Ace to Eight. None if no low qualifies.
+     */
+    pub fn omaha(hole_cards: &[Card], board: &[Card]) -> Option<LowHand> {
+        let mut best: Option<LowHand> = None;
+        for_each_combination(hole_cards, 2, &mut |two| {
+            for_each_combination(board, 3, &mut |three| {
+                let mut five = [two, three].concat();
+                five.sort_by_key(|card| std::cmp::Reverse(low_value(card.rank)));
+                let values: Vec<u8> = five.iter().map(|card| low_value(card.rank)).collect();
+                if values[0] > 8 || values.windows(2).any(|pair| pair[0] == pair[1]) {
+                    return;
+                }
+                let rank = LowRank([values[0], values[1], values[2], values[3], values[4]]);
+                if best.as_ref().is_none_or(|best| rank < best.rank) {
+                    best = Some(LowHand { rank, cards: five });
+                }
+            });
+        });
+        best
+    }
+
+    // Name of the low, e.g. "8-6-4-2-A low"
+    pub fn description(&self) -> String {
+        let ranks: Vec<String> = self.cards.iter().map(|card| card.rank.symbol().to_string()).collect();
+        format!("{} low", ranks.join("-"))
+    }
+}
+
+// The value of a rank in a low hand, the Ace counts as 1
+fn low_value(rank: Rank) -> u8 {
+    if rank == Rank::Ace {
+        1
+    } else {
+        rank as u8 + 2
+    }
+}
+
+// Each rank gets a prime, so the product of a hand's primes identifies its ranks in any order (Cactus Kev)
+const PRIMES: [u64; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/*
+ * Hasher for the prime products. The keys are already unique integers, so mixing them once
+ * is enough and much cheaper than the default SipHash.
+ */
+#[derive(Default)]
+struct ProductHasher(u64);
+
+impl Hasher for ProductHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_u64(self.0 ^ *byte as u64);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        let mixed = n.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        self.0 = mixed ^ (mixed >> 29);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/*
+ * Table-driven hand evaluator.
+ * Every distinct five card hand is ranked once when the tables are built. Six and seven card
+ * sets are stored as the best five card hand they contain, so an evaluation is one table
+ * lookup for flushes and one hash lookup for everything else.
+ */
+pub struct Evaluator {
+    // Best flush or straight flush for every 13-bit mask of ranks in one suit, NONE if fewer than five cards
+    flushes: Vec<HandRank>,
+    // Best non-flush hand for every multiset of five to seven ranks, keyed by its prime product
+    unsuited: HashMap<u64, HandRank, BuildHasherDefault<ProductHasher>>,
+}
+
+impl Default for Evaluator {
+    fn default() -> Evaluator {
+        Evaluator::new()
+    }
+}
+
+impl Evaluator {
+    pub fn new() -> Evaluator {
+        Evaluator::with_ranking(Ranking::Standard)
+    }
+
+    // Builds the tables for the ranks in the ranking's deck, ranking the hands its way
+    pub fn with_ranking(ranking: Ranking) -> Evaluator {
+        let lowest = ranking.lowest_rank() as usize;
+        // Collect the category and kickers of every distinct five card hand
+        let mut five_card_hands = Vec::new();
+        for_each_multiset(5, lowest, &mut |ranks| {
+            five_card_hands.push(classify(ranks, false, lowest));
+            if is_distinct(ranks) {
+                five_card_hands.push(classify(ranks, true, lowest));
+            }
+        });
+        five_card_hands.sort_by_key(|(hand, kickers)| (ranking.strength(*hand), kickers.clone()));
+        five_card_hands.dedup();
+
+        // Number the hands within each category, starting at 1 so that 0 never is a valid hand
+        let mut values = HashMap::new();
+        let mut index = 0;
+        for (i, key) in five_card_hands.iter().enumerate() {
+            if i == 0 || five_card_hands[i - 1].0 != key.0 {
+                index = 0;
+            }
+            index += 1;
+            values.insert(key.clone(), HandRank { value: (ranking.strength(key.0) << 12) | index, hand: key.0 });
+        }
+
+        // Flushes, the best five cards of every suit mask with at least five cards
+        let mut flushes = vec![HandRank::NONE; 1 << 13];
+        let in_deck = |mask: u16| mask.trailing_zeros() as usize >= lowest;
+        for mask in 0..(1u16 << 13) {
+            if mask.count_ones() == 5 && in_deck(mask) {
+                flushes[mask as usize] = values[&classify(&mask_ranks(mask), true, lowest)];
+            }
+        }
+        for mask in 0..(1u16 << 13) {
+            if mask.count_ones() > 5 && in_deck(mask) {
+                let mut best = HandRank::NONE;
+                let mut submask = mask;
+                while submask != 0 {
+                    if submask.count_ones() == 5 {
+                        best = best.max(flushes[submask as usize]);
+                    }
+                    submask = (submask - 1) & mask;
+                }
+                flushes[mask as usize] = best;
+            }
+        }
+
+        // Non-flush hands, the best five ranks of every multiset of five to seven ranks
+        let mut unsuited = HashMap::default();
+        for_each_multiset(5, lowest, &mut |ranks| {
+            unsuited.insert(product(ranks), values[&classify(ranks, false, lowest)]);
+        });
+        for size in 6..=7 {
+            let mut larger = Vec::new();
+            for_each_multiset(size, lowest, &mut |ranks| {
+                let mut best = HandRank::NONE;
+                for_each_combination(ranks, 5, &mut |five| {
+                    best = best.max(unsuited[&product(five)]);
+                });
+                larger.push((product(ranks), best));
+            });
+            unsuited.extend(larger);
+        }
+
+        Evaluator { flushes, unsuited }
+    }
+
+    /*
+     * Evaluates a set of five to seven cards.
+     * Panics if the set holds fewer than five or more than seven cards.
+     */
+    pub fn evaluate(&self, cards: CardSet) -> HandRank {
+        let mut suits = [0u16; 4];
+        let mut key = 1;
+        // Walk the set bits directly, card index = rank * 4 + suit
+        let mut bits = cards.bits();
+        while bits != 0 {
+            let index = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            suits[index % 4] |= 1 << (index / 4);
+            key *= PRIMES[index / 4];
+        }
+        let unsuited = *self.unsuited.get(&key).expect("hands must have five to seven cards");
+        let flush = suits.iter().map(|&mask| self.flushes[mask as usize]).max().unwrap_or(HandRank::NONE);
+        unsuited.max(flush)
+    }
+}
+
+// Tables shared by the whole program, built the first time a hand is ranked each way
+fn evaluator(ranking: Ranking) -> &'static Evaluator {
+    static STANDARD: OnceLock<Evaluator> = OnceLock::new();
+    static SHORT_DECK: OnceLock<Evaluator> = OnceLock::new();
+    let evaluator = match ranking {
+        Ranking::Standard => &STANDARD,
+        Ranking::ShortDeck => &SHORT_DECK,
+    };
+    evaluator.get_or_init(|| Evaluator::with_ranking(ranking))
+}
+
+pub fn evaluate(cards: CardSet) -> HandRank {
+    Ranking::Standard.evaluate(cards)
+}
+
+pub fn evaluate_cards(cards: &[Card]) -> HandRank {
+    Ranking::Standard.evaluate_cards(cards)
+}
+
+pub fn evaluate_omaha(hole_cards: &[Card], board: &[Card]) -> HandRank {
+    best_omaha_five(hole_cards, board).0
+}
+
+/*
+ * Tries every two of the hole cards with every three of the board cards, 60 hands with four
+ * hole cards and five on the board, and keeps the best.
+ * Panics with fewer than two hole cards or three board cards.
+ */
+fn best_omaha_five(hole_cards: &[Card], board: &[Card]) -> (HandRank, Vec<Card>) {
+    let mut best: Option<(HandRank, Vec<Card>)> = None;
+    for_each_combination(hole_cards, 2, &mut |two| {
+        for_each_combination(board, 3, &mut |three| {
+            let five = [two, three].concat();
+            let rank = evaluate_cards(&five);
+            if best.as_ref().is_none_or(|(best, _)| rank > *best) {
+                best = Some((rank, five));
+            }
+        });
+    });
+    best.expect("an Omaha hand needs two hole cards and three board cards")
+}
+
+/*
+ * Category and tie-breaking ranks of five ranks (0 = Two, 12 = Ace) in descending order.
+ * Kickers are ordered by how often the rank appears, then by rank, so comparing the
+ * tuples compares the hands. The Ace also plays below lowest, the lowest rank in the deck.
+ */
+fn classify(ranks: &[usize], flush: bool, lowest: usize) -> (Hand, Vec<usize>) {
+    let mut counts = [0; 13];
+    for &rank in ranks {
+        counts[rank] += 1;
+    }
+    let mut groups: Vec<(usize, usize)> = (0..13).rev().filter(|&r| counts[r] > 0).map(|r| (counts[r], r)).collect();
+    groups.sort_by(|a, b| b.cmp(a));
+    let kickers: Vec<usize> = groups.iter().map(|&(_, rank)| rank).collect();
+
+    let straight_high = if groups.len() == 5 && kickers[0] - kickers[4] == 4 {
+        Some(kickers[0])
+    } else if kickers == [12, lowest + 3, lowest + 2, lowest + 1, lowest] {
+        // A-2-3-4-5, or A-6-7-8-9 in a short deck, the fourth lowest rank is the high card
+        Some(lowest + 3)
+    } else {
+        None
+    };
+
+    let hand = match (groups[0].0, groups.get(1).map(|group| group.0)) {
+        _ if flush && straight_high.is_some() => Hand::StraightFlush,
+        (4, _) => Hand::FourOfAKind,
+        (3, Some(2)) => Hand::FullHouse,
+        _ if flush => Hand::Flush,
+        _ if straight_high.is_some() => Hand::Straight,
+        (3, _) => Hand::ThreeOfAKind,
+        (2, Some(2)) => Hand::TwoPair,
+        (2, _) => Hand::OnePair,
+        _ => Hand::HighCard,
+    };
+    match straight_high {
+        Some(high) if hand == Hand::Straight || hand == Hand::StraightFlush => (hand, vec![high]),
+        _ => (hand, kickers),
+    }
+}
+
+fn is_distinct(ranks: &[usize]) -> bool {
+    ranks.windows(2).all(|pair| pair[0] != pair[1])
+}
+
+fn product(ranks: &[usize]) -> u64 {
+    ranks.iter().map(|&rank| PRIMES[rank]).product()
+}
+
+// Ranks of a suit mask in descending order
+fn mask_ranks(mask: u16) -> Vec<usize> {
+    (0..13).rev().filter(|&rank| mask & (1 << rank) != 0).collect()
+}
+
+// Calls f with every multiset of `size` ranks from lowest up, at most four of each, in descending order
+fn for_each_multiset(size: usize, lowest: usize, f: &mut dyn FnMut(&[usize])) {
+    fn extend(ranks: &mut Vec<usize>, size: usize, lowest: usize, max_rank: usize, f: &mut dyn FnMut(&[usize])) {
+        if ranks.len() == size {
+            f(ranks);
+            return;
+        }
+        for rank in (lowest..=max_rank).rev() {
+            if ranks.iter().filter(|&&r| r == rank).count() < 4 {
+                ranks.push(rank);
+                extend(ranks, size, lowest, rank, f);
+                ranks.pop();
+            }
+        }
+    }
+    extend(&mut Vec::with_capacity(size), size, lowest, 12, f);
+}
+
+// Calls f with every combination of `size` items, keeping their order
+fn for_each_combination<T: Copy>(items: &[T], size: usize, f: &mut dyn FnMut(&[T])) {
+    fn extend<T: Copy>(items: &[T], start: usize, size: usize, chosen: &mut Vec<T>, f: &mut dyn FnMut(&[T])) {
+        if chosen.len() == size {
+            f(chosen);
+            return;
+        }
+        for i in start..items.len() {
+            chosen.push(items[i]);
+            extend(items, i + 1, size, chosen, f);
+            chosen.pop();
+        }
+    }
+    extend(items, 0, size, &mut Vec::with_capacity(size), f);
+}
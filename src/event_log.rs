@@ -0,0 +1,58 @@
+use crate::events::{EventSink, GameEvent};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/*
+ * Bumped whenever a change to the events could break a program reading the log. Version 2
+ * added the variant to hand_started, a reader of version 1 would take an Omaha hand for
+ * Hold'em. Version 3 added the share to pot_awarded, where a split pot is awarded in halves.
+ * Version 4 added the ante to hand_started, whose small blind is 0 in games with antes.
+ * Logs of older versions are still read, the fields they lack take their defaults.
+ */
+pub const SCHEMA_VERSION: u32 = 4;
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * One line of the event log: the schema version next to the fields of the event, e.g.
+ * {"schema":4,"event":"blind_posted","seat":1,"blind":"small","amount":5,"all_in":false}
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogRecord {
+    pub schema: u32,
+    #[serde(flatten)]
+    pub event: GameEvent,
+}
+
+/*
+ * Writes every game event as one JSON object per line (JSON Lines), for programs that
+ * follow the game. Unlike the terminal, the log holds every player's hole cards.
+ */
+pub struct EventLog<W: Write> {
+    out: W,
+}
+
+impl<W: Write> EventLog<W> {
+    pub fn new(out: W) -> EventLog<W> {
+        EventLog { out }
+    }
+}
+
+impl<W: Write> EventSink for EventLog<W> {
+    fn handle(&mut self, event: &GameEvent) {
+        let record = LogRecord { schema: SCHEMA_VERSION, event: event.clone() };
+        let line = serde_json::to_string(&record).expect("events always serialize");
+        // Flushed after every line so a reader sees each event as it happens
+        if let Err(error) = writeln!(self.out, "{}", line).and_then(|_| self.out.flush()) {
+            eprintln!("Could not write the event log: {}", error);
+        }
+    }
+}
+
+// Reads one line of an event log, checking it was written with a schema this version understands
+pub fn parse_line(line: &str) -> Result<GameEvent, String> {
+    let record: LogRecord = serde_json::from_str(line).map_err(|error| error.to_string())?;
+    if !(1..=SCHEMA_VERSION).contains(&record.schema) {
+        return Err(format!("unsupported schema version {}, expected at most {}", record.schema, SCHEMA_VERSION));
+    }
+    Ok(record.event)
+}
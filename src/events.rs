@@ -0,0 +1,269 @@
+use crate::card::{Card, Rank};
+use crate::eval::Ranking;
+use serde::{Deserialize, Serialize};
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the betting rounds of a hand
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the actions a player can take when it is their turn to bet.
+ * Raise holds the player's total bet for the round after raising, a raise with no bet
+ * to match is a bet.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    Raise(i32),
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * A seat as it is at the start of a hand.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeatInfo {
+    pub seat: usize,
+    pub name: String,
+    pub chips: i32,
+    // False for players without chips or sitting out, who are not dealt in
+    pub playing: bool,
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the betting structures: how much a player may bet or raise.
+ * No limit allows any bet up to all in, pot limit a raise up to the size of the pot, and
+ * fixed limit only bets of the big blind, or twice the big blind on the turn and river.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BettingStructure {
+    #[default]
+    #[serde(alias = "nl")]
+    NoLimit,
+    #[serde(alias = "pl")]
+    PotLimit,
+    #[serde(alias = "fl", alias = "limit")]
+    FixedLimit,
+}
+
+impl BettingStructure {
+    // Reads the structure's name, e.g. "pot-limit" or its short form "pl"
+    pub fn parse(name: &str) -> Option<BettingStructure> {
+        match name.to_ascii_lowercase().as_str() {
+            "no-limit" | "nl" => Some(BettingStructure::NoLimit),
+            "pot-limit" | "pl" => Some(BettingStructure::PotLimit),
+            "fixed-limit" | "fl" | "limit" => Some(BettingStructure::FixedLimit),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BettingStructure::NoLimit => "No Limit",
+            BettingStructure::PotLimit => "Pot Limit",
+            BettingStructure::FixedLimit => "Limit",
+        }
+    }
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the games that can be played. Hold'em deals two hole cards that make a hand
+ * with any of the board cards. Omaha deals four, and a hand is made of exactly two of them and
+ * exactly three board cards. Omaha Hi/Lo splits every pot between the best high hand and the
+ * best ace-to-five low of Eight or better, made the same way. Short deck is Hold'em with the
+ * Twos to Fives taken out of the deck, where every player antes and the button posts a blind.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Variant {
+    #[default]
+    Holdem,
+    #[serde(alias = "plo")]
+    Omaha,
+    #[serde(alias = "plo8")]
+    OmahaHiLo,
+    #[serde(alias = "6+")]
+    ShortDeck,
+}
+
+impl Variant {
+    // Reads the variant's name, e.g. "omaha" or its short form "plo"
+    pub fn parse(name: &str) -> Option<Variant> {
+        match name.to_ascii_lowercase().as_str() {
+            "holdem" | "hold'em" => Some(Variant::Holdem),
+            "omaha" | "plo" => Some(Variant::Omaha),
+            "omaha-hi-lo" | "plo8" | "omaha8" | "o8" => Some(Variant::OmahaHiLo),
+            "short-deck" | "6+" | "shortdeck" => Some(Variant::ShortDeck),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Holdem => "Hold'em",
+            Variant::Omaha => "Omaha",
+            Variant::OmahaHiLo => "Omaha Hi/Lo",
+            Variant::ShortDeck => "Short Deck Hold'em",
+        }
+    }
+
+    pub fn hole_cards(self) -> usize {
+        match self {
+            Variant::Holdem | Variant::ShortDeck => 2,
+            Variant::Omaha | Variant::OmahaHiLo => 4,
+        }
+    }
+
+    // How the hands are ranked, and so which deck they are dealt from
+    pub fn ranking(self) -> Ranking {
+        match self {
+            Variant::ShortDeck => Ranking::ShortDeck,
+            _ => Ranking::Standard,
+        }
+    }
+
+    // Whether every player posts an ante and the button the big blind as a button blind, instead of the blinds
+    pub fn antes(self) -> bool {
+        self == Variant::ShortDeck
+    }
+
+    // Whether the pots are split between a high and a low hand
+    pub fn split_pot(self) -> bool {
+        self == Variant::OmahaHiLo
+    }
+
+    // Omaha is played pot limit unless another structure is chosen
+    pub fn default_structure(self) -> BettingStructure {
+        match self {
+            Variant::Holdem | Variant::ShortDeck => BettingStructure::NoLimit,
+            Variant::Omaha | Variant::OmahaHiLo => BettingStructure::PotLimit,
+        }
+    }
+
+    // The most players the deck can deal to, keeping five board cards and three burnt cards
+    pub fn max_players(self) -> usize {
+        let deck = 4 * (Rank::ALL.len() - self.ranking().lowest_rank() as usize);
+        (deck - 8) / self.hole_cards()
+    }
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the parts of a pot a player can be awarded. A pot that isn't split is won
+ * whole. In a split pot game a player scoops the pot when they win both halves, or the high
+ * half when no low qualifies, and otherwise the high and low halves are won apart.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PotShare {
+    #[default]
+    Whole,
+    Scoop,
+    High,
+    Low,
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the forced bets posted before the cards are dealt. Games with antes have no
+ * small blind: every player posts the ante, which goes straight into the pot, and the button
+ * posts the big blind as a button blind.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Blind {
+    Small,
+    Big,
+    Ante,
+    Button,
+}
+
+impl Blind {
+    // Name of the blind, e.g. "small blind" or "ante"
+    pub fn name(self) -> &'static str {
+        match self {
+            Blind::Small => "small blind",
+            Blind::Big => "big blind",
+            Blind::Ante => "ante",
+            Blind::Button => "button blind",
+        }
+    }
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates everything that happens during a hand. The engine sends every event to
+ * each event sink, in order, so a sink can rebuild the whole hand from the events alone.
+ * HoleCards events are sent for every player; sinks that show the hand to a person must
+ * only show that person's cards.
+ * In JSON the variant is written as the "event" field, e.g. {"event": "hand_mucked", "seat": 2}.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    // small_blind is 0 in games with antes, where the button posts big_blind as a button blind
+    // and ante is what every player posts. Logs of schema 3 and before have no ante.
+    HandStarted {
+        hand_number: u32,
+        button: usize,
+        small_blind: i32,
+        big_blind: i32,
+        #[serde(default)]
+        ante: i32,
+        // Logs written before betting structures were added are no limit
+        #[serde(default)]
+        structure: BettingStructure,
+        // Logs of schema 1, written before Omaha was added, are Hold'em
+        #[serde(default)]
+        variant: Variant,
+        seats: Vec<SeatInfo>,
+    },
+    BlindPosted { seat: usize, blind: Blind, amount: i32, all_in: bool },
+    HoleCards { seat: usize, cards: Vec<Card> },
+    // Cards turned on a street, and the whole board after turning them
+    StreetDealt { street: Street, cards: Vec<Card>, board: Vec<Card> },
+    // amount is the chips put in by this action, bet the player's total bet for the round and
+    // raised_by how much the action raised the bet to match (equal to bet for an opening bet)
+    PlayerActed { seat: usize, action: Action, amount: i32, bet: i32, raised_by: i32, all_in: bool },
+    BettingRoundEnded { street: Street, pot: i32 },
+    UncalledBetReturned { seat: usize, amount: i32 },
+    ShowdownStarted,
+    // hand names the best five cards, e.g. "Full house, Kings full of Sevens"
+    HandShown { seat: usize, cards: Vec<Card>, best_five: Vec<Card>, hand: String },
+    HandMucked { seat: usize },
+    // pot 0 is the main pot, side pots follow in the order they were made. Logs of schema 2 and
+    // before, written before split pots were added, only have whole pots.
+    PotAwarded {
+        pot: usize,
+        seat: usize,
+        amount: i32,
+        hand: Option<String>,
+        #[serde(default)]
+        share: PotShare,
+    },
+    HandEnded { chips: Vec<i32> },
+}
+
+/*
+ * Receives the events of every hand as they happen, e.g. to print them or to write a
+ * hand history.
+ */
+pub trait EventSink {
+    fn handle(&mut self, event: &GameEvent);
+}
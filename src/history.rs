@@ -0,0 +1,319 @@
+use crate::card::Card;
+use crate::eval::{BestHand, Hand, Ranking};
+use crate::events::{Action, BettingStructure, Blind, EventSink, GameEvent, SeatInfo, Street};
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/*
+ * Writes every hand in the PokerStars hand history text format, which hand tracking software
+ * can import. The hand is collected from the engine's events and written when it ends.
+ */
+pub struct HandHistory<W: Write> {
+    out: W,
+    // The seat whose hole cards are shown, like the "Dealt to" line of the player's own history
+    hero: usize,
+    table_name: String,
+    // Hand numbers start from the session's start time so they are unique across sessions
+    first_hand_id: u64,
+    lines: Vec<String>,
+    seats: Vec<SeatInfo>,
+    button: usize,
+    // How the game being played ranks its hands
+    ranking: Ranking,
+    blinds: Vec<Option<Blind>>,
+    hole_cards: Vec<Vec<Card>>,
+    board: Vec<Card>,
+    street: Street,
+    put_in_chips: Vec<bool>,
+    folded_on: Vec<Option<Street>>,
+    shown: Vec<Option<(Vec<Card>, String)>>,
+    mucked: Vec<bool>,
+    showdown: bool,
+    // Lines about hands that were not part of a showdown, written after the pot is collected
+    uncontested_lines: Vec<String>,
+    // (pot, seat, amount) for every share of a pot that was won
+    awards: Vec<(usize, usize, i32)>,
+}
+
+impl<W: Write> HandHistory<W> {
+    pub fn new(out: W, hero: usize, table_name: &str) -> HandHistory<W> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+        HandHistory {
+            out,
+            hero,
+            table_name: table_name.to_string(),
+            first_hand_id: now * 1_000_000,
+            lines: Vec::new(),
+            seats: Vec::new(),
+            button: 0,
+            ranking: Ranking::Standard,
+            blinds: Vec::new(),
+            hole_cards: Vec::new(),
+            board: Vec::new(),
+            street: Street::Preflop,
+            put_in_chips: Vec::new(),
+            folded_on: Vec::new(),
+            shown: Vec::new(),
+            mucked: Vec::new(),
+            showdown: false,
+            uncontested_lines: Vec::new(),
+            awards: Vec::new(),
+        }
+    }
+
+    fn name(&self, seat: usize) -> &str {
+        &self.seats[seat].name
+    }
+
+    // Name of a pot in the collected and summary lines
+    fn pot_name(pot: usize, pots: usize) -> String {
+        match (pot, pots) {
+            (_, 1) => "pot".to_string(),
+            (0, _) => "main pot".to_string(),
+            (_, 2) => "side pot".to_string(),
+            _ => format!("side pot-{}", pot),
+        }
+    }
+
+    fn finish_hand(&mut self) {
+        let pots = self.awards.iter().map(|&(pot, _, _)| pot + 1).max().unwrap_or(0);
+        for &(pot, seat, amount) in self.awards.iter() {
+            self.lines.push(format!("{} collected {} from {}", self.name(seat), amount, Self::pot_name(pot, pots)));
+        }
+        self.lines.append(&mut self.uncontested_lines);
+
+        self.lines.push("*** SUMMARY ***".to_string());
+        let mut pot_sizes = vec![0; pots];
+        for &(pot, _, amount) in self.awards.iter() {
+            pot_sizes[pot] += amount;
+        }
+        let total: i32 = pot_sizes.iter().sum();
+        let mut pot_line = format!("Total pot {}", total);
+        if pots > 1 {
+            for (pot, size) in pot_sizes.iter().enumerate() {
+                let name = Self::pot_name(pot, pots);
+                pot_line += &format!(" {}{} {}.", name[..1].to_uppercase(), &name[1..], size);
+            }
+        }
+        self.lines.push(pot_line + " | Rake 0");
+        if !self.board.is_empty() {
+            self.lines.push(format!("Board [{}]", codes(&self.board)));
+        }
+
+        for seat in 0..self.seats.len() {
+            if !self.seats[seat].playing {
+                continue;
+            }
+            let mut line = format!("Seat {}: {}", seat + 1, self.name(seat));
+            if seat == self.button {
+                line += " (button)";
+            }
+            match self.blinds[seat] {
+                Some(Blind::Ante) | None => {}
+                Some(blind) => line += &format!(" ({})", blind.name()),
+            }
+            let won: i32 = self.awards.iter().filter(|award| award.1 == seat).map(|award| award.2).sum();
+            if let Some(street) = self.folded_on[seat] {
+                line += &match street {
+                    Street::Preflop => " folded before Flop".to_string(),
+                    street => format!(" folded on the {}", street_name(street)),
+                };
+                if !self.put_in_chips[seat] {
+                    line += " (didn't bet)";
+                }
+            } else if let Some((cards, hand)) = &self.shown[seat] {
+                line += &format!(" showed [{}] and", codes(cards));
+                line += &if won > 0 { format!(" won ({})", won) } else { " lost".to_string() };
+                if !hand.is_empty() {
+                    line += &format!(" with {}", hand);
+                }
+            } else if self.mucked[seat] && won > 0 {
+                line += &format!(" collected ({})", won);
+            } else if self.mucked[seat] {
+                line += " mucked";
+                if seat == self.hero {
+                    line += &format!(" [{}]", codes(&self.hole_cards[seat]));
+                }
+            }
+            self.lines.push(line);
+        }
+
+        let mut text = self.lines.join("\n");
+        text += "\n\n\n";
+        if let Err(error) = self.out.write_all(text.as_bytes()).and_then(|_| self.out.flush()) {
+            eprintln!("Could not write the hand history: {}", error);
+        }
+        self.lines.clear();
+    }
+}
+
+impl<W: Write> EventSink for HandHistory<W> {
+    fn handle(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::HandStarted { hand_number, button, small_blind, big_blind, ante, structure, variant, seats } => {
+                let n = seats.len();
+                self.seats = seats.clone();
+                self.button = *button;
+                self.ranking = variant.ranking();
+                self.blinds = vec![None; n];
+                self.hole_cards = vec![Vec::new(); n];
+                self.board.clear();
+                self.street = Street::Preflop;
+                self.put_in_chips = vec![false; n];
+                self.folded_on = vec![None; n];
+                self.shown = vec![None; n];
+                self.mucked = vec![false; n];
+                self.showdown = false;
+                self.awards.clear();
+
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+                // Limit games are named by their small and big bet instead of the blinds, and games
+                // with antes by the button blind and the ante
+                let stakes = match structure {
+                    BettingStructure::FixedLimit => format!("{}/{}", big_blind, 2 * big_blind),
+                    _ if variant.antes() => format!("Button Blind {} - Ante {}", big_blind, ante),
+                    _ => format!("{}/{}", small_blind, big_blind),
+                };
+                self.lines.push(format!(
+                    "PokerStars Hand #{}: {} {} ({}) - {}",
+                    self.first_hand_id + *hand_number as u64, variant.name(), structure.name(), stakes, timestamp(now)
+                ));
+                self.lines.push(format!("Table '{}' {}-max Seat #{} is the button", self.table_name, n, button + 1));
+                for seat in seats.iter().filter(|seat| seat.playing) {
+                    self.lines.push(format!("Seat {}: {} ({} in chips)", seat.seat + 1, seat.name, seat.chips));
+                }
+            }
+            GameEvent::BlindPosted { seat, blind, amount, all_in } => {
+                self.blinds[*seat] = Some(*blind);
+                self.put_in_chips[*seat] = true;
+                // PokerStars writes "posts the ante 5" but "posts small blind 5"
+                let blind = if *blind == Blind::Ante { "the ante" } else { blind.name() };
+                self.lines.push(format!("{}: posts {} {}{}", self.name(*seat), blind, amount, all_in_suffix(*all_in)));
+            }
+            GameEvent::HoleCards { seat, cards } => {
+                if self.hole_cards.iter().all(|cards| cards.is_empty()) {
+                    self.lines.push("*** HOLE CARDS ***".to_string());
+                }
+                self.hole_cards[*seat] = cards.clone();
+                if *seat == self.hero {
+                    self.lines.push(format!("Dealt to {} [{}]", self.name(*seat), codes(cards)));
+                }
+            }
+            GameEvent::StreetDealt { street, cards, board } => {
+                self.street = *street;
+                self.board = board.clone();
+                let header = format!("*** {} ***", street_name(*street).to_uppercase());
+                if *street == Street::Flop {
+                    self.lines.push(format!("{} [{}]", header, codes(board)));
+                } else {
+                    let before = &board[..board.len() - cards.len()];
+                    self.lines.push(format!("{} [{}] [{}]", header, codes(before), codes(cards)));
+                }
+            }
+            GameEvent::PlayerActed { seat, action, amount, bet, raised_by, all_in } => {
+                let text = match action {
+                    Action::Fold => {
+                        self.folded_on[*seat] = Some(self.street);
+                        "folds".to_string()
+                    }
+                    Action::Check => "checks".to_string(),
+                    Action::Call => format!("calls {}", amount),
+                    Action::Raise(_) if raised_by == bet => format!("bets {}", bet),
+                    Action::Raise(_) => format!("raises {} to {}", raised_by, bet),
+                };
+                if *amount > 0 {
+                    self.put_in_chips[*seat] = true;
+                }
+                self.lines.push(format!("{}: {}{}", self.name(*seat), text, all_in_suffix(*all_in)));
+            }
+            GameEvent::BettingRoundEnded { .. } => {}
+            GameEvent::UncalledBetReturned { seat, amount } => {
+                self.lines.push(format!("Uncalled bet ({}) returned to {}", amount, self.name(*seat)));
+            }
+            GameEvent::ShowdownStarted => {
+                self.showdown = true;
+                self.lines.push("*** SHOW DOWN ***".to_string());
+            }
+            GameEvent::HandShown { seat, cards, best_five, .. } => {
+                let description = if best_five.len() == 5 { describe(&self.ranking.best_hand(best_five)) } else { String::new() };
+                let mut line = format!("{}: shows [{}]", self.name(*seat), codes(cards));
+                if !description.is_empty() {
+                    line += &format!(" ({})", description);
+                }
+                if self.showdown {
+                    self.lines.push(line);
+                } else {
+                    self.uncontested_lines.push(line);
+                }
+                self.shown[*seat] = Some((cards.clone(), description));
+            }
+            GameEvent::HandMucked { seat } => {
+                self.mucked[*seat] = true;
+                if self.showdown {
+                    self.lines.push(format!("{}: mucks hand", self.name(*seat)));
+                } else {
+                    self.uncontested_lines.push(format!("{}: doesn't show hand", self.name(*seat)));
+                }
+            }
+            GameEvent::PotAwarded { pot, seat, amount, .. } => {
+                self.awards.push((*pot, *seat, *amount));
+            }
+            GameEvent::HandEnded { .. } => self.finish_hand(),
+        }
+    }
+}
+
+fn all_in_suffix(all_in: bool) -> &'static str {
+    if all_in {
+        " and is all-in"
+    } else {
+        ""
+    }
+}
+
+fn street_name(street: Street) -> &'static str {
+    match street {
+        Street::Preflop => "Preflop",
+        Street::Flop => "Flop",
+        Street::Turn => "Turn",
+        Street::River => "River",
+    }
+}
+
+// Cards in short notation separated by spaces, e.g. "Ah Kd"
+fn codes(cards: &[Card]) -> String {
+    cards.iter().map(|card| card.code()).collect::<Vec<_>>().join(" ")
+}
+
+// Name of a hand the way PokerStars writes it, e.g. "a full house, Kings full of Sevens"
+fn describe(hand: &BestHand) -> String {
+    let ranks: Vec<_> = hand.cards.iter().map(|card| card.rank).collect();
+    match hand.rank.hand() {
+        Hand::HighCard => format!("high card {:?}", ranks[0]),
+        Hand::OnePair => format!("a pair of {}", ranks[0].plural()),
+        Hand::TwoPair => format!("two pair, {} and {}", ranks[0].plural(), ranks[2].plural()),
+        Hand::ThreeOfAKind => format!("three of a kind, {}", ranks[0].plural()),
+        Hand::Straight => format!("a straight, {:?} to {:?}", ranks[4], ranks[0]),
+        Hand::Flush => format!("a flush, {:?} high", ranks[0]),
+        Hand::FullHouse => format!("a full house, {} full of {}", ranks[0].plural(), ranks[3].plural()),
+        Hand::FourOfAKind => format!("four of a kind, {}", ranks[0].plural()),
+        Hand::StraightFlush if ranks[4] == crate::card::Rank::Ten => "a Royal Flush".to_string(),
+        Hand::StraightFlush => format!("a straight flush, {:?} to {:?}", ranks[4], ranks[0]),
+    }
+}
+
+// Date and time in UTC, e.g. "2024/05/01 18:30:00 UTC"
+fn timestamp(seconds: u64) -> String {
+    // Days to civil date (Howard Hinnant's algorithm)
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    let time = seconds % 86400;
+    format!("{}/{:02}/{:02} {:02}:{:02}:{:02} UTC", year, month, day, time / 3600, time % 3600 / 60, time % 60)
+}
@@ -0,0 +1,23 @@
+pub mod bot;
+pub mod card;
+pub mod client;
+pub mod clock;
+pub mod config;
+pub mod deck;
+pub mod engine;
+pub mod equity;
+pub mod eval;
+pub mod event_log;
+pub mod events;
+pub mod history;
+pub mod odds;
+pub mod profiles;
+pub mod protocol;
+pub mod range;
+pub mod replay;
+pub mod server;
+pub mod session;
+pub mod spectators;
+pub mod stats;
+pub mod summary;
+pub mod web;
@@ -1,650 +1,367 @@
-mod card;
-mod deck;
+mod calculator;
+mod remote;
+mod replayer;
+mod terminal;
+mod tui;
 
-use deck::Deck;
-use card::Card;
-use rand::Rng;
-use std::io;
 use colored::Colorize;
-
-/*
- * This is an enumeration type (Sebesta, 6.4)
- * It enumerates the possible hands in poker
- */
-#[derive(Clone, Debug)]
-enum Hand {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeOfAKind,
-    Straight,
-    Flush,
-    FullHouse,
-    FourOfAKind,
-    StraightFlush,
-}
-
-/*
- * The struct type in Rust is a record type (Sebesta, 6.7)
- * It is an aggregate data type with two elements named cards and chips.
- */
-#[derive(Clone)]
-struct Player {    
-    cards: Vec<Card>,
-    chips: i32,
-}
-
-/*
- * The struct type in Rust is a record type (Sebesta, 6.7)
- * It is an aggregate data type with three elements named hand, rank_score, and suit_score.
- */
-#[derive(Debug)]
-struct HandScore {
-    hand: Hand,
-    rank_score: usize,
-    suit_score: usize,
-}
-
-impl HandScore {
-    fn new(hand: Hand, rank_score: usize, suit_score: usize) -> HandScore {
-        HandScore { hand, rank_score, suit_score }
-    }
-}
-
-impl Player {
-    fn new(starting_chips: i32) -> Player {
-        Player { cards: Vec::new(), chips: starting_chips }
-    }
-
-    fn add(&mut self, card: Card) {
-        self.cards.push(card);
-    }
-
-    fn update_chips(&mut self, amount: i32) {
-        self.chips += amount;
-    }
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::str::FromStr;
+use terminal::{read_line, TerminalDisplay, TerminalPlayer};
+use tui::{Tui, TuiDisplay, TuiPlayer};
+use texas_holdem::bot::BotKind;
+use texas_holdem::clock::Timed;
+use texas_holdem::config::{self, Config, Options, USAGE};
+use texas_holdem::engine::{Controller, Seat, Table};
+use texas_holdem::event_log::EventLog;
+use texas_holdem::events::EventSink;
+use texas_holdem::history::HandHistory;
+use texas_holdem::odds::OddsHelper;
+use texas_holdem::profiles::{Finish, Profiles};
+use texas_holdem::session::Session;
+use texas_holdem::stats::SharedStats;
+use texas_holdem::summary::{SessionSummary, SharedSummary, CHART_HEIGHT, CHART_WIDTH};
+
+// Every hand is appended to this file in the PokerStars hand history format
+const HAND_HISTORY_FILE: &str = "hand_history.txt";
+// The game is saved here after every hand, and continued from here with --resume
+const SESSION_FILE: &str = "session.json";
+// Every player's results over all their games
+const PROFILES_FILE: &str = "profiles.json";
+// Printed when the user quits full screen in the middle of a hand
+const LEAVE_MESSAGE: &str = "Left in the middle of a hand, the game carries on from the last finished hand with --resume";
+
+// Runs a subcommand with the arguments after its name
+type Subcommand = fn(&[String]) -> Result<(), String>;
+
+// Prints what is wrong with the options and quits
+fn exit_with(error: &str) -> ! {
+    eprintln!("{}", error);
+    eprintln!("Run with --help to see the options");
+    std::process::exit(2);
 }
 
 /*
- * The parameter players is a reference to a vector of Player structs. It is a reference type (Sebesta, 6.11).
+ * Asks a question until the answer is valid. An empty answer is the default.
+ * The String type in Rust is a sequence of Unicode characters (Sebesta, 6.3). It is not a primitve data type and has dynamic length (Sebesta, 6.3.1).
  */
-fn find_winning_hand(players: &Vec<Player>, community_cards: &Vec<Card>, bets: &Vec<i32>) -> usize {
-    /*
-        This function is used to find the score of a player's hand. It takes a vector of cards as input and returns a HandScore struct.
-     */
-    fn hand_score(hand: &Vec<Card>) -> HandScore {
-        let mut current_hand = Hand::HighCard;
-
-        let mut ranks = vec![0; 13];
-        let mut suits = vec![0; 4];
-
-        // Marks the ranks and suits of the cards
-        for card in hand.iter() {
-            ranks[card.rank.clone() as usize] += 1;
-            suits[card.suit.clone() as usize] += 1;
-        }
-
-        // Checks for 5 or more of one suit
-        let is_flush = suits.iter().any(|&count| count >= 5);
-        // Checks for 5 or more consecutive ranks
-        let is_straight = ranks.windows(5).any(|window| window.iter().all(|&count| count > 0));
-
-        if is_flush {
-            current_hand = Hand::Flush; 
-        }
-
-        if is_straight {
-            current_hand = Hand::Straight; 
-        }
-
-        // Checks for a straight flush
-        if is_flush && is_straight {
-            current_hand = Hand::StraightFlush;
-        }
-
-        // Checks for four of a kind
-        if ranks.iter().any(|&count| count == 4) {
-            current_hand = Hand::FourOfAKind;
-        }
-
-        // Checks for a full house
-
-        // Finds the rank of the triple
-        let mut triple_rank = ranks.iter().position(|&count| count == 3);
-        if ranks.iter().filter(|&&count| count == 3).count() == 2 {
-            triple_rank = ranks.iter().skip(triple_rank.unwrap() + 1).position(|&count| count == 3);
-        }
-        if triple_rank.is_some() {
-            // first pair
-            let mut pair_rank = ranks.iter().position(|&count| count == 2);
-            // Deals with case of two pairs
-            if ranks.iter().filter(|&&count| count == 2).count() == 2 {
-                if ranks.iter().skip(pair_rank.unwrap() + 1).position(|&count| count == 2) != triple_rank {
-                    pair_rank = ranks.iter().skip(pair_rank.unwrap() + 1).position(|&count| count == 2);
-                }
-            }
-            // Deals with case of three pairs
-            if ranks.iter().filter(|&&count| count == 2).count() == 3 {
-                // second pair
-                let mut next_pair_rank = ranks.iter().skip(pair_rank.unwrap() + 1).position(|&count| count == 2);
-                // second highest pair is no the same as the triple
-                if next_pair_rank != triple_rank {
-                    pair_rank = next_pair_rank;
-                    
-                } 
-                // third pair
-                next_pair_rank = ranks.iter().skip(next_pair_rank.unwrap() + 1).position(|&count| count == 2);
-                // third pair is not the same as the triple
-                if next_pair_rank != triple_rank {
-                    pair_rank = next_pair_rank;
-                }
-                
-            }
-            if pair_rank.is_some() {
-                current_hand = Hand::FullHouse;
-            }
-        }
-
-        
-        // Checks for three of a kind
-        if triple_rank.is_some() {
-            current_hand = Hand::ThreeOfAKind;
+fn ask<T: FromStr + Copy + std::fmt::Display>(question: &str, default: T, check: impl Fn(T) -> Result<(), String>) -> T {
+    loop {
+        println!("{}", question);
+        let input = read_line();
+        if input.trim().is_empty() {
+            println!("Using the default of {}\n", default);
+            return default;
         }
-        //  Checks for two pair 
-        if ranks.iter().filter(|&&count| count == 2).count() == 2 {
-            current_hand = Hand::TwoPair;
+        match input.trim().parse::<T>() {
+            Ok(value) => match check(value) {
+                Ok(()) => return value,
+                Err(error) => println!("Invalid value, {}", error),
+            },
+            Err(_) => println!("Please enter a number"),
         }
-
-        if ranks.iter().any(|&count| count == 2) {
-            current_hand = Hand::OnePair;
-        }
-        let mut rank_score = 0;
-        // Adds points for the ranks of the cards (high cards)
-        for (i, rank) in ranks.iter().enumerate() {
-            rank_score += i * rank;
-        }
-        // Adds points for the suits of the cards
-        let mut suit_score = 0;
-        for (i, suit) in suits.iter().enumerate() {
-            suit_score += i * suit;
-        }
-        HandScore::new(current_hand, rank_score, suit_score)
     }
+}
 
-    // Finds the score of each player's hand
-    let mut hand_scores = Vec::new();
-    for player in players.iter() {
-        let mut all_cards = player.cards.clone();
-        all_cards.extend_from_slice(community_cards);
-        hand_scores.push(hand_score(&all_cards));
+// Asks who is playing: one of the profiles kept so far, or a new name for a new profile
+fn choose_profile(profiles: &Profiles) -> String {
+    let names: Vec<&str> = profiles.profiles.iter().map(|profile| profile.name.as_str()).collect();
+    match names.first() {
+        Some(first) => println!("Who is playing? {} or a new name (default is {})", names.join(", "), first),
+        None => println!("What is your name? Your results are kept over every game you play"),
     }
-
-    let mut tie = true;
-    // sets initial score type to check
-    // 0: check score by poker hand
-    // 1: check score by card ranks (high card)
-    // 2: check score by card suits (highest suit)
-
-    let mut current_score_type = 0;
-    let mut winning_players = Vec::new();
-    let mut current_players = Vec::new();
-        for (i, _player) in players.iter().enumerate() {
-            // Skip players who have folded
-            if bets[i] != -1 {
-                current_players.push(i);
-            }
-        }
-
-    // Repeat until single winner found
-    while tie {
-        let mut best_hand = 0;
-        
-        // Finds the best hand and the winning player for a score type
-        for player in current_players.iter() {
-            let mut score = hand_scores[*player].hand.clone() as usize;
-            if current_score_type == 1 {
-                score = hand_scores[*player].rank_score;
-            } else if current_score_type == 2 {
-                score = hand_scores[*player].suit_score;
-            }
-            if score > best_hand {
-                best_hand = score;
-                winning_players = vec![*player];
-                tie = false;
-            } else if score == best_hand {
-                tie = true;
-                winning_players.push(*player);
-            }
-        }
-        // If multiple players have the same score, move to the next score type to check
-        if tie {
-            current_score_type += 1;
-            current_players = winning_players.clone();
+    loop {
+        let input = read_line();
+        let name = input.trim();
+        if let Some(profile) = profiles.get(name) {
+            return profile.name.clone();
         }
-    }
-    if winning_players.len() > 1 {
-        println!("Error, tie between players:");
-        for player in winning_players.iter() {
-            println!("{}", player);
+        match names.first() {
+            Some(first) if name.is_empty() => return first.to_string(),
+            _ if name.is_empty() => println!("Please enter a name"),
+            _ => return name.to_string(),
         }
     }
-
-    winning_players[0]
 }
 
-/*
-    This function is used to get the bets from the players. It cycles through the players and asks them to bet or fold. The function returns a vector of the bets made by each player.
-*/
-fn get_bets(players: &mut Vec<Player>, starting_better: usize, bets: &mut Vec<i32>, pot: &mut i32, community_cards: & Vec<Card>) -> Vec<i32> {
-    let mut current_better = starting_better;
-    let mut current_bet = 0;
-    let mut all_bets_in = false;
-    let mut players_visited = 0;
-    while !all_bets_in || players_visited < players.len() {
-        
-        // Skip players who have folded
-        if bets[current_better] == -1 {
-            // Move to the next player
-            current_better = (current_better + 1) % players.len();
-            // Check if all bets are in
-            for bet in bets.iter() {
-                if *bet != -1 && *bet != current_bet {
-                    all_bets_in = false;
-                    break;
-                }
-                all_bets_in = true;
-            }
-            players_visited += 1;
-            continue;
-        }
-
-        // User's turn to bet
-        if current_better == 0 {
-            
-            // Print the current bets of all players
-            if starting_better != 0 || players_visited > 0 {
-                println!("\nCurrent bets:");
-                println!("{}", "----------------------------".blue());
-                for (i, bet) in bets.iter().enumerate() {
-                    if *bet == -1 { // Player has folded
-                        println!("{:<27}{}", format!("Player {} has folded", i + 1), "|".blue());
-                    } else if *bet == -2 { // Player has not bet yet
-                        if i == 0 {
-                            println!("{:<27}{}", format!("You have not bet yet"), "|".blue());
-                        } else {
-                            println!("{:<27}{}", format!("Player {} has not bet yet", i + 1), "|".blue());
-                        }
-                    } else {
-                        if i == 0 {
-                            println!("{:<27}{}", format!("Your current bet: {}", bet), "|".blue());
-                        } else {
-                            println!("{:<27}{}", format!("Player {}'s current bet: {}", i + 1, bet), "|".blue());
-                        }
-                    }
-                }
-                println!("{}", "----------------------------".blue());
-                println!("It's your turn to bet");
-            } else { // User is the first to bet, no need to show current bets
-                println!("\nYou are betting first");
-            }
-            println!("{}", "\n+==================+".bold().red());
-            println!("{}{:^18}{}", "|".bold().red(), "Your Cards:", "|".bold().red());
-            println!("{}{}{}", "+".red().bold(), "------------------".white(), "+".red().bold());
-            for card in players[0].cards.iter() {
-                println!("{}{:^18}{}", "|".bold().red(), format!("{:?} of {:?}", card.rank, card.suit).bold(), "|".bold().red());
-            }
-            if community_cards.len() > 0 {
-                println!("{}", "+==================+".bold().red());
-                println!("{}{:^18}{}", "|".bold().red(), "Community Cards:", "|".bold().red());
-                println!("{}{}{}", "+".red().bold(), "------------------".white(), "+".red().bold());
-                for card in community_cards.iter() {
-                    println!("{}{:^18}{}", "|".bold().red(), format!("{:?} of {:?}", card.rank, card.suit).bold(), "|".bold().red());
-                }
-            }
-            println!("{}", "+==================+".bold().red());
-            println!("{}{:^18}{}", "|".bold().red(), format!("Your chips: {}", players[current_better].chips), "|".bold().red());
-            println!("{}", "+==================+\n".bold().red());
-            println!("{}", "--------------------------------------------------".bold().white());
-            let mut valid_bet = false;
-            while !valid_bet {
-                println!("The current bet is: {}", format!("{}", current_bet).bold());
-                println!("Enter your bet (-1 to fold): ");
+// Sets up a new game from the config, asking the user for the settings it doesn't give
+fn new_game(config: &Config, profile: &str) -> (Table, bool) {
+    /*
+     * This is a primitive data type (Sebesta, 6.2)
+     * In Rust, the type of num_of_players is inferred from the context. In this case the type will be set to usize, an unsigned integer the size of a pointer (Sebesta, 6.2.1.1).
+     */
+    let num_of_players = config.players.unwrap_or_else(|| {
+        let default = 5.max(config.bot_count() + 1);
+        let max = config.variant().max_players().min(config::MAX_PLAYERS);
+        let question = format!("How many players are playing? [2-{}] (default is {})", max, default);
+        ask(&question, default, |players| config::check_players(players, config.bot_count(), config.variant()))
+    });
+    // Starting chips for each player
+    let starting_chips = config.chips.unwrap_or_else(|| {
+        let question = format!("How many chips does each player start with? [>={}] (default is 1000)", config::MIN_CHIPS);
+        ask(&question, 1000, config::check_chips)
+    });
 
-                // Read the bet from the user   
-                let mut bet = String::new();
-                io::stdin().read_line(&mut bet).expect("Failed to read line");
-                let bet: i32 = bet.trim().parse().expect("Please enter a number");
+    // Let user choose whether their hand is always shown at showdown, even when it can't win
+    let always_show = config.display.always_show.unwrap_or_else(|| {
+        println!("Always show your hand at showdown? ({}/{}) (default is n)", "y".bold().green(), "n".bold().red());
+        read_line().trim() == "y"
+    });
 
-                // check if the bet is valid
-                if bet == -1 {
-                    println!("You fold");
-                    bets[current_better] = -1;
-                    valid_bet = true;
-                } else if bet < -1 {
-                    println!("Please enter a valid bet");
-                } else if bet > players[current_better].chips {
-                    println!("You don't have enough chips to bet that amount, please enter a valid bet");
-                } else if bet < current_bet {
-                    println!("You must bet at least the current bet {}, please enter a valid bet", current_bet);
-                } else {
-                    // Update the current bet, the player's chips, the pot, and the bets
-                    current_bet = bet;
-                    players[current_better].update_chips(-bet);
-                    *pot += bet;
-                    bets[current_better] = bet;
-                    valid_bet = true;
-                
-                }
-            }
-        } else {
-            // Computer's turn to bet
-            let mut rng = rand::thread_rng();
-            let bet_or_fold = rng.gen_range(1..=4);
-            if bet_or_fold == 1{
-                // Random bet between current bet and max chips, favors lower bets
-                let bet = (rng.gen_range(0..=25) as f64 / 100.0).powi(2) * (players[current_better].chips - current_bet) as f64 + current_bet as f64;
-                let bet = bet as i32;
-                players[current_better].update_chips(-bet);
-                *pot += bet;
-                bets[current_better] = bet;
-                current_bet = bet;
-                println!("\nPlayer {} bets {}", current_better + 1, bet);
-            } else if bet_or_fold <= 3 {
-                // Call the current bet
-                players[current_better].update_chips(-current_bet);
-                *pot += current_bet;
-                bets[current_better] = current_bet;
-                println!("\nPlayer {} bets {}", current_better + 1, current_bet);
-            } else {
-                println!("\nPlayer {} folds", current_better + 1);
-                bets[current_better] = -1;
-            }
-        }
-        // Move to the next player
-        current_better = (current_better + 1) % players.len();
-        players_visited += 1;
-        // Check if all bets are in
-        for bet in bets.iter() {
-            if *bet != -1 && *bet != current_bet {
-                all_bets_in = false;
-                break;
-            }
-            all_bets_in = true;
-        }
+    /*
+     * seats is a vector type in Rust. Rust vectors
+     * are Lists (Sebesta, 6.9) that can grow and shrink in size.
+     * Rust vectors have several methods. The vec! macro is used to * create a new vector with the specified elements.
+     */
+    let mut seats = vec![Seat::new(profile, starting_chips)];
+    for i in 1..num_of_players {
+        seats.push(Seat::new(&format!("Player {}", i + 1), starting_chips));
     }
-
-    // Reset the bets, except for the players who have folded
-    // -2 is used to indicate that the player has not yet bet
-    for bet in bets.iter_mut() {
-        if *bet != -1 {
-            *bet = -2;
-        }
+    let (small_blind, big_blind) = config.blinds(starting_chips);
+    let mut table = Table::new(seats, small_blind, big_blind);
+    table.structure = config.structure();
+    table.variant = config.variant();
+    table.ante = config.ante(starting_chips);
+    if let Some(seed) = config.seed {
+        table.seed = seed;
     }
-
-    bets.to_vec()
+    (table, always_show)
 }
 
-fn game(players: &mut Vec<Player>, starting_better: usize) {
-    // Initialize the deck
-    let mut deck = Deck::new();
-    deck.shuffle();
-
-    // Reset the players' cards
-    for player in players.iter_mut() {
-        player.cards = Vec::new();
-    }
-
-    // Deal  first two cards to each player
-    for _ in 0..2 {
-        for player in players.iter_mut() {
-            player.add(deck.deal().unwrap());
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let subcommand: Option<Subcommand> = match args.get(1).map(|arg| arg.as_str()) {
+        Some("replay") => Some(replayer::run),
+        Some("server") => Some(remote::serve),
+        Some("connect") => Some(remote::connect),
+        Some("watch") => Some(remote::watch),
+        Some("equity") => Some(calculator::run),
+        Some("range") => Some(calculator::show_range),
+        Some("stats") => Some(replayer::stats),
+        Some("leaderboard") => Some(leaderboard),
+        _ => None,
+    };
+    if let Some(run) = subcommand {
+        if let Err(error) = run(&args[2..]) {
+            eprintln!("{}", error);
+            std::process::exit(1);
         }
+        return;
     }
 
-    println!("{}", "--------------------------------------------------\n".bold().white());
-
-    // Print the cards of the user
-    println!("{}", "=================".red());
-    println!("{}", "Your cards:".white());
-    for card in players[0].cards.iter() {
-        println!("{}", format!("{:?} of {:?}", card.rank, card.suit).white());
+    // Flags replace the settings of the config file, the missing ones are asked for
+    let options = Options::parse(&args[1..]).unwrap_or_else(|error| exit_with(&error));
+    if options.help {
+        println!("{}", USAGE);
+        return;
     }
-    println!("{}", "=================\n".red());
-
-
-    // Wait for user to view cards and continue
-    println!("Press {} to begin betting", "Enter".bold());
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    println!("{}", "--------------------------------------------------".bold().white());
-
-    let mut bets = vec![-2; players.len()];
-    let mut pot = 0;
-    let mut community_cards = Vec::new();
-
-    // Betting round 1
-    bets = get_bets(players, starting_better, &mut bets, &mut pot, &community_cards);
-    println!("\nAll bets are in, the pot is now {}", pot);
-
-
-    // Wait for user to view bets and continue
-    println!("\nPress {} to deal the flop", "Enter".bold());
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    // Burn a card
-    deck.deal();
-
-    println!("{}", "--------------------------------------------------".bold().white());
-
-    // Deal the flop
-    for _ in 0..3 {
-        community_cards.push(deck.deal().unwrap());
+    let mut config = options.config;
+    if let Some(path) = &options.config_file {
+        config = Config::load(path).unwrap_or_else(|error| exit_with(&error)).merge(config);
     }
-    // Print the flop
-    println!("{}", "\nCards turned:".green());
-    println!("{}", "+----------------+".green());
-    for card in community_cards.iter() {
-        println!("{:^18}", format!("{:?} of {:?}", card.rank, card.suit));
+    if let Err(error) = config.validate() {
+        exit_with(&format!("Invalid settings: {}", error));
     }
-    println!("{}", "+----------------+".green());
-
-
-    // Wait for user to view cards and continue
-    println!("Press {} to begin betting", "Enter".bold());
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    println!("{}", "--------------------------------------------------".bold().white());
-
-    // Betting round 2
-    bets = get_bets(players, starting_better, &mut bets, &mut pot, &community_cards);
-    println!("\nAll bets are in, the pot is now {}", pot);
-
-
-    // Wait for user to view bets and continue
-    println!("\nPress {} to deal the turn", "Enter".bold());
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    println!("{}", "--------------------------------------------------".bold().white());
-
-    // Burn a card
-    deck.deal();
-
-    // Deal the turn
-    community_cards.push(deck.deal().unwrap());
-    // Print the community cards
-    println!("{}", "\nCards turned:".green());
-    println!("{}", "+----------------+".green());
-    for card in community_cards.iter() {
-        println!("{:^18}", format!("{:?} of {:?}", card.rank, card.suit));
+    if config.display.color == Some(false) {
+        colored::control::set_override(false);
     }
-    println!("{}", "+----------------+".green());
-
-
-    // Wait for user to view cards and continue
-    println!("Press {} to begin betting", "Enter".bold());
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    println!("{}", "--------------------------------------------------".bold().white());
-
-    // Betting round 3
-    bets = get_bets(players, starting_better, &mut bets, &mut pot, &community_cards);
-    println!("\nAll bets are in, the pot is now {}", pot);
-
-
-    // Wait for user to view bets and continue
-    println!("\nPress {} to deal the river", "Enter".bold());
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    println!("{}", "--------------------------------------------------".bold().white());
-
-    // Burn a card
-    deck.deal();
-    // Deal the turn
-    community_cards.push(deck.deal().unwrap());
-    // Print the community cards
-    println!("{}", "\nCards turned:".green());
-    println!("{}", "+----------------+".green());
-    for card in community_cards.iter() {
-        println!("{:^18}", format!("{:?} of {:?}", card.rank, card.suit));
-    }
-    println!("{}", "+----------------+".green());
-
-
-    // Wait for user to view cards and continue
-    println!("Press {} to begin betting", "Enter".bold());
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    println!("{}", "--------------------------------------------------".bold().white());
-
-    // Betting round 4
-    bets = get_bets(players, starting_better, &mut bets, &mut pot, &community_cards);
-    println!("\nAll bets are in, the pot is now {}", pot);
-
-
-    // Wait for user to view bets and continue
-    println!("\nPress {} to reveal the winning hand", "Enter".bold());
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    println!("{}", "--------------------------------------------------".bold().white());
-
-    // Find the winning player
-    let winning_player = find_winning_hand(players, &community_cards, &bets);
-    players[winning_player].update_chips(pot);
-
-    // Print the winning player
-    if winning_player == 0 {
-        println!("You have the best hand");
-    } else {
-        println!("{} {} has the best hand\n", "Player", format!("{}", winning_player + 1).bold().red().on_yellow());
-        println!("Player {}'s hand:", winning_player + 1);
-        println!("{}", "+====================+".bold().yellow());
-        for card in players[winning_player].cards.iter() {
-            println!("{}{:<20}{}", "|".bold().yellow(), format!("{:?} of {:?}", card.rank, card.suit), "|".bold().yellow());
-        }
-        println!("{}", "+====================+\n".bold().yellow());
+    let full_screen = config.display.tui == Some(true);
+    if full_screen && options.events.as_deref() == Some("-") {
+        exit_with("The event log can't be written to standard output while playing full screen");
     }
 
-    // Print the chips of each player
-    println!("\nEnd of round, each player has the following chips:");
-    println!("{}", "+====================+".bold().cyan());
-    for (i, player) in players.iter().enumerate() {
-        if i == 0 {
-            println!("{}{:<20}{}", "|".bold().cyan(), format!("You: {}", player.chips).bold().red(), "|".bold().cyan());
-        } else {
-            println!("{}{:<20}{}", "|".bold().cyan(), format!("Player {}: {}", i + 1, player.chips), "|".bold().cyan());
-        }
-    }
-    println!("{}", "+====================+\n".bold().cyan());
-
-    println!("{}", "--------------------------------------------------".bold().white());
-
-}
-
-fn main() {
-    /*
-     * This is a primitive data type (Sebesta, 6.2)
-     * In Rust, the type of num_of_players is inferred from the context. In this case the type will be set to i32, a 32-bit signed integer (Sebesta, 6.2.1.1).
-     */
-    let mut num_of_players = 5;
-    // Starting chips for each player
-    let mut starting_chips = 1000;
-
     println!("{}", "===================================".bold().dimmed().yellow());
     println!("{} {} {}", "|".bold().dimmed().yellow(), "Welcome to Texas Hold'em Poker!".bold().yellow(), "|".bold().dimmed().yellow());
     println!("{}", "===================================\n\n".bold().dimmed().yellow());
 
-    // Let user choose the number of players
-    println!("How many players are playing? [>2] (default is 5)");
-
-    /*
-     * The String type in Rust is a sequence of Unicode characters (Sebesta, 6.3). It is not a primitve data type and has dynamic length (Sebesta, 6.3.1).
-     */
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-    if !input.trim().is_empty() {
-        num_of_players = input.trim().parse::<usize>().expect("Please enter a number");
-        if num_of_players < 2 {
-            println!("Invalid number of players, defaulting to 5 players");
+    let mut profiles = Profiles::load(PROFILES_FILE).unwrap_or_else(|error| {
+        println!("{}, results will not be kept", error);
+        Profiles::default()
+    });
+    let (mut table, always_show, bots) = if options.resume {
+        match Session::load(SESSION_FILE) {
+            Ok(session) => {
+                println!("Continuing the saved game from round {}\n", session.table.hand_number + 1);
+                (session.table, session.always_show, session.bots)
+            }
+            Err(error) => {
+                eprintln!("Could not resume the game from {}: {}", SESSION_FILE, error);
+                std::process::exit(1);
+            }
         }
     } else {
-        println!("Setting the number of players to 5\n");
+        let profile = config.profile.clone().unwrap_or_else(|| choose_profile(&profiles));
+        let (table, always_show) = new_game(&config, &profile);
+        let bots: Vec<BotKind> = (1..table.seats.len()).map(|seat| config.bot(seat)).collect();
+        (table, always_show, bots)
+    };
+
+    // The user plays seat 1, the computer plays the others. The game is printed or drawn full screen,
+    // and every hand is written to the hand history file.
+    let pause = config.display.pause.unwrap_or(true);
+    let mut controllers: Vec<Box<dyn Controller>> = Vec::new();
+    let mut sinks: Vec<Box<dyn EventSink>> = Vec::new();
+    // The odds helper reads the other players from the game's events
+    let odds = config.display.odds.map(OddsHelper::new);
+    if let Some(helper) = &odds {
+        sinks.push(Box::new(helper.clone()));
     }
-
-    // Let user choose the starting chips for each player
-    println!("How many chips does each player start with? [>10] (default is 1000)");
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-    if !input.trim().is_empty() {
-        starting_chips = input.trim().parse::<i32>().expect("Please enter a number");
-        if starting_chips < 10 {
-            println!("Invalid number of chips, defaulting to 1000 chips");
-        }
+    let tui = if full_screen {
+        let tui = Tui::open(0, always_show, pause, LEAVE_MESSAGE).unwrap_or_else(|error| exit_with(&format!("Could not play full screen: {}", error)));
+        tui.borrow_mut().odds = odds;
+        controllers.push(Box::new(TuiPlayer(tui.clone())));
+        sinks.push(Box::new(TuiDisplay(tui.clone())));
+        Some(tui)
     } else {
-        println!("Setting the number of chips to 1000\n");
+        // The players' statistics are counted from the game's events, to show next to their bets
+        let stats = SharedStats::default();
+        sinks.push(Box::new(stats.clone()));
+        controllers.push(Box::new(TerminalPlayer { always_show, odds, stats: Some(stats) }));
+        sinks.push(Box::new(TerminalDisplay::new(pause)));
+        None
+    };
+    // With a shot clock, the user's time is counted, the bots always act in time
+    if let Some(shot_clock) = config.shot_clock() {
+        let user = controllers.remove(0);
+        controllers.insert(0, Box::new(Timed::new(user, shot_clock)));
+    }
+    for seat in 1..table.seats.len() {
+        controllers.push(bots.get(seat - 1).copied().unwrap_or_default().controller());
+    }
+    match OpenOptions::new().create(true).append(true).open(HAND_HISTORY_FILE) {
+        Ok(file) => sinks.push(Box::new(HandHistory::new(file, 0, "Terminal"))),
+        Err(error) => println!("Could not open {}, hands will not be recorded: {}", HAND_HISTORY_FILE, error),
+    }
+    // The session's results are printed when the game ends
+    let summary = SharedSummary::default();
+    sinks.push(Box::new(summary.clone()));
+    match options.events.as_deref() {
+        Some("-") => sinks.push(Box::new(EventLog::new(io::stdout()))),
+        // A resumed game carries on the log it was writing before
+        Some(path) => match if options.resume { OpenOptions::new().create(true).append(true).open(path) } else { File::create(path) } {
+            Ok(file) => sinks.push(Box::new(EventLog::new(file))),
+            Err(error) => println!("Could not create {}, events will not be logged: {}", path, error),
+        },
+        None => {}
     }
-
-    /*
-     * players is a vector type in Rust. Rust vectors
-     * are Lists (Sebesta, 6.9) that can grow and shrink in size.
-     * Rust vectors have several methods. The vec! macro is used to * create a new vector with the specified elements.
-     */
-    let mut players = vec![Player::new(starting_chips); num_of_players];
-
-    let mut round = 1;
 
     /*
      * This is a primitive data type (Sebesta, 6.2)
      * play_again is a boolean type in Rust (Sebesta, 6.2.2)
-     * Booleans are used to represent true or 
+     * Booleans are used to represent true or
      * false values.
      */
     let mut play_again = true;
+    // Where the user finished, once the game is played out
+    let mut finish = None;
     // Game loop
     while play_again {
-        println!("{:^35}", "+---------+");
-        println!("{:^35}", format!("| Round {} |", round));
-        println!("{:^37}", "+---------+\n\n");
-        game(&mut players, (round - 1) % num_of_players);
-        if players[0].chips <= 0 {
-            println!("You have run out of chips, game over!");
+        table.play_hand(&mut controllers, &mut sinks);
+        if table.seats[0].chips <= 0 || table.players_with_chips() == 1 {
+            // A finished game can't be continued
+            let _ = fs::remove_file(SESSION_FILE);
+            if let Some(tui) = &tui {
+                tui.borrow_mut().close();
+            }
+            if table.seats[0].chips <= 0 {
+                println!("You have run out of chips, game over!");
+            } else {
+                println!("Everyone else has run out of chips, you win!");
+            }
+            let position = if table.seats[0].chips > 0 { 1 } else { table.players_with_chips() + 1 };
+            finish = Some(Finish { position, players: table.seats.len() });
             break;
         }
+        if let Err(error) = Session::new(table.clone(), bots.clone(), always_show).save(SESSION_FILE) {
+            println!("Could not save the game to {}: {}", SESSION_FILE, error);
+        }
+        // Full screen, the user chooses whether to go on when the hand is over
+        if let Some(tui) = &tui {
+            if tui.borrow().quit() {
+                tui.borrow_mut().close();
+                println!("The game is saved, continue it with --resume");
+                play_again = false;
+            }
+            continue;
+        }
         println!("\nDo you want to play another round? ({}/{})", "y".bold().green(), "n".bold().red());
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).expect("Failed to read line");
-        if input.trim() == "n" {
+        if read_line().trim() == "n" {
             play_again = false;
         }
-        round += 1;
     }
+    let summary = summary.0.borrow();
+    print_summary(&summary, options.csv.as_deref());
+    record_results(&mut profiles, &table.seats[0].name, &summary, finish);
+}
+
+// Adds the session to the user's profile and prints their lifetime results
+fn record_results(profiles: &mut Profiles, name: &str, summary: &SessionSummary, finish: Option<Finish>) {
+    if summary.hands.is_empty() {
+        return;
+    }
+    profiles.record_session(name, summary.hands_played(0) as u64, summary.net(0) as i64);
+    if let Some(finish) = finish {
+        profiles.record_finish(name, finish);
+    }
+    if let Err(error) = profiles.save(PROFILES_FILE) {
+        println!("Could not save the results to {}: {}", PROFILES_FILE, error);
+        return;
+    }
+    let profile = profiles.get_or_create(name);
+    let plural = |count: usize, word: &str| format!("{} {}{}", count, word, if count == 1 { "" } else { "s" });
+    println!(
+        "\n{} has played {}, {:+} chips, and won {} of {}",
+        profile.name,
+        plural(profile.hands as usize, "hand"),
+        profile.net_chips,
+        profile.tournaments_won(),
+        plural(profile.finishes.len(), "game")
+    );
+}
 
+// The leaderboard subcommand: every profile ranked by games won, then by chips won
+fn leaderboard(args: &[String]) -> Result<(), String> {
+    if !args.is_empty() {
+        return Err("Usage: texas-holdem leaderboard".to_string());
+    }
+    let profiles = Profiles::load(PROFILES_FILE)?;
+    if profiles.profiles.is_empty() {
+        println!("Nobody has played yet");
+        return Ok(());
+    }
+    let width = profiles.profiles.iter().map(|profile| profile.name.chars().count()).max().unwrap_or(0).max(6) + 2;
+    println!("{}", format!("{:<6}{:<width$}{:>8}{:>11}{:>7}{:>7}{:>16}", "Rank", "Player", "Hands", "Net chips", "Won", "Games", "Average finish").bold());
+    for (rank, profile) in profiles.leaderboard().iter().enumerate() {
+        let average = profile.average_finish().map_or("-".to_string(), |average| format!("{:.1}", average));
+        println!(
+            "{:<6}{:<width$}{:>8}{:>+11}{:>7}{:>7}{:>16}",
+            rank + 1,
+            profile.name,
+            profile.hands,
+            profile.net_chips,
+            profile.tournaments_won(),
+            profile.finishes.len(),
+            average
+        );
+    }
+    Ok(())
+}
 
-}
\ No newline at end of file
+// Prints the results of the session and a chart of the stacks, and writes the hands to a CSV file
+fn print_summary(summary: &SessionSummary, csv: Option<&str>) {
+    if summary.hands.is_empty() {
+        return;
+    }
+    println!("\n{}", "Session summary".bold());
+    print!("{}", summary.report());
+    println!("\n{}", "Stacks".bold());
+    print!("{}", summary.chart(CHART_WIDTH, CHART_HEIGHT));
+    if let Some(path) = csv {
+        match File::create(path).and_then(|file| summary.write_csv(io::BufWriter::new(file))) {
+            Ok(()) => println!("The hands are written to {}", path),
+            Err(error) => println!("Could not write the hands to {}: {}", path, error),
+        }
+    }
+}
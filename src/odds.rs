@@ -0,0 +1,198 @@
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::engine::ActionRequest;
+use crate::equity::{calculate_ranges, Equity};
+use crate::eval::{evaluate_cards, BestHand, Hand};
+use crate::events::{Action, EventSink, GameEvent};
+use crate::range::Range;
+use rand::thread_rng;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Random deals for the live equity, enough for it to be within about a percent
+pub const ODDS_SAMPLES: u64 = 10_000;
+// The hands a player who raised before the flop is taken to hold, about the best 15%
+pub const RAISING_RANGE: &str = "66+, A8s+, K9s+, QTs+, JTs, T9s, ATo+, KJo+";
+// The hands a player who called before the flop is taken to hold, about the best 40%
+pub const CALLING_RANGE: &str = "22+, A2s+, K5s+, Q8s+, J8s+, T8s+, 97s+, 86s+, 75s+, 65s, 54s, A7o+, K9o+, QTo+, JTo";
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the hands the odds helper takes the other players to hold: any two cards, or
+ * a range estimated from what they did before the flop.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OddsAgainst {
+    Random,
+    Estimated,
+}
+
+impl OddsAgainst {
+    pub fn parse(name: &str) -> Option<OddsAgainst> {
+        match name.to_ascii_lowercase().as_str() {
+            "random" => Some(OddsAgainst::Random),
+            "estimated" => Some(OddsAgainst::Estimated),
+            _ => None,
+        }
+    }
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * Help with a decision: the player's made hand, the cards that would improve it on the next
+ * street, the share of the pot a call pays for and how often the hand wins.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Odds {
+    // The made hand, None before the flop
+    pub hand: Option<String>,
+    // The outs grouped by the hand they make, the best first
+    pub outs: Vec<(Hand, Vec<Card>)>,
+    // The call as a share of the pot after calling, the equity the call needs; None with nothing to call
+    pub pot_odds: Option<f64>,
+    pub equity: Equity,
+    pub opponents: usize,
+}
+
+impl Odds {
+    pub fn out_count(&self) -> usize {
+        self.outs.iter().map(|(_, cards)| cards.len()).sum()
+    }
+}
+
+/*
+ * The odds of the player asked to act, against the given ranges of the players still in the
+ * hand. The outs are the cards that would give the player a better kind of hand than they
+ * have now, and than the board would give everyone.
+ */
+pub fn odds(request: &ActionRequest, opponents: &[Range]) -> Result<Odds, String> {
+    if request.hole_cards.len() != 2 {
+        return Err("the odds helper only works in Hold'em".to_string());
+    }
+    let mut cards = request.hole_cards.clone();
+    cards.extend(&request.board);
+    let hand = (cards.len() >= 5).then(|| BestHand::new(&cards).description());
+
+    let mut outs: Vec<(Hand, Vec<Card>)> = Vec::new();
+    if (3..5).contains(&request.board.len()) {
+        let current = category(&cards);
+        let mut deck = Deck::new();
+        deck.remove(cards.iter().collect());
+        for card in deck.cards {
+            let made = category(&[cards.as_slice(), &[card]].concat());
+            let board = category(&[request.board.as_slice(), &[card]].concat());
+            if made > current && made > board {
+                match outs.iter_mut().find(|(hand, _)| *hand == made) {
+                    Some((_, cards)) => cards.push(card),
+                    None => outs.push((made, vec![card])),
+                }
+            }
+        }
+        outs.sort_by_key(|(hand, _)| std::cmp::Reverse(*hand));
+        for (_, cards) in outs.iter_mut() {
+            cards.sort_by(|a, b| b.cmp(a));
+        }
+    }
+
+    let pot_odds = (request.to_call > 0).then(|| request.to_call as f64 / (request.pot + request.to_call) as f64);
+    let hero = Range::from_hand(request.hole_cards[0], request.hole_cards[1]);
+    let ranges: Vec<Range> = std::iter::once(hero).chain(opponents.iter().cloned()).collect();
+    let report = calculate_ranges(&ranges, &request.board, &[], ODDS_SAMPLES, &mut thread_rng())?;
+    Ok(Odds { hand, outs, pot_odds, equity: report.players[0], opponents: opponents.len() })
+}
+
+// The kind of hand the cards make, counting only pairs and sets with fewer than five cards
+fn category(cards: &[Card]) -> Hand {
+    if cards.len() >= 5 {
+        return evaluate_cards(cards).hand();
+    }
+    let mut counts = [0; 13];
+    for card in cards {
+        counts[card.rank as usize] += 1;
+    }
+    let pairs = counts.iter().filter(|&&count| count == 2).count();
+    match counts.iter().max() {
+        Some(4) => Hand::FourOfAKind,
+        Some(3) => Hand::ThreeOfAKind,
+        _ if pairs >= 2 => Hand::TwoPair,
+        _ if pairs == 1 => Hand::OnePair,
+        _ => Hand::HighCard,
+    }
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates what a player did before the flop, which the estimated ranges are read from.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Read {
+    Unknown,
+    Called,
+    Raised,
+}
+
+// What every player did before the flop of the hand being played
+#[derive(Default)]
+struct Reads {
+    seats: Vec<Read>,
+    preflop: bool,
+}
+
+/*
+ * The odds helper of one player: follows the game as an event sink to read the other players,
+ * and works out the odds when the player is asked to act. Clones share what they have read.
+ */
+#[derive(Clone)]
+pub struct OddsHelper {
+    pub against: OddsAgainst,
+    reads: Rc<RefCell<Reads>>,
+}
+
+impl OddsHelper {
+    pub fn new(against: OddsAgainst) -> OddsHelper {
+        OddsHelper { against, reads: Rc::default() }
+    }
+
+    // The hands a player is taken to hold
+    pub fn range(&self, seat: usize) -> Range {
+        let read = self.reads.borrow().seats.get(seat).copied().unwrap_or(Read::Unknown);
+        match (self.against, read) {
+            (OddsAgainst::Estimated, Read::Raised) => Range::parse(RAISING_RANGE).expect("the raising range is valid"),
+            (OddsAgainst::Estimated, Read::Called) => Range::parse(CALLING_RANGE).expect("the calling range is valid"),
+            _ => Range::any(),
+        }
+    }
+
+    // The odds against every other player still in the hand
+    pub fn odds(&self, request: &ActionRequest) -> Result<Odds, String> {
+        let opponents: Vec<Range> = (0..request.seats.len())
+            .filter(|&seat| seat != request.seat && request.seats[seat].playing && !request.seats[seat].folded)
+            .map(|seat| self.range(seat))
+            .collect();
+        odds(request, &opponents)
+    }
+}
+
+impl EventSink for OddsHelper {
+    fn handle(&mut self, event: &GameEvent) {
+        let mut reads = self.reads.borrow_mut();
+        match event {
+            GameEvent::HandStarted { seats, .. } => {
+                reads.seats = vec![Read::Unknown; seats.len()];
+                reads.preflop = true;
+            }
+            GameEvent::StreetDealt { .. } => reads.preflop = false,
+            GameEvent::PlayerActed { seat, action, .. } if reads.preflop => {
+                match action {
+                    Action::Raise(_) => reads.seats[*seat] = Read::Raised,
+                    Action::Call if reads.seats[*seat] == Read::Unknown => reads.seats[*seat] = Read::Called,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * Where a player finished a game played until one player had every chip: 1 for the winner,
+ * and how many players it started with.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finish {
+    pub position: usize,
+    pub players: usize,
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * A person who plays at this computer, with their results over every session they played.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub hands: u64,
+    pub net_chips: i64,
+    pub finishes: Vec<Finish>,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Profile {
+        Profile { name: name.to_string(), ..Profile::default() }
+    }
+
+    pub fn tournaments_won(&self) -> usize {
+        self.finishes.iter().filter(|finish| finish.position == 1).count()
+    }
+
+    // The average finishing position, None before the player has finished a game
+    pub fn average_finish(&self) -> Option<f64> {
+        let total: usize = self.finishes.iter().map(|finish| finish.position).sum();
+        (!self.finishes.is_empty()).then(|| total as f64 / self.finishes.len() as f64)
+    }
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * Every player's profile, kept in a JSON file. Names are matched without regard to case.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    // Reads the profiles, there are none yet when the file doesn't exist
+    pub fn load(path: &str) -> Result<Profiles, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|error| format!("Invalid profiles file {}: {}", path, error)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Profiles::default()),
+            Err(error) => Err(format!("Could not read {}: {}", path, error)),
+        }
+    }
+
+    // Writes the profiles next to the old file and renames them over it, as sessions are saved
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        let temp = format!("{}.tmp", path);
+        fs::write(&temp, text).and_then(|_| fs::rename(&temp, path)).map_err(|error| error.to_string())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    // The profile with this name, made when there is none yet
+    pub fn get_or_create(&mut self, name: &str) -> &mut Profile {
+        let index = match self.profiles.iter().position(|profile| profile.name.eq_ignore_ascii_case(name)) {
+            Some(index) => index,
+            None => {
+                self.profiles.push(Profile::new(name));
+                self.profiles.len() - 1
+            }
+        };
+        &mut self.profiles[index]
+    }
+
+    // Adds the hands a player played in a session and the chips they won or lost
+    pub fn record_session(&mut self, name: &str, hands: u64, net_chips: i64) {
+        let profile = self.get_or_create(name);
+        profile.hands += hands;
+        profile.net_chips += net_chips;
+    }
+
+    pub fn record_finish(&mut self, name: &str, finish: Finish) {
+        self.get_or_create(name).finishes.push(finish);
+    }
+
+    // Everyone ranked by the games they won, then by the chips they won over their lifetime
+    pub fn leaderboard(&self) -> Vec<&Profile> {
+        let mut ranked: Vec<&Profile> = self.profiles.iter().collect();
+        ranked.sort_by(|a, b| (b.tournaments_won(), b.net_chips).cmp(&(a.tournaments_won(), a.net_chips)).then_with(|| a.name.cmp(&b.name)));
+        ranked
+    }
+}
@@ -0,0 +1,153 @@
+use crate::card::Card;
+use crate::engine::ActionRequest;
+use crate::events::{Action, GameEvent};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+
+/*
+ * The messages between the table server and its clients, described for other programs in
+ * docs/protocol.md. Every message is a JSON object sent as a frame: its length in bytes as a
+ * 4 byte big-endian number, then the JSON text.
+ */
+
+// Bumped whenever a change to the messages would break a client written for an older version
+pub const PROTOCOL_VERSION: u32 = 1;
+// Larger frames are skipped and answered with an error
+pub const MAX_FRAME_LENGTH: u32 = 1 << 20;
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the messages a client sends to the table server.
+ * In JSON the variant is written as the "type" field, e.g. {"type": "join", "version": 1, "name": "Ann"}.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    // Asks for a seat, the first message of every client. version is the protocol version the client speaks.
+    Join { version: u32, name: String },
+    // Takes back the seat of a player who lost their connection, with the token they were welcomed with
+    Rejoin { version: u32, token: String },
+    // The answer to an action request
+    Act { action: Action },
+    // The answer to a show request, true to show the hand
+    Show { show: bool },
+    // Asks to follow the table as a spectator instead of taking a seat, the first message of a spectator
+    Watch { version: u32 },
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the actions a player may take, as listed in an action request.
+ * Call holds the chips needed to call, Raise the smallest and largest total bet to raise to.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum LegalAction {
+    Fold,
+    Check,
+    Call { amount: i32 },
+    Raise { min: i32, max: i32 },
+}
+
+// The actions the player asked by request may take
+pub fn legal_actions(request: &ActionRequest) -> Vec<LegalAction> {
+    let mut legal = vec![LegalAction::Fold];
+    if request.can_check() {
+        legal.push(LegalAction::Check);
+    } else {
+        legal.push(LegalAction::Call { amount: request.to_call });
+    }
+    if request.can_raise() {
+        legal.push(LegalAction::Raise { min: request.min_raise, max: request.max_raise });
+    }
+    legal
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates what can go wrong with a client's messages, so a program can tell the errors apart.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    // The frame isn't a message: it is too large, isn't JSON or is missing fields
+    InvalidMessage,
+    // The message is not the one the server is waiting for, e.g. an action before joining
+    UnexpectedMessage,
+    UnsupportedVersion,
+    InvalidName,
+    NameTaken,
+    // Every seat is taken or the game has started, the client may still watch
+    TableFull,
+    // No player was given the token
+    InvalidToken,
+    // The action is not one of the legal actions of the request, the request is still open
+    IllegalAction,
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates the messages the table server sends to a client. Events and action requests
+ * carry their own fields next to the type, e.g. {"type": "event", "event": "hand_mucked", "seat": 2}.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    // The client has been given a seat, or has it back after losing their connection. The
+    // token takes the seat back with a rejoin message, the client should keep it to itself.
+    Welcome { version: u32, seat: usize, token: String },
+    // The client follows the table as a spectator. god_view is true when every player's hole
+    // cards are revealed to spectators later on.
+    Watching { version: u32, god_view: bool },
+    // Something that happened at the table. Clients only get their own hole cards.
+    Event(GameEvent),
+    // It is the client's turn to act. The request's time_limit is how many seconds the client
+    // has to answer, None if it may take as long as it likes.
+    ActionRequest {
+        #[serde(flatten)]
+        request: ActionRequest,
+        legal: Vec<LegalAction>,
+    },
+    // The client may show or muck their hand. best_five is empty if the board has too few cards
+    // to make a hand, uncontested is true when everyone else folded.
+    ShowRequest { cards: Vec<Card>, best_five: Vec<Card>, uncontested: bool },
+    // A player's hole cards in the given hand, sent to spectators once the god view's delay is over
+    Revealed { hand_number: u32, seat: usize, cards: Vec<Card> },
+    Error { code: ErrorCode, message: String },
+    // The game is over, nothing more will be sent
+    TableClosed { reason: String },
+}
+
+impl ServerMessage {
+    pub fn error(code: ErrorCode, message: &str) -> ServerMessage {
+        ServerMessage::Error { code, message: message.to_string() }
+    }
+}
+
+// Writes a message as one frame
+pub fn send<T: Serialize>(out: &mut impl Write, message: &T) -> io::Result<()> {
+    let json = serde_json::to_vec(message).expect("messages always serialize");
+    let mut frame = (json.len() as u32).to_be_bytes().to_vec();
+    frame.extend_from_slice(&json);
+    out.write_all(&frame)?;
+    out.flush()
+}
+
+/*
+ * Reads the next frame as a message. The error is UnexpectedEof once the connection is closed,
+ * and InvalidData if the frame isn't a message; the frame has been read, so the next one can be.
+ */
+pub fn receive<T: DeserializeOwned>(input: &mut impl Read) -> io::Result<T> {
+    let mut length = [0; 4];
+    input.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length);
+    if length > MAX_FRAME_LENGTH {
+        io::copy(&mut input.by_ref().take(length as u64), &mut io::sink())?;
+        let message = format!("the frame is {} bytes, at most {} are allowed", length, MAX_FRAME_LENGTH);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+    let mut json = vec![0; length as usize];
+    input.read_exact(&mut json)?;
+    serde_json::from_slice(&json).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
@@ -0,0 +1,249 @@
+use crate::card::{Card, CardSet, Rank, Suit};
+use std::collections::BTreeMap;
+
+// Every way to be dealt two hole cards
+pub const ALL_COMBOS: usize = 1326;
+
+// Two hole cards, the higher card first
+pub type Combo = [Card; 2];
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * The hands a player may hold, each with a weight from 0 to 1: how often they would play it
+ * this way. Ranges are written in the usual notation, separated by commas:
+ *
+ *     QQ+          pairs of Queens or better
+ *     AKs, KQo     suited or offsuit hands, AK is both
+ *     A5s-A2s      every kicker from A2s to A5s
+ *     ATs+         Ace Ten suited up to Ace King suited, the kicker goes up
+ *     76s+         connectors from 76s up to AKs, both cards go up
+ *     AhKh         exactly these two cards
+ *     QQ:0.5       any of these with a weight, e.g. Queens half the time
+ *     random       every hand, the range of a player nothing is known about
+ *
+ * A hand given twice takes the later weight.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Range {
+    weights: BTreeMap<Combo, f64>,
+}
+
+impl Range {
+    // A range of exactly these two hole cards
+    pub fn from_hand(first: Card, second: Card) -> Range {
+        let mut range = Range::default();
+        range.weights.insert(combo(first, second), 1.0);
+        range
+    }
+
+    // Every two cards a player can be dealt
+    pub fn any() -> Range {
+        let mut range = Range::default();
+        for high in Rank::ALL {
+            for low in Rank::ALL.into_iter().filter(|&low| low <= high) {
+                for combo in combos_of(high, low, Suits::Any) {
+                    range.weights.insert(combo, 1.0);
+                }
+            }
+        }
+        range
+    }
+
+    pub fn parse(text: &str) -> Result<Range, String> {
+        let mut range = Range::default();
+        for part in text.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let (hands, weight) = match part.split_once(':') {
+                Some((hands, weight)) => match weight.trim().parse::<f64>() {
+                    Ok(weight) if (0.0..=1.0).contains(&weight) => (hands.trim(), weight),
+                    _ => return Err(format!("the weight of {} must be a number from 0 to 1", part)),
+                },
+                None => (part, 1.0),
+            };
+            for combo in parse_hands(hands)? {
+                range.weights.insert(combo, weight);
+            }
+        }
+        if range.weights.is_empty() {
+            return Err(format!("{} is not a range such as QQ+, AKs", text.trim()));
+        }
+        Ok(range)
+    }
+
+    // The hands in the range with their weights, leaving out those with a weight of 0
+    pub fn combos(&self) -> impl Iterator<Item = (Combo, f64)> + '_ {
+        self.weights.iter().filter(|(_, &weight)| weight > 0.0).map(|(&combo, &weight)| (combo, weight))
+    }
+
+    // The hands that could still have been dealt once the known cards are out of the deck
+    pub fn without(&self, known: CardSet) -> Range {
+        let weights = self.combos().filter(|(combo, _)| !combo.iter().any(|&card| known.contains(card))).collect();
+        Range { weights }
+    }
+
+    // The number of hands, counting a hand with a weight of 0.5 as half
+    pub fn size(&self) -> f64 {
+        self.combos().map(|(_, weight)| weight).sum()
+    }
+
+    // How much of every possible deal the range covers, from 0 to 1
+    pub fn share(&self) -> f64 {
+        self.size() / ALL_COMBOS as f64
+    }
+
+    /*
+     * The range drawn as the 13 by 13 grid of starting hands: pairs on the diagonal, suited
+     * hands above it and offsuit hands below it. A hand that is all in the range is named,
+     * one that is partly in it shows how much of it is, and the rest are dots.
+     * The array type in Rust has a fixed size (Sebesta, 6.5).
+     */
+    pub fn grid(&self) -> String {
+        let mut grid = String::new();
+        for row in Rank::ALL.iter().rev() {
+            for column in Rank::ALL.iter().rev() {
+                let (high, low, suited) = if column <= row { (*row, *column, true) } else { (*column, *row, false) };
+                let combos = class_combos(high, low, suited && high != low);
+                let weight: f64 = combos.iter().map(|combo| self.weights.get(combo).copied().unwrap_or(0.0)).sum::<f64>() / combos.len() as f64;
+                let cell = if weight >= 1.0 {
+                    class_name(high, low, suited)
+                } else if weight > 0.0 {
+                    format!("{}%", ((weight * 100.0).round() as u32).clamp(1, 99))
+                } else {
+                    ".".to_string()
+                };
+                grid += &format!("{:<4}", cell);
+            }
+            grid = grid.trim_end().to_string();
+            grid.push('\n');
+        }
+        grid
+    }
+}
+
+fn combo(first: Card, second: Card) -> Combo {
+    if first > second {
+        [first, second]
+    } else {
+        [second, first]
+    }
+}
+
+// The name of a starting hand, e.g. "QQ", "AKs" or "T9o"
+fn class_name(high: Rank, low: Rank, suited: bool) -> String {
+    match (high == low, suited) {
+        (true, _) => format!("{}{}", high.symbol(), low.symbol()),
+        (false, true) => format!("{}{}s", high.symbol(), low.symbol()),
+        (false, false) => format!("{}{}o", high.symbol(), low.symbol()),
+    }
+}
+
+// Every combo of a starting hand: 6 for a pair, 4 suited and 12 offsuit
+fn class_combos(high: Rank, low: Rank, suited: bool) -> Vec<Combo> {
+    let mut combos = Vec::new();
+    for first in Suit::ALL {
+        for second in Suit::ALL {
+            let keep = if high == low { first < second } else { (first == second) == suited };
+            if keep {
+                combos.push(combo(Card::new(high, first), Card::new(low, second)));
+            }
+        }
+    }
+    combos
+}
+
+fn parse_rank(symbol: char) -> Option<Rank> {
+    Rank::ALL.into_iter().find(|rank| rank.symbol() == symbol.to_ascii_uppercase())
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates which suits a starting hand is written for: AKs, AKo or AK for both.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Suits {
+    Suited,
+    Offsuit,
+    Any,
+}
+
+// A starting hand written as two ranks and maybe s or o, e.g. "AKs", with the higher rank first
+fn parse_class(text: &str) -> Option<(Rank, Rank, Suits)> {
+    let mut chars = text.chars();
+    let (first, second) = (parse_rank(chars.next()?)?, parse_rank(chars.next()?)?);
+    let suits = match chars.next().map(|c| c.to_ascii_lowercase()) {
+        None => Suits::Any,
+        Some('s') if first != second => Suits::Suited,
+        Some('o') if first != second => Suits::Offsuit,
+        Some(_) => return None,
+    };
+    if chars.next().is_some() {
+        return None;
+    }
+    Some((first.max(second), first.min(second), suits))
+}
+
+fn combos_of(high: Rank, low: Rank, suits: Suits) -> Vec<Combo> {
+    match suits {
+        // A pair is never suited
+        _ if high == low => class_combos(high, low, false),
+        Suits::Suited => class_combos(high, low, true),
+        Suits::Offsuit => class_combos(high, low, false),
+        Suits::Any => [class_combos(high, low, true), class_combos(high, low, false)].concat(),
+    }
+}
+
+// The hands of one part of a range, without its weight
+fn parse_hands(text: &str) -> Result<Vec<Combo>, String> {
+    if text.eq_ignore_ascii_case("random") || text.eq_ignore_ascii_case("any") {
+        return Ok(Range::any().weights.into_keys().collect());
+    }
+    let invalid = || format!("{} is not a hand such as QQ+, AKs, A5s-A2s or AhKh", text);
+    // Exactly two cards
+    if let (Some(first), Some(second)) = (text.get(..2).and_then(Card::from_code), text.get(2..).and_then(Card::from_code)) {
+        if first == second {
+            return Err(format!("{} holds the same card twice", text));
+        }
+        return Ok(vec![combo(first, second)]);
+    }
+
+    let mut combos = Vec::new();
+    if let Some(start) = text.strip_suffix('+') {
+        let (high, low, suits) = parse_class(start).ok_or_else(invalid)?;
+        let (high, low) = (high as usize, low as usize);
+        if high == low {
+            // Pairs up to Aces
+            for rank in high..Rank::ALL.len() {
+                combos.extend(combos_of(Rank::ALL[rank], Rank::ALL[rank], suits));
+            }
+        } else if high == low + 1 {
+            // Connectors, both cards go up to Ace King
+            for step in 0..Rank::ALL.len() - high {
+                combos.extend(combos_of(Rank::ALL[high + step], Rank::ALL[low + step], suits));
+            }
+        } else {
+            // The kicker goes up to one below the high card
+            for kicker in low..high {
+                combos.extend(combos_of(Rank::ALL[high], Rank::ALL[kicker], suits));
+            }
+        }
+    } else if let Some((from, to)) = text.split_once('-') {
+        let ((high, low, suits), (other_high, other_low, other_suits)) = (parse_class(from).ok_or_else(invalid)?, parse_class(to).ok_or_else(invalid)?);
+        if suits != other_suits {
+            return Err(invalid());
+        }
+        if high == low && other_high == other_low {
+            for rank in high.min(other_high) as usize..=high.max(other_high) as usize {
+                combos.extend(combos_of(Rank::ALL[rank], Rank::ALL[rank], suits));
+            }
+        } else if high == other_high && high != low && other_high != other_low {
+            for kicker in low.min(other_low) as usize..=low.max(other_low) as usize {
+                combos.extend(combos_of(high, Rank::ALL[kicker], suits));
+            }
+        } else {
+            return Err(format!("{} must be a span of pairs or of kickers to one card, e.g. QQ-99 or A5s-A2s", text));
+        }
+    } else {
+        let (high, low, suits) = parse_class(text).ok_or_else(invalid)?;
+        combos = combos_of(high, low, suits);
+    }
+    Ok(combos)
+}
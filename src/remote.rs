@@ -0,0 +1,304 @@
+use crate::terminal::{event_line, format_cards, read_line, TerminalPlayer};
+use crate::tui::{Tui, TuiPlayer};
+use colored::Colorize;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+use texas_holdem::card::Card;
+use texas_holdem::client::{Client, Spectator};
+use texas_holdem::config::{self, Config, Options, USAGE};
+use texas_holdem::engine::{Controller, Seat, Table};
+use texas_holdem::event_log::EventLog;
+use texas_holdem::events::{EventSink, GameEvent};
+use texas_holdem::replay::ReplayTable;
+use texas_holdem::server::TableServer;
+use texas_holdem::spectators::RevealDelay;
+
+const DEFAULT_PORT: u16 = 7777;
+const DEFAULT_CHIPS: i32 = 1000;
+// A lost connection is tried again every second for half a minute
+const RECONNECT_ATTEMPTS: u32 = 30;
+const RECONNECT_PAUSE: Duration = Duration::from_secs(1);
+
+/*
+ * The server subcommand: hosts a table for players who connect with the connect subcommand.
+ * It takes the table options of the local game, apart from the ones about the user's terminal.
+ */
+pub fn serve(args: &[String]) -> Result<(), String> {
+    let mut address = format!("0.0.0.0:{}", DEFAULT_PORT);
+    let mut web = None;
+    let mut humans = 2;
+    let mut hands = None;
+    let mut god_view = None;
+    let mut disconnect_bank = None;
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--listen" => address = value()?,
+            "--web" => web = Some(value()?),
+            "--humans" => humans = number(arg, &value()?)?,
+            "--hands" => hands = Some(number(arg, &value()?)?),
+            "--god-view-hands" => god_view = Some(RevealDelay::Hands(number(arg, &value()?)?)),
+            "--god-view-seconds" => god_view = Some(RevealDelay::Seconds(number(arg, &value()?)?)),
+            "--disconnect-bank" => disconnect_bank = Some(Duration::from_secs(number(arg, &value()?)?)),
+            _ => rest.push(arg.clone()),
+        }
+    }
+    // Showing the cards straight away would let spectators tell the players
+    if matches!(god_view, Some(RevealDelay::Hands(0) | RevealDelay::Seconds(0))) {
+        return Err("the god view needs a delay of at least 1".to_string());
+    }
+    let options = Options::parse(&rest)?;
+    if options.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    if options.resume {
+        return Err("--resume is not supported by the server".to_string());
+    }
+    let mut config = options.config;
+    if let Some(path) = &options.config_file {
+        config = Config::load(path)?.merge(config);
+    }
+    config.validate().map_err(|error| format!("Invalid settings: {}", error))?;
+
+    // Without a number of players, the table has a seat for every player and computer player given
+    let players = config.players.unwrap_or(humans + config.bot_count()).max(2);
+    if !(1..=players).contains(&humans) {
+        return Err(format!("--humans must be between 1 and the number of players ({}), got {}", players, humans));
+    }
+    if config.bot_count() > players - humans {
+        return Err(format!("{} bots are given but there are only {} computer players", config.bot_count(), players - humans));
+    }
+    config::check_players(players, 0, config.variant())?;
+    let chips = config.chips.unwrap_or(DEFAULT_CHIPS);
+    let seats = (0..players).map(|i| Seat::new(&format!("Player {}", i + 1), chips)).collect();
+    let (small_blind, big_blind) = config.blinds(chips);
+    let mut table = Table::new(seats, small_blind, big_blind);
+    table.structure = config.structure();
+    table.variant = config.variant();
+    table.ante = config.ante(chips);
+    if let Some(seed) = config.seed {
+        table.seed = seed;
+    }
+
+    let mut sinks: Vec<Box<dyn EventSink>> = Vec::new();
+    match options.events.as_deref() {
+        Some("-") => sinks.push(Box::new(EventLog::new(io::stdout()))),
+        Some(path) => sinks.push(Box::new(EventLog::new(File::create(path).map_err(|error| format!("Could not create {}: {}", path, error))?))),
+        None => {}
+    }
+
+    let bots = config.bots.clone().unwrap_or_default();
+    let mut server = TableServer::bind(&address, table, humans, bots).map_err(|error| format!("Could not listen on {}: {}", address, error))?;
+    server.god_view = god_view;
+    server.shot_clock = config.shot_clock();
+    if let Some(bank) = disconnect_bank {
+        server.disconnect_bank = bank;
+    }
+    eprintln!("Listening on {}, waiting for {} player(s) to join", server.local_addr(), humans);
+    if let Some(web) = web {
+        let local = server.listen_web(&web).map_err(|error| format!("Could not listen on {}: {}", web, error))?;
+        eprintln!("Players can also join from a browser at http://{}/", local);
+    }
+    server.run(hands, sinks).map_err(|error| format!("The server stopped: {}", error))?;
+    for seat in &server.table.seats {
+        eprintln!("{}: {}", seat.name, seat.chips);
+    }
+    Ok(())
+}
+
+/*
+ * The connect subcommand: sits at a table hosted with the server subcommand. The game is
+ * printed line by line, or drawn full screen with --tui.
+ */
+pub fn connect(args: &[String]) -> Result<(), String> {
+    const CONNECT_USAGE: &str = "Usage: texas-holdem connect <address> [--name <name>] [--token <token>] [--tui] [--always-show] [--no-color]";
+    let mut address = None;
+    let mut name = None;
+    let mut token = None;
+    let mut full_screen = false;
+    let mut always_show = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => name = Some(args.next().cloned().ok_or("--name needs a value")?),
+            "--token" => token = Some(args.next().cloned().ok_or("--token needs a value")?),
+            "--tui" => full_screen = true,
+            "--always-show" => always_show = true,
+            "--no-color" => colored::control::set_override(false),
+            _ if arg.starts_with("--") || address.is_some() => return Err(format!("unknown option {}\n{}", arg, CONNECT_USAGE)),
+            _ => address = Some(arg.clone()),
+        }
+    }
+    let address = with_port(address.ok_or(CONNECT_USAGE)?);
+
+    // Ask for another name until the server gives a seat, unless the user is taking back theirs
+    let client = match token {
+        Some(token) => Client::rejoin(&address, &token).map_err(|error| format!("Could not rejoin: {}", error))?,
+        None => loop {
+            let name = match name.take() {
+                Some(name) => name,
+                None => {
+                    println!("What is your name?");
+                    read_line().trim().to_string()
+                }
+            };
+            match Client::join(&address, &name) {
+                Ok(client) => break client,
+                Err(error) if error.starts_with("Could not connect") => return Err(error),
+                Err(error) => println!("Could not join: {}", error),
+            }
+        },
+    };
+
+    let reason = if full_screen {
+        let tui = Tui::open(client.seat, always_show, true, "Left the table").map_err(|error| format!("Could not play full screen: {}", error))?;
+        let result = play_on(client, &address, &mut TuiPlayer(tui.clone()), &mut RemoteTui(tui.clone()), false);
+        tui.borrow_mut().close();
+        result?
+    } else {
+        println!("Sitting at seat {}, waiting for the game to start", client.seat + 1);
+        println!("If you lose the connection, take your seat back with: texas-holdem connect {} --token {}", address, client.token);
+        let mut display = RemoteTerminal { table: ReplayTable::new(), hero: Some(client.seat) };
+        play_on(client, &address, &mut TerminalPlayer { always_show, odds: None, stats: None }, &mut display, true)?
+    };
+    println!("The table is closed: {}", reason);
+    Ok(())
+}
+
+// Plays until the table closes, taking the seat back whenever the connection is lost
+fn play_on(mut client: Client, address: &str, controller: &mut dyn Controller, sink: &mut dyn EventSink, verbose: bool) -> Result<String, String> {
+    loop {
+        match client.play(controller, sink) {
+            Err(error) if error.starts_with("Lost the connection") => {
+                if verbose {
+                    println!("{}, trying to get back to the table", error);
+                }
+                client = reconnect(address, &client.token).ok_or(error)?;
+                if verbose {
+                    println!("Back at the table");
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+// Tries to take the seat back for a while, None if the server can't be reached or refuses
+fn reconnect(address: &str, token: &str) -> Option<Client> {
+    for _ in 0..RECONNECT_ATTEMPTS {
+        thread::sleep(RECONNECT_PAUSE);
+        match Client::rejoin(address, token) {
+            Ok(client) => return Some(client),
+            Err(error) if error.starts_with("Could not connect") => {}
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+/*
+ * The watch subcommand: follows a table hosted with the server subcommand as a spectator. With
+ * a god view the players' cards are printed once the server reveals them.
+ */
+pub fn watch(args: &[String]) -> Result<(), String> {
+    const WATCH_USAGE: &str = "Usage: texas-holdem watch <address> [--no-color]";
+    let mut address = None;
+    for arg in args {
+        match arg.as_str() {
+            "--no-color" => colored::control::set_override(false),
+            _ if arg.starts_with("--") || address.is_some() => return Err(format!("unknown option {}\n{}", arg, WATCH_USAGE)),
+            _ => address = Some(arg.clone()),
+        }
+    }
+    let address = with_port(address.ok_or(WATCH_USAGE)?);
+    let (mut client, god_view) = Client::watch(&address)?;
+    if god_view {
+        println!("Watching the table, the players' cards are shown after a delay");
+    } else {
+        println!("Watching the table");
+    }
+    let reason = client.follow(&mut RemoteTerminal { table: ReplayTable::new(), hero: None })?;
+    println!("The table is closed: {}", reason);
+    Ok(())
+}
+
+// Addresses without a port are on the default one
+fn with_port(address: String) -> String {
+    if address.contains(':') {
+        address
+    } else {
+        format!("{}:{}", address, DEFAULT_PORT)
+    }
+}
+
+// Draws the game full screen, leaving the table when the user quits at the end of a hand
+struct RemoteTui(Rc<RefCell<Tui>>);
+
+impl EventSink for RemoteTui {
+    fn handle(&mut self, event: &GameEvent) {
+        let mut tui = self.0.borrow_mut();
+        tui.handle(event);
+        if tui.quit() {
+            tui.close();
+            println!("Left the table");
+            std::process::exit(0);
+        }
+    }
+}
+
+// Prints the game line by line from the user's seat, or for a spectator without one
+struct RemoteTerminal {
+    table: ReplayTable,
+    hero: Option<usize>,
+}
+
+impl EventSink for RemoteTerminal {
+    fn handle(&mut self, event: &GameEvent) {
+        self.table.apply(event);
+        match event {
+            GameEvent::HandStarted { hand_number, .. } => {
+                println!("{}", "--------------------------------------------------".bold().white());
+                println!("{}", format!("Round {}", hand_number).bold());
+            }
+            GameEvent::HoleCards { cards, .. } => println!("Your cards: {}", format_cards(cards)),
+            GameEvent::BettingRoundEnded { pot, .. } => println!("All bets are in, the pot is now {}", pot),
+            _ => {}
+        }
+        if let Some(line) = event_line(&self.table, self.hero, event) {
+            println!("{}", line);
+        }
+        if let GameEvent::HandEnded { chips } = event {
+            println!("\nEnd of round, each player has the following chips:");
+            for (i, chips) in chips.iter().enumerate() {
+                let line = format!("{}: {}", self.table.seats[i].name, chips);
+                if Some(i) == self.hero {
+                    println!("  {}", line.bold());
+                } else {
+                    println!("  {}", line);
+                }
+            }
+        }
+    }
+}
+
+impl Spectator for RemoteTerminal {
+    fn revealed(&mut self, hand_number: u32, seat: usize, cards: &[Card]) {
+        let name = self.table.seats.get(seat).map(|seat| seat.name.clone()).unwrap_or_else(|| format!("Seat {}", seat + 1));
+        if hand_number == self.table.hand_number {
+            println!("{}", format!("{} was dealt {}", name, format_cards(cards)).dimmed());
+        } else {
+            println!("{}", format!("In round {}, {} was dealt {}", hand_number, name, format_cards(cards)).dimmed());
+        }
+    }
+}
+
+fn number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("{} needs a number, got {}", flag, value))
+}
@@ -0,0 +1,187 @@
+use crate::card::Card;
+use crate::engine::SeatView;
+use crate::event_log;
+use crate::events::{Action, Blind, GameEvent, Street};
+
+/*
+ * Splits an event log into hands, each starting with its HandStarted event.
+ * Events before the first hand are skipped, so a log that was cut short at the start still loads.
+ */
+pub fn read_hands(text: &str) -> Result<Vec<Vec<GameEvent>>, String> {
+    let mut hands: Vec<Vec<GameEvent>> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = event_log::parse_line(line).map_err(|error| format!("line {}: {}", i + 1, error))?;
+        match event {
+            GameEvent::HandStarted { .. } => hands.push(vec![event]),
+            event => {
+                if let Some(hand) = hands.last_mut() {
+                    hand.push(event);
+                }
+            }
+        }
+    }
+    Ok(hands)
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * The table as it stands after some of the events of a hand.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayTable {
+    pub hand_number: u32,
+    pub button: usize,
+    pub street: Street,
+    pub seats: Vec<SeatView>,
+    pub hole_cards: Vec<Vec<Card>>,
+    // True for the players whose cards were shown at the showdown
+    pub shown: Vec<bool>,
+    pub board: Vec<Card>,
+    // All chips bet this hand that have not been won yet
+    pub pot: i32,
+}
+
+impl ReplayTable {
+    pub fn new() -> ReplayTable {
+        ReplayTable {
+            hand_number: 0,
+            button: 0,
+            street: Street::Preflop,
+            seats: Vec::new(),
+            hole_cards: Vec::new(),
+            shown: Vec::new(),
+            board: Vec::new(),
+            pot: 0,
+        }
+    }
+
+    pub fn apply(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::HandStarted { hand_number, button, seats, .. } => {
+                *self = ReplayTable::new();
+                self.hand_number = *hand_number;
+                self.button = *button;
+                self.seats = seats
+                    .iter()
+                    .map(|seat| SeatView {
+                        name: seat.name.clone(),
+                        chips: seat.chips,
+                        bet: 0,
+                        folded: false,
+                        acted: false,
+                        all_in: false,
+                        playing: seat.playing,
+                    })
+                    .collect();
+                self.hole_cards = vec![Vec::new(); seats.len()];
+                self.shown = vec![false; seats.len()];
+            }
+            GameEvent::BlindPosted { seat, blind, amount, all_in } => {
+                let view = &mut self.seats[*seat];
+                view.chips -= amount;
+                // Antes go straight into the pot
+                if *blind != Blind::Ante {
+                    view.bet += amount;
+                }
+                view.all_in = *all_in;
+                self.pot += amount;
+            }
+            GameEvent::HoleCards { seat, cards } => self.hole_cards[*seat] = cards.clone(),
+            GameEvent::StreetDealt { street, board, .. } => {
+                self.street = *street;
+                self.board = board.clone();
+                self.clear_bets();
+            }
+            GameEvent::PlayerActed { seat, action, amount, bet, all_in, .. } => {
+                let view = &mut self.seats[*seat];
+                view.chips -= amount;
+                view.bet = *bet;
+                view.acted = true;
+                view.all_in = *all_in;
+                view.folded = *action == Action::Fold;
+                self.pot += amount;
+            }
+            GameEvent::BettingRoundEnded { pot, .. } => self.pot = *pot,
+            GameEvent::UncalledBetReturned { seat, amount } => {
+                let view = &mut self.seats[*seat];
+                view.chips += amount;
+                view.bet -= amount;
+                self.pot -= amount;
+            }
+            GameEvent::ShowdownStarted => self.clear_bets(),
+            GameEvent::HandShown { seat, cards, .. } => {
+                self.hole_cards[*seat] = cards.clone();
+                self.shown[*seat] = true;
+            }
+            GameEvent::HandMucked { .. } => {}
+            GameEvent::PotAwarded { seat, amount, .. } => {
+                self.seats[*seat].chips += amount;
+                self.pot -= amount;
+            }
+            GameEvent::HandEnded { chips } => {
+                for (view, chips) in self.seats.iter_mut().zip(chips) {
+                    view.chips = *chips;
+                }
+                self.pot = 0;
+            }
+        }
+    }
+
+    fn clear_bets(&mut self) {
+        for view in self.seats.iter_mut() {
+            view.bet = 0;
+            view.acted = false;
+        }
+    }
+}
+
+impl Default for ReplayTable {
+    fn default() -> ReplayTable {
+        ReplayTable::new()
+    }
+}
+
+/*
+ * One recorded hand, split into steps the replayer moves between: the deal and preflop
+ * betting, each later street, and the showdown.
+ */
+pub struct Replay {
+    pub events: Vec<GameEvent>,
+    // Index of the first event of every step
+    starts: Vec<usize>,
+}
+
+impl Replay {
+    pub fn new(events: Vec<GameEvent>) -> Replay {
+        let mut starts = vec![0];
+        for (i, event) in events.iter().enumerate() {
+            if i > 0 && matches!(event, GameEvent::StreetDealt { .. } | GameEvent::ShowdownStarted) {
+                starts.push(i);
+            }
+        }
+        Replay { events, starts }
+    }
+
+    pub fn steps(&self) -> usize {
+        self.starts.len()
+    }
+
+    // The events of a step, in the order they happened
+    pub fn step_events(&self, step: usize) -> &[GameEvent] {
+        let end = self.starts.get(step + 1).copied().unwrap_or(self.events.len());
+        &self.events[self.starts[step]..end]
+    }
+
+    // The table at the end of a step
+    pub fn table_after(&self, step: usize) -> ReplayTable {
+        let end = self.starts.get(step + 1).copied().unwrap_or(self.events.len());
+        let mut table = ReplayTable::new();
+        for event in self.events[..end].iter() {
+            table.apply(event);
+        }
+        table
+    }
+}
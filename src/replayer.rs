@@ -0,0 +1,136 @@
+use crate::terminal::{event_line, format_cards, print_bets, read_line};
+use colored::Colorize;
+use std::fs;
+use texas_holdem::events::{GameEvent, Street};
+use texas_holdem::replay::{read_hands, Replay, ReplayTable};
+use texas_holdem::stats::Stats;
+
+const USAGE: &str = "Usage: texas-holdem replay <event log> [hand number] [--show-cards]";
+
+/*
+ * The replay subcommand: steps through a hand recorded with --events, one street at a time.
+ * Every player is named as they were at the table, the user by the name of their profile.
+ * Hole cards stay hidden until they are shown at the showdown, unless --show-cards is given
+ * or they are revealed with the h key.
+ */
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut show_cards = false;
+    let mut path = None;
+    let mut hand_number = None;
+    for arg in args {
+        if arg == "--show-cards" {
+            show_cards = true;
+        } else if path.is_none() {
+            path = Some(arg.clone());
+        } else if hand_number.is_none() {
+            hand_number = Some(arg.parse::<u32>().map_err(|_| format!("Invalid hand number {}\n{}", arg, USAGE))?);
+        } else {
+            return Err(USAGE.to_string());
+        }
+    }
+    let path = path.ok_or(USAGE)?;
+
+    let text = fs::read_to_string(&path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    let mut hands = read_hands(&text).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    // Replay the last hand in the log unless another one is asked for
+    let events = match hand_number {
+        Some(number) => {
+            let found = hands.iter().position(|hand| matches!(hand[0], GameEvent::HandStarted { hand_number, .. } if hand_number == number));
+            hands.swap_remove(found.ok_or(format!("Hand {} is not in {}", number, path))?)
+        }
+        None => hands.pop().ok_or(format!("There are no hands in {}", path))?,
+    };
+
+    let replay = Replay::new(events);
+    let mut step = 0;
+    loop {
+        print_step(&replay, step, show_cards);
+        println!(
+            "{} next, {} back, {} {} hole cards, {} quit",
+            "Enter".bold(),
+            "b".bold(),
+            "h".bold(),
+            if show_cards { "hide" } else { "show" },
+            "q".bold()
+        );
+        match read_line().trim() {
+            "" | "n" => {
+                if step + 1 == replay.steps() {
+                    return Ok(());
+                }
+                step += 1;
+            }
+            "b" => step = step.saturating_sub(1),
+            "h" => show_cards = !show_cards,
+            "q" => return Ok(()),
+            _ => println!("Unknown key"),
+        }
+    }
+}
+
+fn print_step(replay: &Replay, step: usize, show_cards: bool) {
+    let table = replay.table_after(step);
+    let title = match replay.step_events(step).first() {
+        Some(GameEvent::StreetDealt { street: Street::Flop, .. }) => "Flop",
+        Some(GameEvent::StreetDealt { street: Street::Turn, .. }) => "Turn",
+        Some(GameEvent::StreetDealt { street: Street::River, .. }) => "River",
+        Some(GameEvent::ShowdownStarted) => "Showdown",
+        _ => "Preflop",
+    };
+    println!("{}", "--------------------------------------------------".bold().white());
+    println!("{}", format!("Hand {}: {} ({} of {})", table.hand_number, title, step + 1, replay.steps()).bold());
+
+    // What happened during this step, replayed on the table as it was before the step
+    let mut before = if step == 0 { ReplayTable::new() } else { replay.table_after(step - 1) };
+    for event in replay.step_events(step) {
+        before.apply(event);
+        if let Some(line) = event_line(&before, None, event) {
+            println!("  {}", line);
+        }
+    }
+
+    if !table.board.is_empty() {
+        println!("\n{} {}", "Board:".green(), format_cards(&table.board));
+    }
+    // Bets are only shown while the hand is still being played
+    let hand_over = replay.step_events(step).iter().any(|event| matches!(event, GameEvent::ShowdownStarted | GameEvent::HandEnded { .. }));
+    if !hand_over {
+        print_bets(&table.seats, None, None);
+    }
+    print_stacks(&table, show_cards);
+    println!("Pot: {}\n", format!("{}", table.pot).bold());
+}
+
+// The chips of every player, with their hole cards when they can be seen
+fn print_stacks(table: &ReplayTable, show_cards: bool) {
+    println!("{}", "+==================================================+".bold().cyan());
+    for (i, seat) in table.seats.iter().enumerate() {
+        if !seat.playing {
+            continue;
+        }
+        let mut line = format!("{}: {}", seat.name, seat.chips);
+        if i == table.button {
+            line += " (dealer)";
+        }
+        if (show_cards || table.shown[i]) && !table.hole_cards[i].is_empty() {
+            line += &format!("  [{}]", table.hole_cards[i].iter().map(|card| card.code()).collect::<Vec<_>>().join(" "));
+        }
+        println!("{}{:<50}{}", "|".bold().cyan(), line, "|".bold().cyan());
+    }
+    println!("{}", "+==================================================+".bold().cyan());
+}
+
+// The stats subcommand: the statistics of every player over the hands in an event log
+pub fn stats(args: &[String]) -> Result<(), String> {
+    let [path] = args else {
+        return Err("Usage: texas-holdem stats <event log>".to_string());
+    };
+    let text = fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    let hands = read_hands(&text).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    let stats = Stats::from_events(hands.iter().flatten());
+    if stats.players.is_empty() {
+        return Err(format!("There are no hands in {}", path));
+    }
+    print!("{}", stats.table());
+    Ok(())
+}
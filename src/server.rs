@@ -0,0 +1,572 @@
+use crate::bot::BotKind;
+use crate::card::Card;
+use crate::clock::{Clock, ShotClock};
+use crate::engine::{ActionRequest, Controller, Table};
+use crate::eval::BestHand;
+use crate::events::{Action, EventSink, GameEvent};
+use crate::protocol::{legal_actions, receive, send, ClientMessage, ErrorCode, ServerMessage, PROTOCOL_VERSION};
+use crate::spectators::{RevealDelay, SpectatorFeed, Spectators};
+use crate::web;
+use rand::Rng;
+use std::cell::RefCell;
+use std::io::{self, BufReader};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const MAX_NAME_LENGTH: usize = 20;
+// How long a new connection has to ask for a seat before it is dropped
+const JOIN_TIMEOUT: Duration = Duration::from_secs(30);
+pub const DEFAULT_DISCONNECT_BANK: Duration = Duration::from_secs(30);
+// Answers are waited for a little longer than the time limit, for the time they take to arrive
+const ANSWER_GRACE: Duration = Duration::from_secs(2);
+// How long a message may take to send before the player is taken to have gone away, so a client
+// that stops reading can't hold up the table once its socket's buffer is full
+pub const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/*
+ * How messages travel between the server and a player: as frames over TCP, or as WebSocket
+ * messages from a browser.
+ */
+pub trait Transport: Send {
+    fn send(&mut self, message: &ServerMessage) -> io::Result<()>;
+
+    // The errors are those of protocol::receive: UnexpectedEof once closed, InvalidData for frames that aren't messages
+    fn receive(&mut self) -> io::Result<ClientMessage>;
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+// A client speaking the protocol over TCP
+pub struct TcpTransport {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl TcpTransport {
+    pub fn new(stream: TcpStream) -> io::Result<TcpTransport> {
+        // Messages are small and answered one at a time, so they are sent straight away
+        stream.set_nodelay(true)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(TcpTransport { reader, writer: stream })
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, message: &ServerMessage) -> io::Result<()> {
+        send(&mut self.writer, message)
+    }
+
+    fn receive(&mut self) -> io::Result<ClientMessage> {
+        receive(&mut self.reader)
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.writer.set_read_timeout(timeout)
+    }
+}
+
+/*
+ * The server's end of a connection to a player. Once the player has gone away nothing more
+ * is sent or read, so the game carries on without them.
+ */
+pub struct Connection {
+    transport: Box<dyn Transport>,
+    connected: bool,
+}
+
+impl Connection {
+    pub fn new(transport: Box<dyn Transport>) -> Connection {
+        Connection { transport, connected: true }
+    }
+
+    pub fn connected(&self) -> bool {
+        self.connected
+    }
+
+    // A message that can't be sent in time, or at all, drops the connection
+    pub fn send(&mut self, message: &ServerMessage) {
+        if self.connected && self.transport.send(message).is_err() {
+            self.connected = false;
+        }
+    }
+
+    // The next message from the player, None once they have gone away. Frames that aren't messages are answered with an error.
+    pub fn receive(&mut self) -> Option<ClientMessage> {
+        self.receive_before(None)
+    }
+
+    // Like receive, but also None once the deadline has passed, the player is still connected then
+    pub fn receive_before(&mut self, deadline: Option<Instant>) -> Option<ClientMessage> {
+        while self.connected {
+            if let Some(deadline) = deadline {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() || self.transport.set_read_timeout(Some(left)).is_err() {
+                    return None;
+                }
+            }
+            let received = self.transport.receive();
+            if deadline.is_some() {
+                let _ = self.transport.set_read_timeout(None);
+            }
+            match received {
+                Ok(message) => return Some(message),
+                Err(error) if matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => return None,
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                    self.send(&ServerMessage::error(ErrorCode::InvalidMessage, &error.to_string()));
+                }
+                Err(_) => self.connected = false,
+            }
+        }
+        None
+    }
+
+    /*
+     * Waits for the client to ask for a seat with a name that isn't taken, to take back a seat
+     * with one of the players' tokens, or to watch. New seats are given while the lobby has
+     * some left. None if the client goes away first.
+     */
+    fn join(&mut self, lobby: &Mutex<Lobby>) -> Option<Arrival> {
+        let _ = self.transport.set_read_timeout(Some(JOIN_TIMEOUT));
+        let arrival = loop {
+            let error = match self.receive()? {
+                ClientMessage::Join { version, .. } | ClientMessage::Rejoin { version, .. } | ClientMessage::Watch { version }
+                    if version != PROTOCOL_VERSION =>
+                {
+                    let message = format!("the server speaks protocol version {}, not {}", PROTOCOL_VERSION, version);
+                    ServerMessage::error(ErrorCode::UnsupportedVersion, &message)
+                }
+                ClientMessage::Watch { .. } => break Arrival::Spectator,
+                ClientMessage::Rejoin { token, .. } => match lobby.lock().unwrap().tokens.iter().position(|other| *other == token) {
+                    Some(seat) => break Arrival::Returning(seat),
+                    None => ServerMessage::error(ErrorCode::InvalidToken, "no player has this token"),
+                },
+                ClientMessage::Join { name, .. } => match lobby.lock().unwrap().claim(&name) {
+                    Ok((seat, name)) => break Arrival::Player(seat, name),
+                    Err((code, message)) => ServerMessage::error(code, &message),
+                },
+                _ => ServerMessage::error(ErrorCode::UnexpectedMessage, "join a seat first"),
+            };
+            self.send(&error);
+        };
+        let _ = self.transport.set_read_timeout(None);
+        Some(arrival)
+    }
+}
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates what a new connection has asked for: the seat it was given for the named
+ * player, the seat of a player coming back, or to watch.
+ */
+enum Arrival {
+    Player(usize, String),
+    Returning(usize),
+    Spectator,
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * The players' seats, handed out in order as the players join: the names taken so far, the
+ * computer players' included, and the token of every seat that has been taken.
+ */
+struct Lobby {
+    taken: Vec<String>,
+    tokens: Vec<String>,
+    seats: usize,
+}
+
+impl Lobby {
+    // Gives the player the next seat and a token for it, returns the seat and the name without the surrounding spaces
+    fn claim(&mut self, name: &str) -> Result<(usize, String), (ErrorCode, String)> {
+        if self.tokens.len() == self.seats {
+            return Err((ErrorCode::TableFull, "the game has started, but you can still watch".to_string()));
+        }
+        let name = check_name(name).map_err(|message| (ErrorCode::InvalidName, message))?;
+        if self.taken.iter().any(|other| other.eq_ignore_ascii_case(&name)) {
+            return Err((ErrorCode::NameTaken, format!("the name {} is taken", name)));
+        }
+        self.taken.push(name.clone());
+        self.tokens.push(new_token());
+        Ok((self.tokens.len() - 1, name))
+    }
+}
+
+// A secret for a player to take back their seat with, 128 random bits in hex
+fn new_token() -> String {
+    format!("{:032x}", rand::thread_rng().gen::<u128>())
+}
+
+// Checks a player's name, returns it without the surrounding spaces
+pub fn check_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("a name is needed to join".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("names can be at most {} characters, got {}", MAX_NAME_LENGTH, name.chars().count()));
+    }
+    Ok(name.to_string())
+}
+
+/*
+ * The players at other computers, in seat order. A player who loses their connection can come
+ * back with the token they were welcomed with: the lobby hands their new connection over through
+ * returning, and they are sent the hand being played so far, their hole cards included.
+ * Meanwhile their seat has a time bank to wait for them, before it checks or folds for them.
+ */
+pub struct RemotePlayers {
+    connections: Vec<Connection>,
+    tokens: Vec<String>,
+    returning: Receiver<(usize, Box<dyn Transport>)>,
+    // What each player's time bank has left, it is filled up again when they come back
+    banks: Vec<Duration>,
+    time_bank: Duration,
+    // Each player's shot clock, None without one
+    clocks: Vec<Option<Clock>>,
+    // The events of the hand being played, to bring returning players up to date
+    hand: Vec<GameEvent>,
+}
+
+impl RemotePlayers {
+    pub fn new(
+        connections: Vec<Connection>,
+        tokens: Vec<String>,
+        returning: Receiver<(usize, Box<dyn Transport>)>,
+        time_bank: Duration,
+        shot_clock: Option<ShotClock>,
+    ) -> RemotePlayers {
+        let banks = vec![time_bank; connections.len()];
+        let clocks = vec![shot_clock.map(Clock::new); connections.len()];
+        RemotePlayers { connections, tokens, returning, banks, time_bank, clocks, hand: Vec::new() }
+    }
+
+    pub fn connected(&self, seat: usize) -> bool {
+        self.connections[seat].connected()
+    }
+
+    pub fn any_connected(&self) -> bool {
+        self.connections.iter().any(|connection| connection.connected())
+    }
+
+    // Gives a returning player their seat back and sends them what they missed of the hand
+    fn welcome_back(&mut self, seat: usize, transport: Box<dyn Transport>) {
+        let connection = &mut self.connections[seat];
+        *connection = Connection::new(transport);
+        connection.send(&ServerMessage::Welcome { version: PROTOCOL_VERSION, seat, token: self.tokens[seat].clone() });
+        for event in &self.hand {
+            if sees(seat, event) {
+                connection.send(&ServerMessage::Event(event.clone()));
+            }
+        }
+        self.banks[seat] = self.time_bank;
+    }
+
+    // Welcomes back every player who has come back by now
+    pub fn check_returning(&mut self) {
+        while let Ok((seat, transport)) = self.returning.try_recv() {
+            self.welcome_back(seat, transport);
+        }
+    }
+
+    // Waits for the player of seat to come back, using up their time bank. False if it runs out first.
+    fn wait_for(&mut self, seat: usize) -> bool {
+        while !self.connected(seat) {
+            let started = Instant::now();
+            let returned = self.returning.recv_timeout(self.banks[seat]);
+            self.banks[seat] = self.banks[seat].saturating_sub(started.elapsed());
+            match returned {
+                Ok((seat, transport)) => self.welcome_back(seat, transport),
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+
+    // Waits up to a full time bank for any player to come back, false if none does
+    pub fn wait_for_anyone(&mut self) -> bool {
+        match self.returning.recv_timeout(self.time_bank) {
+            Ok((seat, transport)) => {
+                self.welcome_back(seat, transport);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+// Whether the player of seat may see the event, they only see their own hole cards
+fn sees(seat: usize, event: &GameEvent) -> bool {
+    !matches!(event, GameEvent::HoleCards { seat: owner, .. } if *owner != seat)
+}
+
+// A player at another computer, asked for their actions over their connection
+pub struct RemotePlayer {
+    pub seat: usize,
+    pub players: Rc<RefCell<RemotePlayers>>,
+}
+
+impl RemotePlayer {
+    // Asks the player for their action until the deadline. None if they go away first, or check or fold once it passes.
+    fn ask(&self, players: &mut RemotePlayers, request: &ActionRequest, deadline: Option<Instant>) -> Option<Action> {
+        let mut request = request.clone();
+        if let (Some(deadline), Some(limit)) = (deadline, request.time_limit) {
+            // A player who has come back only has the time that is left
+            let left = deadline.saturating_duration_since(Instant::now() + ANSWER_GRACE).as_millis().div_ceil(1000) as u32;
+            request.time_bank = request.time_bank.saturating_sub(limit - left.min(limit));
+            request.time_limit = Some(left);
+        }
+        let connection = &mut players.connections[self.seat];
+        connection.send(&ServerMessage::ActionRequest { legal: legal_actions(&request), request: request.clone() });
+        loop {
+            match connection.receive_before(deadline) {
+                Some(ClientMessage::Act { action }) if request.is_legal(action) => return Some(action),
+                Some(ClientMessage::Act { action }) => {
+                    let message = format!("{:?} is not one of the legal actions", action);
+                    connection.send(&ServerMessage::error(ErrorCode::IllegalAction, &message));
+                }
+                Some(_) => connection.send(&ServerMessage::error(ErrorCode::UnexpectedMessage, "expected an action")),
+                None if connection.connected() => return Some(request.check_or_fold()),
+                None => return None,
+            }
+        }
+    }
+}
+
+impl Controller for RemotePlayer {
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        let mut players = self.players.borrow_mut();
+        let mut request = request.clone();
+        // The shot clock runs from the first time they are asked, even if they go away meanwhile
+        let mut deadline = None;
+        if let Some(clock) = &mut players.clocks[self.seat] {
+            clock.start(&mut request);
+            deadline = request.time_limit.map(|limit| Instant::now() + Duration::from_secs(limit as u64) + ANSWER_GRACE);
+        }
+        // A player who has gone away is waited for, and asked again once they are back
+        let mut action = None;
+        while action.is_none() && players.wait_for(self.seat) {
+            action = self.ask(&mut players, &request, deadline);
+        }
+        if let Some(clock) = &mut players.clocks[self.seat] {
+            clock.stop();
+        }
+        // Once their time bank has run out, they check or fold
+        action.unwrap_or_else(|| request.check_or_fold())
+    }
+
+    fn show_hand(&mut self, cards: &[Card], hand: Option<&BestHand>, uncontested: bool) -> bool {
+        let mut players = self.players.borrow_mut();
+        // The player has an action's time to answer, or with no shot clock as long as their seat
+        // waits for them when they are away, so one who never answers doesn't hold up the table
+        let limit = match &players.clocks[self.seat] {
+            Some(clock) => clock.action_time(),
+            None => players.banks[self.seat],
+        };
+        let deadline = Instant::now() + limit + ANSWER_GRACE;
+        let connection = &mut players.connections[self.seat];
+        let best_five = hand.map(|hand| hand.cards.clone()).unwrap_or_default();
+        connection.send(&ServerMessage::ShowRequest { cards: cards.to_vec(), best_five, uncontested });
+        loop {
+            match connection.receive_before(Some(deadline)) {
+                Some(ClientMessage::Show { show }) => return show,
+                Some(_) => connection.send(&ServerMessage::error(ErrorCode::UnexpectedMessage, "expected show or muck")),
+                // A player who has gone away or run out of time mucks
+                None => return false,
+            }
+        }
+    }
+}
+
+/*
+ * Sends every event to the players at other computers. Each player only gets their own hole
+ * cards, the others are only seen when they are shown. Players who have come back are
+ * welcomed before the next event.
+ */
+pub struct RemoteDisplay(pub Rc<RefCell<RemotePlayers>>);
+
+impl EventSink for RemoteDisplay {
+    fn handle(&mut self, event: &GameEvent) {
+        let mut players = self.0.borrow_mut();
+        players.check_returning();
+        if let GameEvent::HandStarted { .. } = event {
+            players.hand.clear();
+        }
+        players.hand.push(event.clone());
+        for (seat, connection) in players.connections.iter_mut().enumerate() {
+            if sees(seat, event) {
+                connection.send(&ServerMessage::Event(event.clone()));
+            }
+        }
+    }
+}
+
+/*
+ * Hosts a table for players at other computers. The first seats are taken by the players in
+ * the order they join, the other seats are played by computer players. The server runs the
+ * engine and deals the cards, the players only get what they are allowed to see.
+ * Connections are accepted on threads of their own and handed to the game through a channel.
+ */
+pub struct TableServer {
+    address: SocketAddr,
+    // Taken by the game's lobby once the players are seated
+    incoming: Option<Receiver<Box<dyn Transport>>>,
+    sender: Sender<Box<dyn Transport>>,
+    pub table: Table,
+    humans: usize,
+    // The computer players of the seats after the players'
+    bots: Vec<BotKind>,
+    // Reveals every player's hole cards to the spectators after the delay, None keeps them hidden
+    pub god_view: Option<RevealDelay>,
+    // How long each player's seat waits for them over the game while they are away
+    pub disconnect_bank: Duration,
+    // How long players have to act, None gives them as long as they like
+    pub shot_clock: Option<ShotClock>,
+}
+
+impl TableServer {
+    pub fn bind(address: &str, table: Table, humans: usize, bots: Vec<BotKind>) -> io::Result<TableServer> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let (sender, incoming) = mpsc::channel::<Box<dyn Transport>>();
+        let players = sender.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let Ok(transport) = TcpTransport::new(stream) else { continue };
+                if players.send(Box::new(transport)).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(TableServer { address, incoming: Some(incoming), sender, table, humans, bots, god_view: None, disconnect_bank: DEFAULT_DISCONNECT_BANK, shot_clock: None })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    // Also lets players join from a browser, at the address the page is served from
+    pub fn listen_web(&self, address: &str) -> io::Result<SocketAddr> {
+        web::listen(address, self.sender.clone())
+    }
+
+    /*
+     * Waits until every player's seat is taken, naming the seats after the players, and welcomes
+     * them. Returns their connections.
+     */
+    fn seat_players(&mut self, seated: &Receiver<(usize, String, Box<dyn Transport>)>, lobby: &Mutex<Lobby>) -> io::Result<Vec<Connection>> {
+        let mut players: Vec<Option<Connection>> = (0..self.humans).map(|_| None).collect();
+        for _ in 0..self.humans {
+            let (seat, name, transport) = seated.recv().map_err(|_| io::Error::other("the listener stopped"))?;
+            self.table.seats[seat].name = name;
+            let mut connection = Connection::new(transport);
+            let token = lobby.lock().unwrap().tokens[seat].clone();
+            connection.send(&ServerMessage::Welcome { version: PROTOCOL_VERSION, seat, token });
+            players[seat] = Some(connection);
+        }
+        Ok(players.into_iter().flatten().collect())
+    }
+
+    /*
+     * Answers every new connection on a thread of its own, so one that is slow to ask for
+     * anything doesn't hold up the others. Players given a seat are handed over through
+     * seated, players taking back their seat through returning, and spectators may come in
+     * at any time.
+     */
+    fn open_lobby(
+        incoming: Receiver<Box<dyn Transport>>,
+        lobby: Arc<Mutex<Lobby>>,
+        seated: Sender<(usize, String, Box<dyn Transport>)>,
+        returning: Sender<(usize, Box<dyn Transport>)>,
+        spectators: Arc<Mutex<Spectators>>,
+    ) {
+        thread::spawn(move || {
+            for transport in incoming {
+                let (lobby, seated, returning, spectators) = (lobby.clone(), seated.clone(), returning.clone(), spectators.clone());
+                thread::spawn(move || {
+                    let mut connection = Connection::new(transport);
+                    match connection.join(&lobby) {
+                        Some(Arrival::Player(seat, name)) => {
+                            let _ = seated.send((seat, name, connection.transport));
+                        }
+                        Some(Arrival::Returning(seat)) => {
+                            let _ = returning.send((seat, connection.transport));
+                        }
+                        Some(Arrival::Spectator) => spectators.lock().unwrap().add(connection.transport),
+                        None => {}
+                    }
+                });
+            }
+        });
+    }
+
+    /*
+     * Seats the players and plays until one of them has all the chips, every player has gone
+     * away or the given number of hands has been played. sinks also get every event, e.g. to
+     * write a hand history on the server. A server runs one game.
+     * Players who are still away at the end of a hand sit out until they come back.
+     */
+    pub fn run(&mut self, hands: Option<u32>, mut sinks: Vec<Box<dyn EventSink>>) -> io::Result<()> {
+        let incoming = self.incoming.take().ok_or_else(|| io::Error::other("the game has already been played"))?;
+        let spectators = Spectators::new(self.god_view);
+        // The computer players' names are taken from the start
+        let taken = self.table.seats[self.humans..].iter().map(|seat| seat.name.clone()).collect();
+        let lobby = Arc::new(Mutex::new(Lobby { taken, tokens: Vec::new(), seats: self.humans }));
+        let (seated, seats) = mpsc::channel();
+        let (returning, returned) = mpsc::channel();
+        TableServer::open_lobby(incoming, lobby.clone(), seated, returning, spectators.clone());
+        let connections = self.seat_players(&seats, &lobby)?;
+        let tokens = lobby.lock().unwrap().tokens.clone();
+        let players = Rc::new(RefCell::new(RemotePlayers::new(connections, tokens, returned, self.disconnect_bank, self.shot_clock)));
+
+        let mut controllers: Vec<Box<dyn Controller>> = Vec::new();
+        for seat in 0..self.humans {
+            controllers.push(Box::new(RemotePlayer { seat, players: players.clone() }));
+        }
+        for seat in self.humans..self.table.seats.len() {
+            controllers.push(self.bots.get(seat - self.humans).copied().unwrap_or_default().controller());
+        }
+        sinks.insert(0, Box::new(RemoteDisplay(players.clone())));
+        sinks.insert(1, Box::new(SpectatorFeed(spectators.clone())));
+
+        let mut played = 0;
+        let reason = loop {
+            if self.table.players_with_chips() < 2 {
+                let winner = self.table.seats.iter().find(|seat| seat.chips > 0).map(|seat| seat.name.clone()).unwrap_or_default();
+                break format!("{} has won all the chips", winner);
+            }
+            if hands.is_some_and(|hands| played >= hands) {
+                break "the last hand has been played".to_string();
+            }
+            players.borrow_mut().check_returning();
+            for seat in 0..self.humans {
+                self.table.seats[seat].sitting_out = !players.borrow().connected(seat);
+            }
+            // Without enough players to deal in, the game waits a while for someone to come back
+            if !players.borrow().any_connected() || self.table.players_dealt_in() < 2 {
+                if players.borrow_mut().wait_for_anyone() {
+                    continue;
+                }
+                if !players.borrow().any_connected() {
+                    break "every player has left".to_string();
+                }
+                break "too few players are left".to_string();
+            }
+            self.table.play_hand(&mut controllers, &mut sinks);
+            played += 1;
+        };
+        for connection in &mut players.borrow_mut().connections {
+            connection.send(&ServerMessage::TableClosed { reason: reason.clone() });
+        }
+        // Waits until everything has been written to the spectators
+        let writers = spectators.lock().unwrap().close(&reason);
+        for writer in writers {
+            let _ = writer.join();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::bot::BotKind;
+use crate::engine::Table;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// Bumped whenever a change to the session file would stop older files from loading correctly
+pub const SESSION_VERSION: u32 = 1;
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * Everything needed to carry on a game after the program is closed: the table with its
+ * players, chips, button, blinds, hand number and deck seed, the computer players and
+ * the user's settings.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    pub table: Table,
+    // The kind of computer player at seats 2, 3 and so on
+    #[serde(default)]
+    pub bots: Vec<BotKind>,
+    pub always_show: bool,
+}
+
+impl Session {
+    pub fn new(table: Table, bots: Vec<BotKind>, always_show: bool) -> Session {
+        Session { version: SESSION_VERSION, table, bots, always_show }
+    }
+
+    /*
+     * Writes the session as JSON. The file is written next to the old one and then renamed over
+     * it, so closing the program while saving never leaves a half-written session behind.
+     */
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        let temp = format!("{}.tmp", path);
+        fs::write(&temp, text).and_then(|_| fs::rename(&temp, path)).map_err(|error| error.to_string())
+    }
+
+    pub fn load(path: &str) -> Result<Session, String> {
+        let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let session: Session = serde_json::from_str(&text).map_err(|error| error.to_string())?;
+        if session.version != SESSION_VERSION {
+            return Err(format!("unsupported session version {}, expected {}", session.version, SESSION_VERSION));
+        }
+        if session.table.seats.len() < 2 {
+            return Err("a session needs at least 2 players".to_string());
+        }
+        Ok(session)
+    }
+}
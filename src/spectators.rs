@@ -0,0 +1,184 @@
+use crate::card::Card;
+use crate::events::{EventSink, GameEvent};
+use crate::protocol::{ServerMessage, PROTOCOL_VERSION};
+use crate::server::Transport;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// How often the cards of a god view with a delay in seconds are checked
+const REVEAL_TICK: Duration = Duration::from_millis(100);
+
+/*
+ * This is an enumeration type (Sebesta, 6.4)
+ * It enumerates how long every player's hole cards are kept from the spectators of a god view:
+ * until the given number of hands has ended since they were dealt, 1 being the end of their own
+ * hand, or for the given number of seconds after they were dealt.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevealDelay {
+    Hands(u32),
+    Seconds(u64),
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * A player's hole cards waiting to be revealed to the spectators.
+ */
+struct Hidden {
+    hand_number: u32,
+    seat: usize,
+    cards: Vec<Card>,
+    dealt_at: Instant,
+    // The number of hands that had ended when they were dealt
+    hands_ended: u32,
+}
+
+/*
+ * The people following a table without a seat. They are sent the public events: the stacks,
+ * the actions and the board, but no hole cards until the players show them. With a god view
+ * every player's hole cards are revealed after a delay, so a spectator can't tell the players.
+ * Each spectator is written to on a thread of their own, so a slow one never holds up the game.
+ */
+pub struct Spectators {
+    watchers: Vec<Sender<ServerMessage>>,
+    writers: Vec<JoinHandle<()>>,
+    god_view: Option<RevealDelay>,
+    // The public messages of the hand being played, for spectators who arrive during it
+    hand: Vec<ServerMessage>,
+    hand_number: u32,
+    hands_ended: u32,
+    hidden: Vec<Hidden>,
+    // Why the table closed, once it has
+    closed: Option<String>,
+}
+
+impl Spectators {
+    pub fn new(god_view: Option<RevealDelay>) -> Arc<Mutex<Spectators>> {
+        let spectators = Arc::new(Mutex::new(Spectators {
+            watchers: Vec::new(),
+            writers: Vec::new(),
+            god_view,
+            hand: Vec::new(),
+            hand_number: 0,
+            hands_ended: 0,
+            hidden: Vec::new(),
+            closed: None,
+        }));
+        if let Some(RevealDelay::Seconds(_)) = god_view {
+            let clock = spectators.clone();
+            thread::spawn(move || loop {
+                thread::sleep(REVEAL_TICK);
+                let mut spectators = clock.lock().unwrap();
+                if spectators.closed.is_some() {
+                    break;
+                }
+                spectators.reveal_due();
+            });
+        }
+        spectators
+    }
+
+    // Starts sending the table to a new spectator, who first catches up on the hand being played
+    pub fn add(&mut self, mut transport: Box<dyn Transport>) {
+        let watching = ServerMessage::Watching { version: PROTOCOL_VERSION, god_view: self.god_view.is_some() };
+        if let Some(reason) = &self.closed {
+            let _ = transport.send(&watching);
+            let _ = transport.send(&ServerMessage::TableClosed { reason: reason.clone() });
+            return;
+        }
+        let (watcher, messages) = mpsc::channel::<ServerMessage>();
+        self.writers.push(thread::spawn(move || {
+            for message in messages {
+                if transport.send(&message).is_err() {
+                    break;
+                }
+            }
+        }));
+        let _ = watcher.send(watching);
+        for message in &self.hand {
+            let _ = watcher.send(message.clone());
+        }
+        self.watchers.push(watcher);
+    }
+
+    // Sends to every spectator, forgetting those who have gone away
+    fn broadcast(&mut self, message: ServerMessage) {
+        self.watchers.retain(|watcher| watcher.send(message.clone()).is_ok());
+    }
+
+    fn reveal(&mut self, hidden: Hidden) {
+        let message = ServerMessage::Revealed { hand_number: hidden.hand_number, seat: hidden.seat, cards: hidden.cards };
+        if hidden.hand_number == self.hand_number {
+            self.hand.push(message.clone());
+        }
+        self.broadcast(message);
+    }
+
+    // Reveals the hole cards whose delay is over
+    fn reveal_due(&mut self) {
+        let (hands_ended, now) = (self.hands_ended, Instant::now());
+        let due = |hidden: &Hidden| match self.god_view {
+            Some(RevealDelay::Hands(hands)) => hands_ended - hidden.hands_ended >= hands,
+            Some(RevealDelay::Seconds(seconds)) => now.duration_since(hidden.dealt_at).as_secs() >= seconds,
+            None => false,
+        };
+        let (due, hidden): (Vec<Hidden>, Vec<Hidden>) = self.hidden.drain(..).partition(due);
+        self.hidden = hidden;
+        for hidden in due {
+            self.reveal(hidden);
+        }
+    }
+
+    /*
+     * Tells every spectator the table has closed. The game is over, so the hole cards still
+     * hidden are revealed first. Returns the threads writing to the spectators, to be waited
+     * for once the spectators are unlocked, so the ones arriving meanwhile aren't held up.
+     */
+    pub fn close(&mut self, reason: &str) -> Vec<JoinHandle<()>> {
+        for hidden in std::mem::take(&mut self.hidden) {
+            self.reveal(hidden);
+        }
+        self.broadcast(ServerMessage::TableClosed { reason: reason.to_string() });
+        self.closed = Some(reason.to_string());
+        self.watchers.clear();
+        std::mem::take(&mut self.writers)
+    }
+}
+
+// Sends a table's events to its spectators
+pub struct SpectatorFeed(pub Arc<Mutex<Spectators>>);
+
+impl EventSink for SpectatorFeed {
+    fn handle(&mut self, event: &GameEvent) {
+        let mut spectators = self.0.lock().unwrap();
+        match event {
+            GameEvent::HandStarted { hand_number, .. } => {
+                spectators.hand.clear();
+                spectators.hand_number = *hand_number;
+            }
+            GameEvent::HoleCards { seat, cards } => {
+                if spectators.god_view.is_some() {
+                    let hidden = Hidden {
+                        hand_number: spectators.hand_number,
+                        seat: *seat,
+                        cards: cards.clone(),
+                        dealt_at: Instant::now(),
+                        hands_ended: spectators.hands_ended,
+                    };
+                    spectators.hidden.push(hidden);
+                }
+                return;
+            }
+            _ => {}
+        }
+        let message = ServerMessage::Event(event.clone());
+        spectators.hand.push(message.clone());
+        spectators.broadcast(message);
+        if let GameEvent::HandEnded { .. } = event {
+            spectators.hands_ended += 1;
+            spectators.reveal_due();
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::events::{Action, EventSink, GameEvent};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * What a player has done over the session, counted in hands. The statistics are worked out
+ * from these counts:
+ *
+ *     VPIP   hands they put chips in before the flop without having to, as a share of hands dealt
+ *     PFR    hands they raised before the flop
+ *     3-bet  hands they raised a single raise before the flop, out of the hands they could
+ *     AF     bets and raises after the flop for every call after the flop
+ *     WTSD   hands they went to showdown, out of the hands they saw the flop
+ *     W$SD   showdowns they won chips at
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerStats {
+    pub name: String,
+    pub hands: u32,
+    pub voluntary: u32,
+    pub raised: u32,
+    pub three_bet_chances: u32,
+    pub three_bets: u32,
+    // Bets and raises, and calls, after the flop
+    pub aggressive: u32,
+    pub calls: u32,
+    pub saw_flop: u32,
+    pub showdowns: u32,
+    pub showdowns_won: u32,
+}
+
+// A count as a percentage of another, None when there was nothing to count
+fn percent(count: u32, of: u32) -> Option<f64> {
+    (of > 0).then(|| count as f64 * 100.0 / of as f64)
+}
+
+impl PlayerStats {
+    pub fn vpip(&self) -> Option<f64> {
+        percent(self.voluntary, self.hands)
+    }
+
+    pub fn pfr(&self) -> Option<f64> {
+        percent(self.raised, self.hands)
+    }
+
+    pub fn three_bet(&self) -> Option<f64> {
+        percent(self.three_bets, self.three_bet_chances)
+    }
+
+    // None until the player has called after the flop, or infinite aggression would show
+    pub fn aggression(&self) -> Option<f64> {
+        (self.calls > 0).then(|| self.aggressive as f64 / self.calls as f64)
+    }
+
+    pub fn wtsd(&self) -> Option<f64> {
+        percent(self.showdowns, self.saw_flop)
+    }
+
+    pub fn won_at_showdown(&self) -> Option<f64> {
+        percent(self.showdowns_won, self.showdowns)
+    }
+
+    // The statistics in a line for the table of current bets, e.g. "VPIP 25 PFR 18 3B 5 AF 2.0 WTSD 30 W$SD 50 (40 hands)"
+    pub fn hud(&self) -> String {
+        format!(
+            "VPIP {} PFR {} 3B {} AF {} WTSD {} W$SD {} ({} {})",
+            whole(self.vpip()),
+            whole(self.pfr()),
+            whole(self.three_bet()),
+            self.aggression().map_or("-".to_string(), |af| format!("{:.1}", af)),
+            whole(self.wtsd()),
+            whole(self.won_at_showdown()),
+            self.hands,
+            if self.hands == 1 { "hand" } else { "hands" }
+        )
+    }
+}
+
+fn whole(percent: Option<f64>) -> String {
+    percent.map_or("-".to_string(), |percent| format!("{:.0}", percent))
+}
+
+// What each player has done in the hand being played, so every hand counts once
+#[derive(Debug, Clone, Default)]
+struct HandFlags {
+    dealt: bool,
+    voluntary: bool,
+    raised: bool,
+    three_bet_chance: bool,
+    three_bet: bool,
+    folded: bool,
+    saw_flop: bool,
+    showdown: bool,
+    won: bool,
+}
+
+/*
+ * The statistics of every seat over the session, taken from the game's events.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub players: Vec<PlayerStats>,
+    hand: Vec<HandFlags>,
+    // The raises made before the flop so far, the flop ends the counting
+    preflop_raises: Option<u32>,
+    showdown: bool,
+}
+
+impl Stats {
+    pub fn new() -> Stats {
+        Stats::default()
+    }
+
+    // Counts the events of a whole game, e.g. read from an event log
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a GameEvent>) -> Stats {
+        let mut stats = Stats::new();
+        for event in events {
+            stats.handle(event);
+        }
+        stats
+    }
+
+    pub fn player(&self, seat: usize) -> Option<&PlayerStats> {
+        self.players.get(seat)
+    }
+
+    // Adds the hand just finished to the players' counts
+    fn count_hand(&mut self) {
+        for (player, flags) in self.players.iter_mut().zip(&self.hand) {
+            if !flags.dealt {
+                continue;
+            }
+            player.hands += 1;
+            player.voluntary += flags.voluntary as u32;
+            player.raised += flags.raised as u32;
+            player.three_bet_chances += flags.three_bet_chance as u32;
+            player.three_bets += flags.three_bet as u32;
+            player.saw_flop += flags.saw_flop as u32;
+            player.showdowns += flags.showdown as u32;
+            player.showdowns_won += flags.won as u32;
+        }
+    }
+
+    /*
+     * The full table of every player's statistics, one player per row.
+     * The String type in Rust is a sequence of Unicode characters (Sebesta, 6.3).
+     */
+    pub fn table(&self) -> String {
+        let width = self.players.iter().map(|player| player.name.chars().count()).max().unwrap_or(0).max(6) + 2;
+        let mut table = format!("{:<width$}{:>7}{:>7}{:>7}{:>7}{:>7}{:>7}{:>7}\n", "Player", "Hands", "VPIP", "PFR", "3-bet", "AF", "WTSD", "W$SD");
+        for player in &self.players {
+            table += &format!(
+                "{:<width$}{:>7}{:>7}{:>7}{:>7}{:>7}{:>7}{:>7}\n",
+                player.name,
+                player.hands,
+                whole(player.vpip()),
+                whole(player.pfr()),
+                whole(player.three_bet()),
+                player.aggression().map_or("-".to_string(), |af| format!("{:.1}", af)),
+                whole(player.wtsd()),
+                whole(player.won_at_showdown())
+            );
+        }
+        table
+    }
+}
+
+impl EventSink for Stats {
+    fn handle(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::HandStarted { seats, .. } => {
+                if self.players.len() < seats.len() {
+                    self.players.resize(seats.len(), PlayerStats::default());
+                }
+                for info in seats {
+                    self.players[info.seat].name = info.name.clone();
+                }
+                self.hand = vec![HandFlags::default(); self.players.len()];
+                self.preflop_raises = Some(0);
+                self.showdown = false;
+            }
+            // A log may start in the middle of a hand, which is left out
+            _ if self.hand.is_empty() => {}
+            GameEvent::HoleCards { seat, .. } => self.hand[*seat].dealt = true,
+            // The flop ends the betting before it
+            GameEvent::StreetDealt { .. } if self.preflop_raises.take().is_some() => {
+                for flags in self.hand.iter_mut().filter(|flags| flags.dealt && !flags.folded) {
+                    flags.saw_flop = true;
+                }
+            }
+            GameEvent::PlayerActed { seat, action, .. } => {
+                let flags = &mut self.hand[*seat];
+                match self.preflop_raises.as_mut() {
+                    Some(raises) => {
+                        // Facing a single raise, a raise is a 3-bet
+                        if *raises == 1 {
+                            flags.three_bet_chance = true;
+                        }
+                        match action {
+                            Action::Call => flags.voluntary = true,
+                            Action::Raise(_) => {
+                                flags.voluntary = true;
+                                flags.raised = true;
+                                flags.three_bet |= *raises == 1;
+                                *raises += 1;
+                            }
+                            _ => {}
+                        }
+                    }
+                    None => match action {
+                        Action::Call => self.players[*seat].calls += 1,
+                        Action::Raise(_) => self.players[*seat].aggressive += 1,
+                        _ => {}
+                    },
+                }
+                if *action == Action::Fold {
+                    flags.folded = true;
+                }
+            }
+            GameEvent::ShowdownStarted => {
+                self.showdown = true;
+                for flags in self.hand.iter_mut().filter(|flags| flags.dealt && !flags.folded) {
+                    flags.showdown = true;
+                }
+            }
+            GameEvent::PotAwarded { seat, .. } if self.showdown => self.hand[*seat].won = true,
+            GameEvent::HandEnded { .. } => {
+                self.count_hand();
+                self.hand.clear();
+            }
+            _ => {}
+        }
+    }
+}
+
+// Statistics shared by the sink that counts them and the display that shows them
+#[derive(Clone, Default)]
+pub struct SharedStats(pub Rc<RefCell<Stats>>);
+
+impl EventSink for SharedStats {
+    fn handle(&mut self, event: &GameEvent) {
+        self.0.borrow_mut().handle(event);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::eval::{HandRank, Ranking};
+use crate::events::{EventSink, GameEvent};
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+// The stack chart is at most this wide, longer sessions are drawn with some hands left out
+pub const CHART_WIDTH: usize = 60;
+pub const CHART_HEIGHT: usize = 12;
+// The marks of the players on the stack chart, by seat
+const MARKS: &str = "123456789ABCDEFGHIJKLMN";
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * One hand of the session: every seat's chips before and after it, and the pot.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandResult {
+    pub hand_number: u32,
+    pub big_blind: i32,
+    // Whether each seat was dealt in
+    pub dealt: Vec<bool>,
+    pub start: Vec<i32>,
+    pub chips: Vec<i32>,
+    // Every pot and side pot awarded, and the seats they went to
+    pub pot: i32,
+    pub winners: Vec<usize>,
+}
+
+impl HandResult {
+    pub fn net(&self, seat: usize) -> i32 {
+        self.chips[seat] - self.start[seat]
+    }
+}
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * The best hand shown down over the session.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShownHand {
+    pub seat: usize,
+    pub hand_number: u32,
+    pub rank: HandRank,
+    pub description: String,
+}
+
+/*
+ * The results of a session, taken from the game's events: the hands played, what every player
+ * won or lost, the biggest pot and the best hand shown down.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct SessionSummary {
+    pub names: Vec<String>,
+    pub hands: Vec<HandResult>,
+    pub best_hand: Option<ShownHand>,
+    // The hand being played, added to hands when it ends
+    current: Option<HandResult>,
+    // How the game being played ranks its hands
+    ranking: Ranking,
+}
+
+impl SessionSummary {
+    pub fn new() -> SessionSummary {
+        SessionSummary::default()
+    }
+
+    // Every seat's chips before the first hand
+    pub fn starting_chips(&self) -> Vec<i32> {
+        self.hands.first().map(|hand| hand.start.clone()).unwrap_or_default()
+    }
+
+    // Every seat's chips after the last hand
+    pub fn final_chips(&self) -> Vec<i32> {
+        self.hands.last().map(|hand| hand.chips.clone()).unwrap_or_default()
+    }
+
+    pub fn hands_played(&self, seat: usize) -> usize {
+        self.hands.iter().filter(|hand| hand.dealt[seat]).count()
+    }
+
+    pub fn net(&self, seat: usize) -> i32 {
+        self.hands.iter().map(|hand| hand.net(seat)).sum()
+    }
+
+    // Big blinds won for every 100 hands played, each hand counted in its own big blind
+    pub fn big_blinds_per_100(&self, seat: usize) -> Option<f64> {
+        let played = self.hands_played(seat);
+        let won: f64 = self.hands.iter().filter(|hand| hand.dealt[seat]).map(|hand| hand.net(seat) as f64 / hand.big_blind as f64).sum();
+        (played > 0).then(|| won * 100.0 / played as f64)
+    }
+
+    // The hand with the most chips in its pots, the earliest of equal ones
+    pub fn biggest_pot(&self) -> Option<&HandResult> {
+        self.hands.iter().rev().max_by_key(|hand| hand.pot)
+    }
+
+    /*
+     * The summary as text: the hands played, the biggest pot and best hand, and a table of
+     * every player's chips, what they won or lost and their win rate.
+     * The String type in Rust is a sequence of Unicode characters (Sebesta, 6.3).
+     */
+    pub fn report(&self) -> String {
+        let mut report = format!("Hands played: {}\n", self.hands.len());
+        if let Some(hand) = self.biggest_pot().filter(|hand| hand.pot > 0) {
+            let winners: Vec<&str> = hand.winners.iter().map(|&seat| self.names[seat].as_str()).collect();
+            report += &format!("Biggest pot: {} in hand {}, won by {}\n", hand.pot, hand.hand_number, winners.join(" and "));
+        }
+        if let Some(best) = &self.best_hand {
+            report += &format!("Best hand shown: {} by {} in hand {}\n", best.description, self.names[best.seat], best.hand_number);
+        }
+        let width = self.names.iter().map(|name| name.chars().count()).max().unwrap_or(0).max(6) + 2;
+        report += &format!("\n{:<width$}{:>7}{:>9}{:>9}{:>9}\n", "Player", "Hands", "Chips", "Net", "bb/100");
+        let chips = self.final_chips();
+        for (seat, name) in self.names.iter().enumerate() {
+            let rate = self.big_blinds_per_100(seat).map_or("-".to_string(), |rate| format!("{:.1}", rate));
+            report += &format!("{:<width$}{:>7}{:>9}{:>+9}{:>9}\n", name, self.hands_played(seat), chips[seat], self.net(seat), rate);
+        }
+        report
+    }
+
+    /*
+     * Every player's stack over the session drawn as a chart, from before the first hand to after
+     * the last. Each player is drawn with the mark of their seat, 1 for the first seat; where
+     * players' stacks meet, the later seat is drawn.
+     */
+    pub fn chart(&self, width: usize, height: usize) -> String {
+        // The stacks before the first hand and after each hand
+        let mut points = vec![self.starting_chips()];
+        points.extend(self.hands.iter().map(|hand| hand.chips.clone()));
+        let top = points.iter().flatten().copied().max().unwrap_or(0).max(1);
+        let columns = points.len().min(width.max(2));
+        let mut rows = vec![vec![' '; columns]; height];
+        for column in 0..columns {
+            let point = &points[column * (points.len() - 1) / (columns - 1).max(1)];
+            for (seat, &chips) in point.iter().enumerate() {
+                let row = (chips.max(0) as i64 * (height as i64 - 1) + top as i64 / 2) / top as i64;
+                rows[height - 1 - row as usize][column] = MARKS.chars().nth(seat).unwrap_or('*');
+            }
+        }
+
+        let label = top.to_string().len();
+        let mut chart = String::new();
+        for (i, row) in rows.iter().enumerate() {
+            let value = match i {
+                0 => top.to_string(),
+                _ if i == height - 1 => "0".to_string(),
+                _ => String::new(),
+            };
+            let line: String = row.iter().collect();
+            chart += &format!("{:>label$} |{}\n", value, line.trim_end());
+        }
+        chart += &format!("{:>label$} +{}\n", "", "-".repeat(columns));
+        chart += &format!("{:>label$}  {:<half$}{:>rest$}\n", "", "start", format!("hand {}", self.hands.len()), half = columns / 2, rest = columns - columns / 2);
+        let legend: Vec<String> = self.names.iter().enumerate().map(|(seat, name)| format!("{} {}", MARKS.chars().nth(seat).unwrap_or('*'), name)).collect();
+        chart += &legend.join(", ");
+        chart.push('\n');
+        chart
+    }
+
+    /*
+     * Writes the hands as CSV, a row for every player dealt in to every hand:
+     * hand,seat,player,big_blind,start_chips,end_chips,net,pot
+     */
+    pub fn write_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "hand,seat,player,big_blind,start_chips,end_chips,net,pot")?;
+        for hand in &self.hands {
+            for seat in (0..self.names.len()).filter(|&seat| hand.dealt[seat]) {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{}",
+                    hand.hand_number,
+                    seat + 1,
+                    csv_field(&self.names[seat]),
+                    hand.big_blind,
+                    hand.start[seat],
+                    hand.chips[seat],
+                    hand.net(seat),
+                    hand.pot
+                )?;
+            }
+        }
+        out.flush()
+    }
+}
+
+// A field quoted when it holds a comma or quote, with its quotes doubled
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+impl EventSink for SessionSummary {
+    fn handle(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::HandStarted { hand_number, big_blind, variant, seats, .. } => {
+                self.ranking = variant.ranking();
+                self.names = seats.iter().map(|info| info.name.clone()).collect();
+                let start: Vec<i32> = seats.iter().map(|info| info.chips).collect();
+                self.current = Some(HandResult {
+                    hand_number: *hand_number,
+                    big_blind: *big_blind,
+                    dealt: seats.iter().map(|info| info.playing).collect(),
+                    chips: start.clone(),
+                    start,
+                    pot: 0,
+                    winners: Vec::new(),
+                });
+            }
+            GameEvent::HandShown { seat, best_five, hand, .. } if best_five.len() == 5 => {
+                let Some(current) = &self.current else { return };
+                let rank = self.ranking.evaluate_cards(best_five);
+                if self.best_hand.as_ref().is_none_or(|best| rank > best.rank) {
+                    self.best_hand = Some(ShownHand { seat: *seat, hand_number: current.hand_number, rank, description: hand.clone() });
+                }
+            }
+            GameEvent::PotAwarded { seat, amount, .. } => {
+                if let Some(current) = &mut self.current {
+                    current.pot += amount;
+                    if !current.winners.contains(seat) {
+                        current.winners.push(*seat);
+                    }
+                }
+            }
+            GameEvent::HandEnded { chips } => {
+                if let Some(mut hand) = self.current.take() {
+                    hand.chips = chips.clone();
+                    self.hands.push(hand);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// A summary shared by the sink that follows the game and the code that prints it at the end
+#[derive(Clone, Default)]
+pub struct SharedSummary(pub Rc<RefCell<SessionSummary>>);
+
+impl EventSink for SharedSummary {
+    fn handle(&mut self, event: &GameEvent) {
+        self.0.borrow_mut().handle(event);
+    }
+}
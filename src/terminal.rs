@@ -0,0 +1,553 @@
+use colored::Colorize;
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use texas_holdem::card::Card;
+use texas_holdem::clock::Countdown;
+use texas_holdem::engine::{ActionRequest, Controller, SeatView};
+use texas_holdem::replay::ReplayTable;
+use texas_holdem::eval::BestHand;
+use texas_holdem::events::{Action, EventSink, GameEvent, PotShare, Street};
+use texas_holdem::odds::{Odds, OddsAgainst, OddsHelper};
+use texas_holdem::stats::{SharedStats, Stats};
+
+// The lines typed by the user, once a read has had to time out. A read of standard input can't
+// be stopped, so from then on every line is read on this thread and handed over.
+static INPUT: OnceLock<Mutex<Receiver<String>>> = OnceLock::new();
+
+fn input() -> &'static Mutex<Receiver<String>> {
+    INPUT.get_or_init(|| {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || loop {
+            let mut line = String::new();
+            // The receiver finds out input is closed when the sender is dropped
+            if !matches!(io::stdin().read_line(&mut line), Ok(n) if n > 0) || sender.send(line).is_err() {
+                break;
+            }
+        });
+        Mutex::new(lines)
+    })
+}
+
+pub fn read_line() -> String {
+    let line = match INPUT.get() {
+        Some(lines) => lines.lock().unwrap().recv().ok(),
+        None => {
+            let mut input = String::new();
+            (io::stdin().read_line(&mut input).expect("Failed to read line") > 0).then_some(input)
+        }
+    };
+    // Nothing more can be read once input is closed, so quit instead of asking forever
+    line.unwrap_or_else(|| std::process::exit(0))
+}
+
+// Reads a line, reminding the user of the time they have left. None once it has run out.
+fn read_line_in_time(countdown: &Countdown) -> Option<String> {
+    let lines = input().lock().unwrap();
+    let (mut in_bank, mut hurried) = (countdown.in_bank(), countdown.seconds_left() <= 10);
+    loop {
+        let left = countdown.deadline().saturating_duration_since(Instant::now());
+        match lines.recv_timeout(left.min(Duration::from_secs(1))) {
+            Ok(line) => return Some(line),
+            Err(RecvTimeoutError::Timeout) if countdown.expired() => return None,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => std::process::exit(0),
+        }
+        if !in_bank && countdown.in_bank() {
+            in_bank = true;
+            println!("{}", format!("Your time is up, using your time bank: {} seconds", countdown.seconds_left()).yellow());
+        } else if !hurried && countdown.seconds_left() <= 10 {
+            hurried = true;
+            println!("{}", format!("{} seconds left", countdown.seconds_left()).red());
+        }
+    }
+}
+
+// Waits for the user to press Enter before the game continues
+fn wait_for_enter(next: &str) {
+    println!("Press {} to {}", "Enter".bold(), next);
+    read_line();
+    println!("{}", "--------------------------------------------------".bold().white());
+}
+
+// Which half of a split pot a player won, e.g. " for low"
+fn share_half(share: PotShare) -> &'static str {
+    match share {
+        PotShare::High => " for high",
+        PotShare::Low => " for low",
+        PotShare::Whole | PotShare::Scoop => "",
+    }
+}
+
+/*
+ * Prints the current bet of every player, as the user sees it when it is their turn.
+ * Every player is listed by name, the hero's line in bold. With stats, each player's
+ * statistics so far are shown next to their bet.
+ */
+pub fn print_bets(seats: &[SeatView], hero: Option<usize>, stats: Option<&Stats>) {
+    println!("\nCurrent bets:");
+    println!("{}", "----------------------------".blue());
+    for (i, seat) in seats.iter().enumerate() {
+        let name = &seat.name;
+        let line = if !seat.playing {
+            format!("{} is out", name)
+        } else if seat.folded {
+            format!("{} has folded", name)
+        } else if seat.all_in {
+            format!("{} all in: {}", name, seat.bet)
+        } else if seat.bet == 0 && !seat.acted {
+            format!("{} has not bet yet", name)
+        } else {
+            format!("{}'s current bet: {}", name, seat.bet)
+        };
+        // Padded before it is made bold, the escape codes would otherwise count towards the width
+        let line = format!("{:<27}", line);
+        let line = if hero == Some(i) { line.bold().to_string() } else { line };
+        match stats.and_then(|stats| stats.player(i)).filter(|player| player.hands > 0) {
+            Some(player) => println!("{}{} {}", line, "|".blue(), player.hud().dimmed()),
+            None => println!("{}{}", line, "|".blue()),
+        }
+    }
+    println!("{}", "----------------------------".blue());
+}
+
+// What a player did, e.g. "raises to 40" or "call 20 and are all in" when you is true
+pub fn action_text(action: Action, amount: i32, bet: i32, raised_by: i32, all_in: bool, you: bool) -> String {
+    let mut text = match action {
+        Action::Fold => if you { "fold".to_string() } else { "folds".to_string() },
+        Action::Check => if you { "check".to_string() } else { "checks".to_string() },
+        Action::Call => format!("{} {}", if you { "call" } else { "calls" }, amount),
+        Action::Raise(_) if raised_by == bet => format!("{} {}", if you { "bet" } else { "bets" }, bet),
+        Action::Raise(_) => format!("{} to {}", if you { "raise" } else { "raises" }, bet),
+    };
+    if all_in {
+        text += if you { " and are all in" } else { " and is all in" };
+    }
+    text
+}
+
+// Prints the user's cards, the community cards and the user's chips
+fn print_cards(hole_cards: &[Card], board: &[Card], chips: i32) {
+    println!("{}", "\n+==================+".bold().red());
+    println!("{}{:^18}{}", "|".bold().red(), "Your Cards:", "|".bold().red());
+    println!("{}{}{}", "+".red().bold(), "------------------".white(), "+".red().bold());
+    for card in hole_cards.iter() {
+        println!("{}{:^18}{}", "|".bold().red(), card.to_string().bold(), "|".bold().red());
+    }
+    if !board.is_empty() {
+        println!("{}", "+==================+".bold().red());
+        println!("{}{:^18}{}", "|".bold().red(), "Community Cards:", "|".bold().red());
+        println!("{}{}{}", "+".red().bold(), "------------------".white(), "+".red().bold());
+        for card in board.iter() {
+            println!("{}{:^18}{}", "|".bold().red(), card.to_string().bold(), "|".bold().red());
+        }
+    }
+    println!("{}", "+==================+".bold().red());
+    println!("{}{:^18}{}", "|".bold().red(), format!("Your chips: {}", chips), "|".bold().red());
+    println!("{}", "+==================+\n".bold().red());
+}
+
+// Prints a player's hole cards, best five cards and the name of their hand, name is None for the user's
+pub fn print_hand(name: Option<&str>, cards: &[Card], best_five: &[Card], hand: &str) {
+    match name {
+        Some(name) => println!("{}'s hand:", name),
+        None => println!("Your hand:"),
+    }
+    println!("{}", "+====================================+".bold().yellow());
+    if !hand.is_empty() {
+        println!("{}{:<36}{}", "|".bold().yellow(), hand.bold(), "|".bold().yellow());
+        println!("{}{}{}", "+".bold().yellow(), "------------------------------------".white(), "+".bold().yellow());
+    }
+    println!("{}{:<36}{}", "|".bold().yellow(), "Hole cards:", "|".bold().yellow());
+    for card in cards.iter() {
+        println!("{}  {:<34}{}", "|".bold().yellow(), card, "|".bold().yellow());
+    }
+    if !best_five.is_empty() {
+        println!("{}{:<36}{}", "|".bold().yellow(), "Best five cards:", "|".bold().yellow());
+        for card in best_five.iter() {
+            println!("{}  {:<34}{}", "|".bold().yellow(), card, "|".bold().yellow());
+        }
+    }
+    println!("{}", "+====================================+\n".bold().yellow());
+}
+
+pub fn format_cards(cards: &[Card]) -> String {
+    cards.iter().map(|card| card.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+// The lines of the odds helper: the made hand, the outs, the pot odds and the equity
+pub fn odds_lines(odds: &Odds, against: OddsAgainst) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(hand) = &odds.hand {
+        lines.push(format!("Your hand: {}", hand));
+    }
+    if !odds.outs.is_empty() {
+        let groups: Vec<String> = odds.outs.iter().map(|(hand, cards)| format!("{} with {}", hand.name(), format_cards(cards))).collect();
+        lines.push(format!("{} outs to improve: {}", odds.out_count(), groups.join("; ")));
+    }
+    if let Some(pot_odds) = odds.pot_odds {
+        lines.push(format!("Pot odds: a call needs {:.1}% equity", pot_odds * 100.0));
+    }
+    let hands = match against {
+        OddsAgainst::Random => "random hands",
+        OddsAgainst::Estimated => "estimated ranges",
+    };
+    let players = if odds.opponents == 1 { "1 player".to_string() } else { format!("{} players", odds.opponents) };
+    lines.push(format!("Equity: {:.1}% (±{:.1}%) against {} with {}", odds.equity.equity * 100.0, odds.equity.margin * 100.0, players, hands));
+    lines
+}
+
+/*
+ * The user, playing from the terminal. always_show shows the user's hand at every showdown
+ * instead of asking whether to muck it. With an odds helper, the odds are printed before
+ * every decision. With stats, the players' statistics are shown next to their bets, and the
+ * user can type stats instead of a bet to see the full table.
+ */
+pub struct TerminalPlayer {
+    pub always_show: bool,
+    pub odds: Option<OddsHelper>,
+    pub stats: Option<SharedStats>,
+}
+
+impl Controller for TerminalPlayer {
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        // Print the current bets of all players, unless the user is the first to bet
+        if request.seats.iter().any(|seat| seat.acted) || request.current_bet > 0 {
+            let stats = self.stats.as_ref().map(|stats| stats.0.borrow());
+            print_bets(&request.seats, Some(request.seat), stats.as_deref());
+            println!("It's your turn to bet");
+        } else {
+            println!("\nYou are betting first");
+        }
+        print_cards(&request.hole_cards, &request.board, request.chips);
+        if let Some(helper) = &self.odds {
+            match helper.odds(request) {
+                Ok(odds) => {
+                    println!("{}", "Odds".bold().cyan());
+                    for line in odds_lines(&odds, helper.against) {
+                        println!("  {}", line);
+                    }
+                }
+                Err(error) => println!("The odds could not be worked out: {}", error),
+            }
+        }
+        println!("{}", "--------------------------------------------------".bold().white());
+
+        // With a shot clock, the user checks or folds once their time has run out
+        let countdown = Countdown::start(request);
+        if let Some(countdown) = &countdown {
+            println!("{}", countdown.describe().bold());
+        }
+        loop {
+            println!("The current bet is: {}", format!("{}", request.current_bet).bold());
+            if request.bet > 0 {
+                println!("You have bet {} so far, {} more to call", request.bet, request.to_call);
+            }
+            // Pot limit and fixed limit games don't let the user raise all in
+            if request.can_raise() && request.max_raise < request.bet + request.chips {
+                if request.min_raise == request.max_raise {
+                    println!("You can raise to {}", request.max_raise);
+                } else {
+                    println!("You can raise to between {} and {}", request.min_raise, request.max_raise);
+                }
+            }
+            if self.stats.is_some() {
+                println!("Enter your bet (-1 to fold, stats for the players' statistics): ");
+            } else {
+                println!("Enter your bet (-1 to fold): ");
+            }
+
+            // Read the bet from the user, it is the user's total bet for this round
+            let input = match &countdown {
+                Some(countdown) => match read_line_in_time(countdown) {
+                    Some(input) => input,
+                    None => {
+                        let action = request.check_or_fold();
+                        println!("{}", format!("Time is up, you {}", if action == Action::Check { "check" } else { "fold" }).red());
+                        return action;
+                    }
+                },
+                None => read_line(),
+            };
+            if let (Some(stats), "stats") = (&self.stats, input.trim()) {
+                print!("\n{}", stats.0.borrow().table());
+                println!();
+                continue;
+            }
+            let bet: i32 = match input.trim().parse() {
+                Ok(bet) => bet,
+                Err(_) => {
+                    println!("Please enter a number");
+                    continue;
+                }
+            };
+
+            // check if the bet is valid
+            if bet == -1 {
+                return Action::Fold;
+            } else if bet < -1 {
+                println!("Please enter a valid bet");
+            } else if bet > request.bet + request.chips {
+                println!("You don't have enough chips to bet that amount, please enter a valid bet");
+            } else if bet > request.max_raise && request.can_raise() {
+                println!("The largest raise is to {}, please enter a valid bet", request.max_raise);
+            } else if bet > request.current_bet && !request.can_raise() {
+                println!("The betting is capped, you can only call {} or fold", request.current_bet);
+            } else if bet == request.max_raise && bet <= request.current_bet {
+                // All in for less than the current bet
+                return Action::Call;
+            } else if bet < request.current_bet {
+                println!("You must bet at least the current bet {}, please enter a valid bet", request.current_bet);
+            } else if bet == request.current_bet {
+                return if request.can_check() { Action::Check } else { Action::Call };
+            } else if bet < request.min_raise {
+                println!("The smallest raise is to {}, please enter a valid bet", request.min_raise);
+            } else {
+                return Action::Raise(bet);
+            }
+        }
+    }
+
+    fn show_hand(&mut self, _cards: &[Card], hand: Option<&BestHand>, uncontested: bool) -> bool {
+        if self.always_show {
+            return true;
+        }
+        if uncontested {
+            println!("\nEveryone else has folded.");
+        } else {
+            let description = hand.map(|hand| hand.description()).unwrap_or_default();
+            println!("\nYour hand ({}) can't win.", description.bold());
+        }
+        println!("Do you want to {} or {} your hand? ({}/{})", "show".bold(), "muck".bold(), "s".bold().green(), "m".bold().red());
+        read_line().trim() == "s"
+    }
+}
+
+/*
+ * Prints the game to the terminal as it happens, from the user's point of view.
+ * Only the user's own hole cards are printed until hands are shown.
+ * What the user does is told to them as "You", the way the prompts speak to them, and the
+ * lists of every player, like the showdown log and the chips after each hand, use the name
+ * of the user's profile.
+ */
+pub struct TerminalDisplay {
+    // Wait for Enter before each street, so the user can follow the hand
+    pause: bool,
+    folded: Vec<bool>,
+    all_in: Vec<bool>,
+    hole_cards: Vec<Card>,
+    showdown_log: Vec<String>,
+    // The players' names, the user's is the name of their profile
+    names: Vec<String>,
+}
+
+impl TerminalDisplay {
+    pub fn new(pause: bool) -> TerminalDisplay {
+        TerminalDisplay { pause, folded: Vec::new(), all_in: Vec::new(), hole_cards: Vec::new(), showdown_log: Vec::new(), names: Vec::new() }
+    }
+
+    // The player as the user is told what they did, "You" for the user
+    fn player(&self, seat: usize) -> String {
+        if seat == 0 {
+            "You".to_string()
+        } else {
+            self.names[seat].clone()
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.folded.iter().filter(|&&folded| !folded).count()
+    }
+
+    // Players who can still bet, betting is over when fewer than two are left
+    fn can_bet(&self) -> usize {
+        (0..self.folded.len()).filter(|&i| !self.folded[i] && !self.all_in[i]).count()
+    }
+}
+
+impl EventSink for TerminalDisplay {
+    fn handle(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::HandStarted { hand_number, button, seats, .. } => {
+                self.folded = seats.iter().map(|seat| !seat.playing).collect();
+                self.all_in = vec![false; seats.len()];
+                self.hole_cards.clear();
+                self.showdown_log.clear();
+                self.names = seats.iter().map(|seat| seat.name.clone()).collect();
+                println!("{:^35}", "+---------+");
+                println!("{:^35}", format!("| Round {} |", hand_number));
+                println!("{:^37}", "+---------+\n\n");
+                if *button == 0 {
+                    println!("You are the dealer");
+                } else {
+                    println!("{} is the dealer", self.names[*button]);
+                }
+            }
+            GameEvent::BlindPosted { seat, blind, amount, all_in } => {
+                self.all_in[*seat] = *all_in;
+                if *seat == 0 {
+                    println!("You post the {}: {}", blind.name(), amount);
+                } else {
+                    println!("{} posts the {}: {}", self.names[*seat], blind.name(), amount);
+                }
+            }
+            GameEvent::HoleCards { seat, cards } => {
+                if *seat != 0 {
+                    return;
+                }
+                self.hole_cards = cards.clone();
+                println!("{}", "--------------------------------------------------\n".bold().white());
+
+                // Print the cards of the user
+                println!("{}", "=================".red());
+                println!("{}", "Your cards:".white());
+                for card in cards.iter() {
+                    println!("{}", card.to_string().white());
+                }
+                println!("{}", "=================\n".red());
+
+                // Wait for user to view cards and continue
+                if self.pause {
+                    wait_for_enter("begin betting");
+                }
+            }
+            GameEvent::StreetDealt { board, .. } => {
+                // Print the community cards
+                println!("{}", "\nCards turned:".green());
+                println!("{}", "+----------------+".green());
+                for card in board.iter() {
+                    println!("{:^18}", card.to_string());
+                }
+                println!("{}", "+----------------+".green());
+
+                // Wait for user to view cards and continue
+                if self.pause && self.can_bet() > 1 {
+                    wait_for_enter("begin betting");
+                }
+            }
+            GameEvent::PlayerActed { seat, action, amount, bet, raised_by, all_in } => {
+                self.all_in[*seat] = *all_in;
+                if *action == Action::Fold {
+                    self.folded[*seat] = true;
+                }
+                let text = action_text(*action, *amount, *bet, *raised_by, *all_in, *seat == 0);
+                println!("\n{} {}", self.player(*seat), text);
+            }
+            GameEvent::BettingRoundEnded { street, pot } => {
+                println!("\nAll bets are in, the pot is now {}", pot);
+
+                // Wait for user to view bets and continue
+                if self.pause && self.remaining() > 1 {
+                    let next = match street {
+                        Street::Preflop => "deal the flop",
+                        Street::Flop => "deal the turn",
+                        Street::Turn => "deal the river",
+                        Street::River => "reveal the winning hand",
+                    };
+                    println!();
+                    wait_for_enter(next);
+                }
+            }
+            GameEvent::UncalledBetReturned { seat, amount } => {
+                if *seat == 0 {
+                    println!("Your uncalled bet of {} is returned to you", amount);
+                } else {
+                    println!("{}'s uncalled bet of {} is returned", self.names[*seat], amount);
+                }
+            }
+            GameEvent::ShowdownStarted => println!("{}", "Showdown:".bold()),
+            GameEvent::HandShown { seat, cards, best_five, hand } => {
+                print_hand((*seat != 0).then_some(self.names[*seat].as_str()), cards, best_five, hand);
+                let mut line = format!("{} showed {}", self.names[*seat], format_cards(cards));
+                if !hand.is_empty() {
+                    line += &format!(" ({})", hand);
+                }
+                self.showdown_log.push(line);
+            }
+            GameEvent::HandMucked { seat } => {
+                self.showdown_log.push(format!("{} mucked", self.names[*seat]));
+                // The user always sees their own hand, even when it is mucked
+                if *seat == 0 {
+                    println!("You mucked {}", format_cards(&self.hole_cards));
+                }
+            }
+            GameEvent::PotAwarded { pot, seat, amount, hand, share } => {
+                if !self.showdown_log.is_empty() {
+                    println!("Showdown log:");
+                    for line in self.showdown_log.drain(..) {
+                        println!("  {}", line);
+                    }
+                    println!();
+                }
+                let pot = if *pot == 0 { "the pot".to_string() } else { format!("side pot {}", pot) };
+                let (you, they) = if *share == PotShare::Scoop { ("scoop", "scoops") } else { ("win", "wins") };
+                let winner = if *seat == 0 { format!("You {}", you) } else { format!("{} {}", self.names[*seat].bold().red().on_yellow(), they) };
+                match hand {
+                    Some(hand) => println!("{} {} from {}{} with {}", winner, amount, pot, share_half(*share), hand.bold()),
+                    None => println!("Everyone else folded. {} {} from {}", winner, amount, pot),
+                }
+            }
+            GameEvent::HandEnded { chips } => {
+                // Print the chips of each player
+                println!("\nEnd of round, each player has the following chips:");
+                println!("{}", "+====================+".bold().cyan());
+                for (i, chips) in chips.iter().enumerate() {
+                    let line = format!("{:<20}", format!("{}: {}", self.names[i], chips));
+                    if i == 0 {
+                        println!("{}{}{}", "|".bold().cyan(), line.bold().red(), "|".bold().cyan());
+                    } else {
+                        println!("{}{}{}", "|".bold().cyan(), line, "|".bold().cyan());
+                    }
+                }
+                println!("{}", "+====================+\n".bold().cyan());
+                println!("{}", "--------------------------------------------------".bold().white());
+            }
+        }
+    }
+}
+
+// A line describing the event, e.g. "Player 2 raises to 40", for the events that are worth a line.
+// table is the table after the event, which gives the players' names. The hero is the person reading
+// the line, so what they did is written as "You"; without a hero every player is named.
+pub fn event_line(table: &ReplayTable, hero: Option<usize>, event: &GameEvent) -> Option<String> {
+    let you = |seat: usize| hero == Some(seat);
+    let name = |seat: usize| if you(seat) { "You".to_string() } else { table.seats[seat].name.clone() };
+    // Picks the form of a verb that goes with the player, e.g. "posts" or "post" after "You"
+    let verb = |seat: usize, verb: &'static str, you_verb: &'static str| if you(seat) { you_verb } else { verb };
+    match event {
+        GameEvent::HandStarted { small_blind, big_blind, ante, variant, .. } => {
+            let dealer = table.button;
+            let stakes = if variant.antes() { format!("Antes {}, button blind {}", ante, big_blind) } else { format!("Blinds {}/{}", small_blind, big_blind) };
+            Some(format!("{}, {} {} the dealer", stakes, name(dealer), verb(dealer, "is", "are")))
+        }
+        GameEvent::BlindPosted { seat, blind, amount, .. } => {
+            Some(format!("{} {} the {}: {}", name(*seat), verb(*seat, "posts", "post"), blind.name(), amount))
+        }
+        GameEvent::StreetDealt { cards, .. } => Some(format!("Cards turned: {}", format_cards(cards))),
+        GameEvent::PlayerActed { seat, action, amount, bet, raised_by, all_in } => {
+            Some(format!("{} {}", name(*seat), action_text(*action, *amount, *bet, *raised_by, *all_in, you(*seat))))
+        }
+        GameEvent::UncalledBetReturned { seat, amount } if you(*seat) => Some(format!("Your uncalled bet of {} is returned to you", amount)),
+        GameEvent::UncalledBetReturned { seat, amount } => Some(format!("{}'s uncalled bet of {} is returned", name(*seat), amount)),
+        GameEvent::HandShown { seat, cards, hand, .. } => {
+            let mut line = format!("{} {} {}", name(*seat), verb(*seat, "shows", "show"), format_cards(cards));
+            if !hand.is_empty() {
+                line += &format!(" ({})", hand);
+            }
+            Some(line)
+        }
+        GameEvent::HandMucked { seat } => Some(format!("{} {}", name(*seat), verb(*seat, "mucks", "muck"))),
+        GameEvent::PotAwarded { pot, seat, amount, hand, share } => {
+            let pot = if *pot == 0 { "the pot".to_string() } else { format!("side pot {}", pot) };
+            let won = if *share == PotShare::Scoop { verb(*seat, "scoops", "scoop") } else { verb(*seat, "wins", "win") };
+            let mut line = format!("{} {} {} from {}{}", name(*seat), won, amount, pot, share_half(*share));
+            if let Some(hand) = hand {
+                line += &format!(" with {}", hand);
+            }
+            Some(line)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,507 @@
+use crate::terminal::{event_line, odds_lines};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, SetAttribute, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+use texas_holdem::card::{Card, Suit};
+use texas_holdem::clock::Countdown;
+use texas_holdem::engine::{ActionRequest, Controller};
+use texas_holdem::eval::BestHand;
+use texas_holdem::events::{Action, BettingStructure, EventSink, GameEvent, Variant};
+use texas_holdem::odds::OddsHelper;
+use texas_holdem::replay::ReplayTable;
+
+const FELT: Color = Color::DarkGreen;
+const RAIL: Color = Color::DarkYellow;
+// How often the time left to act is redrawn
+const COUNTDOWN_TICK: Duration = Duration::from_millis(250);
+
+/*
+ * The struct type in Rust is a record type (Sebesta, 6.7)
+ * One character on the screen and how it is drawn.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    ch: char,
+    fg: Color,
+    bg: Color,
+    bold: bool,
+}
+
+const BLANK: Cell = Cell { ch: ' ', fg: Color::White, bg: Color::Reset, bold: false };
+
+/*
+ * The whole screen, drawn in memory and then written out row by row. Every cell is written on
+ * every redraw, so the screen never has to be cleared and doesn't flicker.
+ */
+struct Canvas {
+    width: i32,
+    height: i32,
+    cells: Vec<Cell>,
+}
+
+impl Canvas {
+    fn new(width: u16, height: u16) -> Canvas {
+        Canvas { width: width as i32, height: height as i32, cells: vec![BLANK; width as usize * height as usize] }
+    }
+
+    fn cell(&mut self, x: i32, y: i32) -> Option<&mut Cell> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells.get_mut((y * self.width + x) as usize)
+    }
+
+    fn fill(&mut self, x: i32, y: i32, bg: Color) {
+        if let Some(cell) = self.cell(x, y) {
+            cell.bg = bg;
+        }
+    }
+
+    // Writes text from (x, y), keeping the background unless bg is given
+    fn put(&mut self, x: i32, y: i32, text: &str, fg: Color, bg: Option<Color>, bold: bool) {
+        for (i, ch) in text.chars().enumerate() {
+            if let Some(cell) = self.cell(x + i as i32, y) {
+                *cell = Cell { ch, fg, bg: bg.unwrap_or(cell.bg), bold };
+            }
+        }
+    }
+
+    fn put_centered(&mut self, x: i32, y: i32, text: &str, fg: Color, bg: Option<Color>, bold: bool) {
+        self.put(x - text.chars().count() as i32 / 2, y, text, fg, bg, bold);
+    }
+
+    // Writes text with the keys in brackets highlighted, e.g. "[F] Fold"
+    fn put_keys(&mut self, x: i32, y: i32, text: &str) {
+        let mut in_key = false;
+        for (x, ch) in (x..).zip(text.chars()) {
+            if ch == '[' {
+                in_key = true;
+            }
+            let (fg, bold) = if in_key { (Color::Yellow, true) } else { (Color::White, false) };
+            self.put(x, y, &ch.to_string(), fg, None, bold);
+            if ch == ']' {
+                in_key = false;
+            }
+        }
+    }
+
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut last: Option<Cell> = None;
+        for y in 0..self.height {
+            queue!(out, MoveTo(0, y as u16))?;
+            for x in 0..self.width {
+                let cell = self.cells[(y * self.width + x) as usize];
+                if last.is_none_or(|last| last.fg != cell.fg || last.bg != cell.bg || last.bold != cell.bold) {
+                    let weight = if cell.bold { Attribute::Bold } else { Attribute::NormalIntensity };
+                    queue!(out, SetAttribute(weight), SetForegroundColor(cell.fg), SetBackgroundColor(cell.bg))?;
+                }
+                queue!(out, Print(cell.ch))?;
+                last = Some(cell);
+            }
+        }
+        queue!(out, SetAttribute(Attribute::Reset))?;
+        out.flush()
+    }
+}
+
+// A card as it is drawn face up, e.g. " A♥ " in red on white
+fn card_face(card: Card) -> (String, Color) {
+    let (suit, color) = match card.suit {
+        Suit::Clubs => ('♣', Color::Black),
+        Suit::Diamonds => ('♦', Color::Red),
+        Suit::Hearts => ('♥', Color::Red),
+        Suit::Spades => ('♠', Color::Black),
+    };
+    (format!("{}{}", card.rank.symbol(), suit), color)
+}
+
+fn put_cards(canvas: &mut Canvas, x: i32, y: i32, cards: &[Card]) {
+    for (i, card) in cards.iter().enumerate() {
+        let (face, color) = card_face(*card);
+        canvas.put(x + 4 * i as i32, y, &format!(" {} ", face), color, Some(Color::White), true);
+    }
+}
+
+/*
+ * What the bottom bar shows: the hotkeys that can be pressed now.
+ */
+enum Bar {
+    Waiting,
+    Act { request: ActionRequest, raise_to: i32, countdown: Option<Countdown> },
+    ShowOrMuck(String),
+    HandOver,
+}
+
+/*
+ * The full-screen game: an oval table with the seats around it, the pot and board in the
+ * middle, an action log on the right and an action bar at the bottom. The table is redrawn
+ * in place after every event.
+ */
+pub struct Tui {
+    table: ReplayTable,
+    // The user's seat, drawn at the bottom of the table
+    hero: usize,
+    blinds: (i32, i32),
+    ante: i32,
+    structure: BettingStructure,
+    variant: Variant,
+    log: Vec<String>,
+    always_show: bool,
+    // How long to wait after each of the other players' actions, so the user can follow them
+    delay: Duration,
+    quit: bool,
+    open: bool,
+    // Printed when the user leaves in the middle of a hand
+    leave_message: &'static str,
+    // Logs the odds when it is the user's turn
+    pub odds: Option<OddsHelper>,
+}
+
+impl Tui {
+    pub fn open(hero: usize, always_show: bool, pause: bool, leave_message: &'static str) -> io::Result<Rc<RefCell<Tui>>> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        let delay = Duration::from_millis(if pause { 600 } else { 0 });
+        Ok(Rc::new(RefCell::new(Tui {
+            table: ReplayTable::new(),
+            hero,
+            blinds: (0, 0),
+            ante: 0,
+            structure: BettingStructure::NoLimit,
+            variant: Variant::Holdem,
+            log: Vec::new(),
+            always_show,
+            delay,
+            quit: false,
+            open: true,
+            leave_message,
+            odds: None,
+        })))
+    }
+
+    // Gives the terminal back as it was, e.g. before printing the end of the game
+    pub fn close(&mut self) {
+        if self.open {
+            self.open = false;
+            let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+
+    // True once the user has chosen to stop playing
+    pub fn quit(&self) -> bool {
+        self.quit
+    }
+
+    fn redraw(&self, bar: &Bar) {
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        let mut canvas = Canvas::new(width, height);
+        let log_width = if width >= 100 { 34 } else if width >= 80 { 26 } else { 0 };
+        let table_width = width as i32 - log_width;
+        self.draw_title(&mut canvas);
+        self.draw_table(&mut canvas, table_width, height as i32 - 4);
+        if log_width > 0 {
+            self.draw_log(&mut canvas, table_width, log_width, height as i32 - 4);
+        }
+        self.draw_bar(&mut canvas, bar);
+        let _ = canvas.write(&mut io::stdout());
+    }
+
+    fn draw_title(&self, canvas: &mut Canvas) {
+        let stakes = match self.structure {
+            BettingStructure::FixedLimit => format!("{}/{}", self.blinds.1, 2 * self.blinds.1),
+            _ if self.variant.antes() => format!("ante {}, button blind {}", self.ante, self.blinds.1),
+            _ => format!("{}/{}", self.blinds.0, self.blinds.1),
+        };
+        let game = if self.variant == Variant::Holdem { "Texas Hold'em" } else { self.variant.name() };
+        let title = format!(" {} · Hand {} · {} {} ", game, self.table.hand_number, self.structure.name(), stakes);
+        for x in 0..canvas.width {
+            canvas.fill(x, 0, Color::DarkBlue);
+        }
+        canvas.put(0, 0, &title, Color::White, None, true);
+        canvas.put_keys(canvas.width - 10, 0, "[Q] quit");
+    }
+
+    // The oval table with the seats around it, in the area from row 1 to bottom and column 0 to width
+    fn draw_table(&self, canvas: &mut Canvas, width: i32, bottom: i32) {
+        let (cx, cy) = (width / 2, 1 + bottom / 2);
+        let rx = (width / 2 - 12).max(10) as f64;
+        let ry = (bottom / 2 - 4).max(3) as f64;
+        for y in cy - ry as i32 - 1..=cy + ry as i32 + 1 {
+            for x in cx - rx as i32 - 1..=cx + rx as i32 + 1 {
+                let (dx, dy) = ((x - cx) as f64, (y - cy) as f64);
+                let outer = (dx / (rx + 0.5)).powi(2) + (dy / (ry + 0.5)).powi(2) <= 1.0;
+                let inner = (dx / (rx - 1.5)).powi(2) + (dy / (ry - 0.5)).powi(2) <= 1.0;
+                if inner {
+                    canvas.fill(x, y, FELT);
+                } else if outer {
+                    canvas.fill(x, y, RAIL);
+                }
+            }
+        }
+
+        // The pot and the board in the middle
+        let pot = self.table.pot - self.table.seats.iter().map(|seat| seat.bet).sum::<i32>();
+        if self.table.pot > 0 {
+            canvas.put_centered(cx, cy - 2, &format!("Pot {}", pot.max(0)), Color::White, None, true);
+        }
+        let board = &self.table.board;
+        put_cards(canvas, cx - 2 * board.len() as i32, cy, board);
+
+        let n = self.table.seats.len();
+        for (i, seat) in self.table.seats.iter().enumerate() {
+            // The user sits at the bottom, the others follow clockwise
+            let angle = PI / 2.0 + 2.0 * PI * ((i + n - self.hero) % n) as f64 / n as f64;
+            let (cos, sin) = (angle.cos(), angle.sin());
+
+            if seat.bet > 0 {
+                let (bx, by) = (cx + (0.62 * rx * cos) as i32, cy + (0.58 * ry * sin) as i32);
+                canvas.put_centered(bx, by, &format!("● {}", seat.bet), Color::Yellow, None, true);
+            }
+
+            // The seat's box sits just outside the rail, on the side facing away from the table
+            let mut lines: Vec<(String, Color, Option<Color>)> = Vec::new();
+            let name_color = if seat.folded || !seat.playing { Color::DarkGrey } else { Color::White };
+            lines.push((seat.name.clone(), name_color, None));
+            let chips = if seat.all_in { "ALL IN".to_string() } else { format!("{} chips", seat.chips) };
+            lines.push((chips, if seat.all_in { Color::Red } else { Color::Grey }, None));
+            let box_width = lines.iter().map(|line| line.0.chars().count()).max().unwrap_or(0).max(9) as i32;
+            let (px, py) = (cx + ((rx + 2.0) * cos) as i32, cy + ((ry + 1.5) * sin) as i32);
+            let left = if cos > 0.3 { px } else if cos < -0.3 { px - box_width } else { px - box_width / 2 };
+            let top = if sin > 0.3 { py } else if sin < -0.3 { py - 2 } else { py - 1 };
+            for (row, (text, color, bg)) in lines.iter().enumerate() {
+                canvas.put(left, top + row as i32, text, *color, *bg, row == 0);
+            }
+            if i == self.table.button {
+                canvas.put(left + seat.name.chars().count() as i32 + 1, top, " D ", Color::Black, Some(Color::White), true);
+            }
+
+            // Cards are face up for the user and for hands shown at showdown
+            let cards = &self.table.hole_cards[i];
+            if !seat.playing {
+                canvas.put(left, top + 2, "out", Color::DarkGrey, None, false);
+            } else if seat.folded {
+                canvas.put(left, top + 2, "folded", Color::DarkGrey, None, false);
+            } else if (i == self.hero || self.table.shown[i]) && !cards.is_empty() {
+                put_cards(canvas, left, top + 2, cards);
+            } else {
+                for back in 0..cards.len() as i32 {
+                    canvas.put(left + 4 * back, top + 2, "░░░", Color::Blue, Some(Color::DarkBlue), false);
+                }
+            }
+        }
+    }
+
+    fn draw_log(&self, canvas: &mut Canvas, left: i32, width: i32, bottom: i32) {
+        for y in 1..=bottom {
+            canvas.put(left, y, "│", Color::DarkGrey, None, false);
+        }
+        canvas.put(left + 2, 1, "Action log", Color::White, None, true);
+        let rows = (bottom - 2).max(0) as usize;
+        let start = self.log.len().saturating_sub(rows);
+        for (row, line) in self.log[start..].iter().enumerate() {
+            let text: String = line.chars().take(width as usize - 3).collect();
+            canvas.put(left + 2, 3 + row as i32, &text, Color::Grey, None, false);
+        }
+    }
+
+    fn draw_bar(&self, canvas: &mut Canvas, bar: &Bar) {
+        let y = canvas.height - 3;
+        canvas.put(0, y, &"─".repeat(canvas.width as usize), Color::DarkGrey, None, false);
+        match bar {
+            Bar::Waiting => {
+                let last = self.log.last().cloned().unwrap_or_default();
+                canvas.put(1, y + 1, &last, Color::Grey, None, false);
+            }
+            Bar::Act { request, raise_to, countdown } => {
+                let call = if request.can_check() { "[C] Check".to_string() } else { format!("[C] Call {}", request.to_call) };
+                let mut keys = format!("[F] Fold   {}", call);
+                if request.can_raise() {
+                    let verb = if request.current_bet == 0 { "Bet" } else { "Raise to" };
+                    keys += &format!("   [R] {} {}   [A] Max {}", verb, raise_to, request.max_raise);
+                }
+                canvas.put_keys(1, y + 1, &keys);
+                if let Some(countdown) = countdown {
+                    // The time left, in red once the time bank is being used or it is nearly up
+                    let text = countdown.describe();
+                    let hurry = countdown.in_bank() || countdown.seconds_left() <= 10;
+                    let x = canvas.width - text.chars().count() as i32 - 2;
+                    canvas.put(x, y + 1, &text, if hurry { Color::Red } else { Color::White }, None, hurry);
+                }
+                if request.can_raise() && request.min_raise < request.max_raise {
+                    // The bet size slider, from the smallest to the largest raise
+                    let slider_width = 30;
+                    let position = ((raise_to - request.min_raise) as f64 / (request.max_raise - request.min_raise) as f64 * slider_width as f64) as usize;
+                    let slider: String = (0..=slider_width).map(|i| if i == position { '●' } else { '─' }).collect();
+                    canvas.put_keys(1, y + 2, &format!("[←] {} [→]  {} to {}   [↓][↑] by a quarter pot", slider, request.min_raise, request.max_raise));
+                } else {
+                    canvas.put(1, y + 2, "Your turn", Color::Grey, None, false);
+                }
+            }
+            Bar::ShowOrMuck(text) => {
+                canvas.put(1, y + 1, text, Color::White, None, false);
+                canvas.put_keys(1, y + 2, "[S] Show   [M] Muck");
+            }
+            Bar::HandOver => canvas.put_keys(1, y + 1, "Hand over. Press any key for the next hand, [Q] to quit"),
+        }
+    }
+
+    // Waits for a key, redrawing the screen if the terminal is resized. Ctrl-C is read as q.
+    fn read_key(&mut self, bar: &Bar) -> KeyCode {
+        self.read_key_until(bar, None).expect("there is no deadline")
+    }
+
+    // Waits for a key until the deadline, None once it has passed. The bar is redrawn as time goes by.
+    fn read_key_until(&mut self, bar: &Bar, deadline: Option<Instant>) -> Option<KeyCode> {
+        self.redraw(bar);
+        loop {
+            if let Some(deadline) = deadline {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    return None;
+                }
+                match event::poll(left.min(COUNTDOWN_TICK)) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        self.redraw(bar);
+                        continue;
+                    }
+                    Err(_) => self.leave(),
+                }
+            }
+            match event::read() {
+                Ok(Event::Key(KeyEvent { code: KeyCode::Char('c'), modifiers, kind: KeyEventKind::Press, .. }))
+                    if modifiers.contains(KeyModifiers::CONTROL) => return Some(KeyCode::Char('q')),
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => return Some(key.code),
+                Ok(Event::Resize(_, _)) => self.redraw(bar),
+                Ok(_) => {}
+                Err(_) => self.leave(),
+            }
+        }
+    }
+
+    // Quits the game in the middle of a hand
+    fn leave(&mut self) -> ! {
+        self.close();
+        println!("{}", self.leave_message);
+        std::process::exit(0);
+    }
+
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        let step = self.blinds.1.max(1);
+        let quarter_pot = (request.pot / 4).max(step);
+        let mut raise_to = request.min_raise;
+        if let Some(helper) = &self.odds {
+            match helper.odds(request) {
+                Ok(odds) => self.log.extend(odds_lines(&odds, helper.against)),
+                Err(error) => self.log.push(format!("The odds could not be worked out: {}", error)),
+            }
+        }
+        // With a shot clock, the user checks or folds once their time has run out
+        let countdown = Countdown::start(request);
+        loop {
+            let bar = Bar::Act { request: request.clone(), raise_to, countdown };
+            let Some(key) = self.read_key_until(&bar, countdown.map(|countdown| countdown.deadline())) else {
+                let action = request.check_or_fold();
+                self.log.push(format!("Time is up, you {}", if action == Action::Check { "check" } else { "fold" }));
+                return action;
+            };
+            let clamp = |to: i32| to.clamp(request.min_raise, request.max_raise);
+            match key {
+                KeyCode::Char('q') => self.leave(),
+                KeyCode::Char('f') => return Action::Fold,
+                KeyCode::Char('c') | KeyCode::Char('k') => return if request.can_check() { Action::Check } else { Action::Call },
+                KeyCode::Char('r') | KeyCode::Enter if request.can_raise() => return Action::Raise(raise_to),
+                KeyCode::Char('a') | KeyCode::End => raise_to = request.max_raise,
+                KeyCode::Home => raise_to = request.min_raise,
+                KeyCode::Left => raise_to = clamp(raise_to - step),
+                KeyCode::Right => raise_to = clamp(raise_to + step),
+                KeyCode::Down => raise_to = clamp(raise_to - quarter_pot),
+                KeyCode::Up => raise_to = clamp(raise_to + quarter_pot),
+                _ => {}
+            }
+        }
+    }
+
+    fn show_hand(&mut self, hand: Option<&BestHand>, uncontested: bool) -> bool {
+        if self.always_show {
+            return true;
+        }
+        let text = if uncontested {
+            "Everyone else has folded.".to_string()
+        } else {
+            format!("Your hand ({}) can't win.", hand.map(|hand| hand.description()).unwrap_or_default())
+        };
+        let bar = Bar::ShowOrMuck(text);
+        loop {
+            match self.read_key(&bar) {
+                KeyCode::Char('q') => self.leave(),
+                KeyCode::Char('s') => return true,
+                KeyCode::Char('m') => return false,
+                _ => {}
+            }
+        }
+    }
+
+    pub fn handle(&mut self, event: &GameEvent) {
+        if let GameEvent::HandStarted { small_blind, big_blind, ante, structure, variant, .. } = event {
+            self.blinds = (*small_blind, *big_blind);
+            self.ante = *ante;
+            self.structure = *structure;
+            self.variant = *variant;
+            self.log.push(String::new());
+        }
+        self.table.apply(event);
+        if let Some(line) = event_line(&self.table, Some(self.hero), event) {
+            self.log.push(line);
+        }
+        // Only the last few hundred lines can ever be seen
+        if self.log.len() > 500 {
+            self.log.drain(..100);
+        }
+        self.redraw(&Bar::Waiting);
+
+        match event {
+            GameEvent::PlayerActed { seat, .. } if *seat != self.hero => thread::sleep(self.delay),
+            GameEvent::StreetDealt { .. } => thread::sleep(self.delay),
+            GameEvent::HandEnded { .. } => self.quit = self.read_key(&Bar::HandOver) == KeyCode::Char('q'),
+            _ => {}
+        }
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// The user's seat, played with the keyboard
+pub struct TuiPlayer(pub Rc<RefCell<Tui>>);
+
+impl Controller for TuiPlayer {
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        self.0.borrow_mut().act(request)
+    }
+
+    fn show_hand(&mut self, _cards: &[Card], hand: Option<&BestHand>, uncontested: bool) -> bool {
+        self.0.borrow_mut().show_hand(hand, uncontested)
+    }
+}
+
+// Draws every event of the game on the table
+pub struct TuiDisplay(pub Rc<RefCell<Tui>>);
+
+impl EventSink for TuiDisplay {
+    fn handle(&mut self, event: &GameEvent) {
+        self.0.borrow_mut().handle(event);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::protocol::{ClientMessage, ServerMessage};
+use crate::server::{Transport, WRITE_TIMEOUT};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+// The browser client, a single page that talks to the server over a WebSocket
+pub const PAGE: &str = include_str!("../web/index.html");
+// The path the page opens its WebSocket on
+pub const SOCKET_PATH: &str = "/ws";
+// How long a browser has to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/*
+ * A player in a browser. Every protocol message is one WebSocket text message holding the
+ * JSON, the WebSocket takes the place of the length before each frame.
+ */
+pub struct WebSocketTransport(WebSocket<TcpStream>);
+
+impl Transport for WebSocketTransport {
+    fn send(&mut self, message: &ServerMessage) -> io::Result<()> {
+        let json = serde_json::to_string(message).expect("messages always serialize");
+        self.0.send(Message::text(json)).map_err(into_io_error)
+    }
+
+    fn receive(&mut self) -> io::Result<ClientMessage> {
+        loop {
+            match self.0.read().map_err(into_io_error)? {
+                Message::Text(text) => {
+                    return serde_json::from_str(text.as_str()).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error));
+                }
+                Message::Binary(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, "messages are sent as text")),
+                Message::Close(_) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the connection was closed")),
+                // Pings are answered by the WebSocket itself
+                _ => {}
+            }
+        }
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.0.get_ref().set_read_timeout(timeout)
+    }
+}
+
+fn into_io_error(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(error) => error,
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            io::Error::new(io::ErrorKind::UnexpectedEof, "the connection was closed")
+        }
+        error => io::Error::new(io::ErrorKind::ConnectionAborted, error.to_string()),
+    }
+}
+
+/*
+ * Serves the browser client over HTTP at the address, and hands every browser that opens the
+ * WebSocket to the server through players. Each request is answered on a thread of its own,
+ * so a slow browser doesn't hold up the others.
+ */
+pub fn listen(address: &str, players: Sender<Box<dyn Transport>>) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(address)?;
+    let address = listener.local_addr()?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let players = players.clone();
+            thread::spawn(move || {
+                if let Ok(Some(socket)) = answer(stream) {
+                    let _ = players.send(Box::new(socket));
+                }
+            });
+        }
+    });
+    Ok(address)
+}
+
+// Answers one HTTP request with the page, or by opening a WebSocket, which is returned
+fn answer(mut stream: TcpStream) -> io::Result<Option<WebSocketTransport>> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // The browser waits for the answer, so nothing after the headers has been read
+    let mut key = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    match (method, path, key) {
+        ("GET", SOCKET_PATH, Some(key)) => {
+            let accept = derive_accept_key(key.as_bytes());
+            write!(stream, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n", accept)?;
+            stream.set_read_timeout(None)?;
+            stream.set_nodelay(true)?;
+            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            Ok(Some(WebSocketTransport(WebSocket::from_raw_socket(stream, Role::Server, None))))
+        }
+        ("GET", "/" | "/index.html", _) => {
+            respond(&mut stream, "200 OK", "text/html; charset=utf-8", PAGE)?;
+            Ok(None)
+        }
+        ("GET", _, _) => {
+            respond(&mut stream, "404 Not Found", "text/plain", "Not found")?;
+            Ok(None)
+        }
+        _ => {
+            respond(&mut stream, "405 Method Not Allowed", "text/plain", "Only GET is supported")?;
+            Ok(None)
+        }
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,162 @@
+use texas_holdem::bot::BotKind;
+use texas_holdem::clock::ShotClock;
+use texas_holdem::config::{Config, DisplayConfig, Options, DEFAULT_TIME_BANK_TOP_UP};
+use texas_holdem::events::{BettingStructure, Variant};
+use texas_holdem::odds::OddsAgainst;
+
+fn args(text: &str) -> Vec<String> {
+    text.split_whitespace().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn reads_a_config_file() {
+    let config = Config::from_toml(
+        r#"
+        players = 6
+        chips = 2000
+        small_blind = 10
+        big_blind = 20
+        structure = "pot-limit"
+        bots = ["calling", "random"]
+        seed = 42
+        shot_clock = 20
+        time_bank = 60
+        profile = "Ann"
+
+        [display]
+        always_show = true
+        pause = false
+        odds = "estimated"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config,
+        Config {
+            players: Some(6),
+            chips: Some(2000),
+            small_blind: Some(10),
+            big_blind: Some(20),
+            ante: None,
+            structure: Some(BettingStructure::PotLimit),
+            variant: None,
+            bots: Some(vec![BotKind::Calling, BotKind::Random]),
+            seed: Some(42),
+            shot_clock: Some(20),
+            time_bank: Some(60),
+            time_bank_top_up: None,
+            profile: Some("Ann".to_string()),
+            display: DisplayConfig { always_show: Some(true), pause: Some(false), color: None, tui: None, odds: Some(OddsAgainst::Estimated) },
+        }
+    );
+    assert_eq!(config.bot(1), BotKind::Calling);
+    assert_eq!(config.bot(5), BotKind::Random);
+    assert_eq!(config.shot_clock(), Some(ShotClock { action: 20, bank: 60, top_up: DEFAULT_TIME_BANK_TOP_UP }));
+    assert_eq!(Config::from_toml("").unwrap(), Config::default());
+}
+
+#[test]
+fn config_file_errors_name_the_problem() {
+    let error = Config::from_toml("player = 6").unwrap_err();
+    assert!(error.contains("player"), "{}", error);
+    let error = Config::from_toml("structure = \"spread-limit\"").unwrap_err();
+    assert!(error.contains("spread-limit"), "{}", error);
+    let error = Config::from_toml("chips = \"lots\"").unwrap_err();
+    assert!(error.contains("chips"), "{}", error);
+}
+
+#[test]
+fn reads_command_line_flags() {
+    let options = Options::parse(&args(
+        "--players 4 --chips 500 --blinds 5/10 --structure fl --bots calling,calling --seed 7 --always-show --no-pause --no-color --tui --odds random --events - --csv hands.csv --profile Bob --resume",
+    ))
+    .unwrap();
+    let config = options.config;
+    assert_eq!(config.players, Some(4));
+    assert_eq!(config.chips, Some(500));
+    assert_eq!(config.blinds(500), (5, 10));
+    assert_eq!(config.structure, Some(BettingStructure::FixedLimit));
+    assert_eq!(config.bots, Some(vec![BotKind::Calling, BotKind::Calling]));
+    assert_eq!(config.seed, Some(7));
+    assert_eq!(config.profile.as_deref(), Some("Bob"));
+    assert_eq!(
+        config.display,
+        DisplayConfig { always_show: Some(true), pause: Some(false), color: Some(false), tui: Some(true), odds: Some(OddsAgainst::Random) }
+    );
+    assert_eq!(options.events.as_deref(), Some("-"));
+    assert_eq!(options.csv.as_deref(), Some("hands.csv"));
+    assert!(options.resume);
+    assert_eq!(config.shot_clock(), None);
+
+    let config = Options::parse(&args("--shot-clock 15 --time-bank 45 --time-bank-top-up 10")).unwrap().config;
+    assert_eq!(config.shot_clock(), Some(ShotClock { action: 15, bank: 45, top_up: 10 }));
+}
+
+#[test]
+fn flag_errors_name_the_problem() {
+    assert_eq!(Options::parse(&args("--players")).unwrap_err(), "--players needs a value");
+    assert_eq!(Options::parse(&args("--chips many")).unwrap_err(), "--chips needs a number, got many");
+    assert!(Options::parse(&args("--blinds 10")).unwrap_err().contains("small/big"));
+    assert!(Options::parse(&args("--bots random,shark")).unwrap_err().contains("shark"));
+    assert_eq!(Options::parse(&args("--colour")).unwrap_err(), "unknown option --colour");
+    assert_eq!(Options::parse(&args("--odds always")).unwrap_err(), "--odds must be random or estimated, got always");
+    assert_eq!(Options::parse(&args("--variant stud")).unwrap_err(), "unknown variant stud, use holdem, omaha, omaha-hi-lo or short-deck");
+}
+
+#[test]
+fn omaha_is_pot_limit_unless_told_otherwise() {
+    let config = Options::parse(&args("--variant plo")).unwrap().config;
+    assert_eq!((config.variant(), config.structure()), (Variant::Omaha, BettingStructure::PotLimit));
+    let config = Options::parse(&args("--variant omaha --structure nl")).unwrap().config;
+    assert_eq!(config.structure(), BettingStructure::NoLimit);
+    assert_eq!(Config::default().structure(), BettingStructure::NoLimit);
+    let config = Options::parse(&args("--variant 6+")).unwrap().config;
+    assert_eq!((config.variant(), config.structure()), (Variant::ShortDeck, BettingStructure::NoLimit));
+}
+
+#[test]
+fn short_deck_antes_the_small_blind_unless_told_otherwise() {
+    let config = Options::parse(&args("--variant short-deck --blinds 5/10")).unwrap().config;
+    assert_eq!(config.ante(1000), 5);
+    let config = Options::parse(&args("--variant short-deck --blinds 5/10 --ante 2")).unwrap().config;
+    assert_eq!((config.ante(1000), config.blinds(1000).1), (2, 10));
+    assert_eq!(Options::parse(&args("--blinds 5/10")).unwrap().config.ante(1000), 0);
+}
+
+#[test]
+fn flags_override_the_config_file() {
+    let file = Config::from_toml("players = 6\nchips = 2000\nstructure = \"nl\"").unwrap();
+    let flags = Options::parse(&args("--players 3")).unwrap().config;
+    let config = file.merge(flags);
+    assert_eq!(config.players, Some(3));
+    assert_eq!(config.chips, Some(2000));
+    assert_eq!(config.structure, Some(BettingStructure::NoLimit));
+}
+
+#[test]
+fn rejects_invalid_settings() {
+    let invalid = [
+        ("players = 1", "players must be between 2 and 22, got 1"),
+        ("players = 3\nbots = [\"random\", \"random\", \"random\"]", "3 bots are given but there are only 2 computer players"),
+        ("chips = 5", "chips must be at least 10, got 5"),
+        ("small_blind = 20\nbig_blind = 10", "small_blind (20) must not be larger than big_blind (10)"),
+        ("shot_clock = 0", "shot_clock must be at least 1 second"),
+        ("time_bank = 30", "a time bank needs a shot_clock"),
+        ("players = 12\nvariant = \"omaha\"", "players must be between 2 and 11, got 12"),
+        ("players = 15\nvariant = \"short-deck\"", "players must be between 2 and 14, got 15"),
+        ("variant = \"plo\"\n[display]\nodds = \"random\"", "the odds helper only works in Hold'em, not Omaha"),
+        ("ante = 5", "Hold'em has no ante"),
+        ("ante = 0\nvariant = \"short-deck\"", "ante must be at least 1, got 0"),
+    ];
+    for (text, error) in invalid {
+        assert_eq!(Config::from_toml(text).unwrap().validate(), Err(error.to_string()));
+    }
+}
+
+#[test]
+fn works_out_missing_blinds() {
+    assert_eq!(Config::default().blinds(1000), (10, 20));
+    assert_eq!(Config::default().blinds(10), (1, 2));
+    assert_eq!(Config { small_blind: Some(25), ..Config::default() }.blinds(1000), (25, 50));
+    assert_eq!(Config { big_blind: Some(30), ..Config::default() }.blinds(1000), (15, 30));
+}
@@ -0,0 +1,568 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+use texas_holdem::bot::{BotKind, CallingBot, RandomBot};
+use texas_holdem::clock::{Countdown, ShotClock, Timed};
+use texas_holdem::engine::{side_pots, ActionRequest, Controller, Pot, Seat, Table};
+use texas_holdem::card::{Card, Rank};
+use texas_holdem::events::{Action, BettingStructure, Blind, PotShare, Street, Variant};
+use texas_holdem::event_log::{self, EventLog, SCHEMA_VERSION};
+use texas_holdem::events::{EventSink, GameEvent, SeatInfo};
+use texas_holdem::history::HandHistory;
+use texas_holdem::replay::{read_hands, Replay};
+use texas_holdem::profiles::{Finish, Profiles};
+use texas_holdem::session::Session;
+use texas_holdem::summary::SharedSummary;
+
+// Writes into a buffer the test can read afterwards
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Rc<RefCell<Vec<GameEvent>>>);
+
+impl EventSink for Recorder {
+    fn handle(&mut self, event: &GameEvent) {
+        self.0.borrow_mut().push(event.clone());
+    }
+}
+
+// Raises as much as it may every time, and keeps the requests it was sent
+#[derive(Clone, Default)]
+struct Maniac(Rc<RefCell<Vec<ActionRequest>>>);
+
+impl Controller for Maniac {
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        self.0.borrow_mut().push(request.clone());
+        if request.can_raise() {
+            Action::Raise(request.max_raise)
+        } else {
+            Action::Call
+        }
+    }
+}
+
+fn maniac_table(structure: BettingStructure) -> (Table, Vec<Box<dyn Controller>>, Maniac, Recorder) {
+    let seats = (0..3).map(|i| Seat::new(&format!("Player {}", i + 1), 10_000)).collect();
+    let mut table = Table::new(seats, 5, 10);
+    table.structure = structure;
+    let maniac = Maniac::default();
+    let controllers = (0..3).map(|_| Box::new(maniac.clone()) as Box<dyn Controller>).collect();
+    (table, controllers, maniac, Recorder::default())
+}
+
+fn bot_table(players: usize, chips: i32) -> (Table, Vec<Box<dyn Controller>>) {
+    let seats = (0..players).map(|i| Seat::new(&format!("Player {}", i + 1), chips)).collect();
+    let controllers = (0..players).map(|_| Box::new(RandomBot) as Box<dyn Controller>).collect();
+    (Table::new(seats, 5, 10), controllers)
+}
+
+// A table where nobody folds or runs out of chips in fewer hands than chips / 10
+fn calling_table(players: usize, chips: i32) -> (Table, Vec<Box<dyn Controller>>) {
+    let seats = (0..players).map(|i| Seat::new(&format!("Player {}", i + 1), chips)).collect();
+    let controllers = (0..players).map(|_| Box::new(CallingBot) as Box<dyn Controller>).collect();
+    (Table::new(seats, 5, 10), controllers)
+}
+
+#[test]
+fn chips_are_conserved() {
+    let (mut table, mut controllers) = bot_table(6, 500);
+    let recorder = Recorder::default();
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(recorder.clone())];
+    for _ in 0..200 {
+        table.play_hand(&mut controllers, &mut sinks);
+        assert_eq!(table.seats.iter().map(|seat| seat.chips).sum::<i32>(), 3000);
+        assert!(table.seats.iter().all(|seat| seat.chips >= 0));
+    }
+
+    // Every hand ends with the pot paid out
+    let events = recorder.0.borrow();
+    let started = events.iter().filter(|event| matches!(event, GameEvent::HandStarted { .. })).count();
+    let ended = events.iter().filter(|event| matches!(event, GameEvent::HandEnded { .. })).count();
+    assert_eq!(started, ended);
+    assert_eq!(started as u32, table.hand_number);
+}
+
+#[test]
+fn sums_up_the_session() {
+    let (mut table, mut controllers) = calling_table(4, 500);
+    let summary = SharedSummary::default();
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(summary.clone())];
+    for _ in 0..50 {
+        table.play_hand(&mut controllers, &mut sinks);
+    }
+    let summary = summary.0.borrow();
+    assert_eq!(summary.hands.len(), 50);
+    assert_eq!(summary.final_chips(), table.seats.iter().map(|seat| seat.chips).collect::<Vec<_>>());
+    assert_eq!((0..4).map(|seat| summary.net(seat)).sum::<i32>(), 0);
+    for seat in 0..4 {
+        assert_eq!(summary.net(seat), table.seats[seat].chips - 500);
+        // The blinds never change, so the win rate is the net in big blinds over the hands played
+        let played = summary.hands_played(seat);
+        if played > 0 {
+            let rate = summary.big_blinds_per_100(seat).unwrap();
+            assert!((rate - summary.net(seat) as f64 / 10.0 * 100.0 / played as f64).abs() < 1e-9);
+        }
+    }
+    let biggest = summary.biggest_pot().unwrap();
+    assert!(summary.hands.iter().all(|hand| hand.pot <= biggest.pot));
+    assert!(summary.report().contains(&format!("Biggest pot: {} in hand {}", biggest.pot, biggest.hand_number)));
+
+    let chart = summary.chart(20, 8);
+    assert_eq!(chart.lines().count(), 11);
+    assert_eq!(chart.lines().last(), Some("1 Player 1, 2 Player 2, 3 Player 3, 4 Player 4"));
+
+    let mut csv = Vec::new();
+    summary.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let dealt: usize = (0..4).map(|seat| summary.hands_played(seat)).sum();
+    assert_eq!(csv.lines().count(), dealt + 1);
+    assert_eq!(csv.lines().nth(1), Some(format!("1,1,Player 1,10,500,{},{},{}", summary.hands[0].chips[0], summary.hands[0].net(0), summary.hands[0].pot).as_str()));
+}
+
+#[test]
+fn writes_a_hand_history_for_every_hand() {
+    let (mut table, mut controllers) = calling_table(4, 1000);
+    let buffer = SharedBuffer::default();
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(HandHistory::new(buffer.clone(), 0, "Test"))];
+    for _ in 0..20 {
+        table.play_hand(&mut controllers, &mut sinks);
+    }
+
+    let text = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    let hands: Vec<&str> = text.split("\n\n\n").filter(|hand| !hand.trim().is_empty()).collect();
+    assert_eq!(hands.len(), 20);
+    for hand in hands {
+        let lines: Vec<&str> = hand.lines().collect();
+        assert!(lines[0].starts_with("PokerStars Hand #"), "{}", hand);
+        assert!(lines[0].contains("Hold'em No Limit (5/10)"), "{}", hand);
+        assert!(lines[1].starts_with("Table 'Test' 4-max Seat #"), "{}", hand);
+        assert!(lines.contains(&"*** HOLE CARDS ***"), "{}", hand);
+        assert!(lines.iter().any(|line| line.starts_with("Dealt to Player 1 [")), "{}", hand);
+        assert!(lines.iter().any(|line| line.contains(" collected ")), "{}", hand);
+        assert!(lines.contains(&"*** SUMMARY ***"), "{}", hand);
+        assert!(!hand.contains(")folded"), "{}", hand);
+    }
+}
+
+#[test]
+fn short_deck_histories_post_antes_and_rank_the_short_way() {
+    let buffer = SharedBuffer::default();
+    let mut history = HandHistory::new(buffer.clone(), 0, "Test");
+    let cards = |text: &str| text.split_whitespace().map(|code| Card::from_code(code).unwrap()).collect::<Vec<Card>>();
+    let seats = (0..2).map(|seat| SeatInfo { seat, name: format!("Player {}", seat + 1), chips: 100, playing: true }).collect();
+    let board = cards("9s 8d 7c Ks Qd");
+    let events = [
+        GameEvent::HandStarted { hand_number: 1, button: 0, small_blind: 0, big_blind: 2, ante: 1, structure: BettingStructure::NoLimit, variant: Variant::ShortDeck, seats },
+        GameEvent::BlindPosted { seat: 1, blind: Blind::Ante, amount: 1, all_in: false },
+        GameEvent::BlindPosted { seat: 0, blind: Blind::Ante, amount: 1, all_in: false },
+        GameEvent::BlindPosted { seat: 0, blind: Blind::Button, amount: 2, all_in: false },
+        GameEvent::HoleCards { seat: 0, cards: cards("Ah 6h") },
+        GameEvent::HoleCards { seat: 1, cards: cards("Kh Kd") },
+        GameEvent::PlayerActed { seat: 1, action: Action::Call, amount: 2, bet: 2, raised_by: 0, all_in: false },
+        GameEvent::PlayerActed { seat: 0, action: Action::Check, amount: 0, bet: 2, raised_by: 0, all_in: false },
+        GameEvent::StreetDealt { street: Street::Flop, cards: board[..3].to_vec(), board: board[..3].to_vec() },
+        GameEvent::StreetDealt { street: Street::Turn, cards: board[3..4].to_vec(), board: board[..4].to_vec() },
+        GameEvent::StreetDealt { street: Street::River, cards: board[4..].to_vec(), board: board.clone() },
+        GameEvent::ShowdownStarted,
+        GameEvent::HandShown { seat: 1, cards: cards("Kh Kd"), best_five: cards("Kh Kd Ks Qd 9s"), hand: String::new() },
+        GameEvent::HandShown { seat: 0, cards: cards("Ah 6h"), best_five: cards("9s 8d 7c 6h Ah"), hand: String::new() },
+        GameEvent::PotAwarded { pot: 0, seat: 0, amount: 6, hand: None, share: PotShare::Whole },
+        GameEvent::HandEnded { chips: vec![103, 97] },
+    ];
+    for event in &events {
+        history.handle(event);
+    }
+
+    let text = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    assert!(text.contains("Short Deck Hold'em No Limit (Button Blind 2 - Ante 1)"), "{}", text);
+    assert!(text.contains("Player 2: posts the ante 1\n"), "{}", text);
+    assert!(text.contains("Player 1: posts button blind 2\n"), "{}", text);
+    // A-6-7-8-9 is the lowest straight
+    assert!(text.contains("Player 1: shows [Ah 6h] (a straight, Ace to Nine)"), "{}", text);
+    assert!(text.contains("Player 2: shows [Kh Kd] (three of a kind, Kings)"), "{}", text);
+}
+
+#[test]
+fn logs_every_event_as_a_json_line() {
+    let (mut table, mut controllers) = bot_table(5, 400);
+    let recorder = Recorder::default();
+    let buffer = SharedBuffer::default();
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(recorder.clone()), Box::new(EventLog::new(buffer.clone()))];
+    for _ in 0..20 {
+        table.play_hand(&mut controllers, &mut sinks);
+    }
+
+    let text = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    let events = recorder.0.borrow();
+    assert_eq!(lines.len(), events.len());
+    for (line, event) in lines.iter().zip(events.iter()) {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(value["schema"], SCHEMA_VERSION);
+        assert!(value["event"].is_string(), "{}", line);
+        assert_eq!(&event_log::parse_line(line).unwrap(), event);
+    }
+
+    let hole_cards = lines.iter().find(|line| line.contains("\"event\":\"hole_cards\"")).unwrap();
+    let value: serde_json::Value = serde_json::from_str(hole_cards).unwrap();
+    assert_eq!(value["cards"].as_array().unwrap().len(), 2);
+    assert_eq!(value["cards"][0].as_str().unwrap().len(), 2);
+}
+
+#[test]
+fn rejects_newer_schema_versions() {
+    let line = format!("{{\"schema\":{},\"event\":\"hand_mucked\",\"seat\":2}}", SCHEMA_VERSION);
+    assert_eq!(event_log::parse_line(&line), Ok(GameEvent::HandMucked { seat: 2 }));
+    let line = format!("{{\"schema\":{},\"event\":\"hand_mucked\",\"seat\":2}}", SCHEMA_VERSION + 1);
+    assert!(event_log::parse_line(&line).is_err());
+    assert!(event_log::parse_line("{\"schema\":0,\"event\":\"hand_mucked\",\"seat\":2}").is_err());
+
+    // Hands logged before the variant was written are Hold'em
+    let line = r#"{"schema":1,"event":"hand_started","hand_number":3,"button":1,"small_blind":5,"big_blind":10,"seats":[]}"#;
+    match event_log::parse_line(line) {
+        Ok(GameEvent::HandStarted { hand_number: 3, variant, .. }) => assert_eq!(variant, Variant::Holdem),
+        parsed => panic!("{:?}", parsed),
+    }
+    // Pots logged before split pots were added are won whole
+    let line = r#"{"schema":2,"event":"pot_awarded","pot":0,"seat":1,"amount":40,"hand":null}"#;
+    assert_eq!(event_log::parse_line(line), Ok(GameEvent::PotAwarded { pot: 0, seat: 1, amount: 40, hand: None, share: PotShare::Whole }));
+    // Hands logged before the ante was written have none
+    let line = r#"{"schema":3,"event":"hand_started","hand_number":3,"button":1,"small_blind":5,"big_blind":10,"variant":"omaha","seats":[]}"#;
+    match event_log::parse_line(line) {
+        Ok(GameEvent::HandStarted { hand_number: 3, ante, variant: Variant::Omaha, .. }) => assert_eq!(ante, 0),
+        parsed => panic!("{:?}", parsed),
+    }
+}
+
+#[test]
+fn replays_logged_hands() {
+    let (mut table, mut controllers) = calling_table(4, 300);
+    let buffer = SharedBuffer::default();
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(EventLog::new(buffer.clone()))];
+    let mut chips_after = Vec::new();
+    for _ in 0..30 {
+        table.play_hand(&mut controllers, &mut sinks);
+        chips_after.push(table.seats.iter().map(|seat| seat.chips).collect::<Vec<i32>>());
+    }
+
+    let text = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    let hands = read_hands(&text).unwrap();
+    assert_eq!(hands.len(), 30);
+    for (hand, chips) in hands.into_iter().zip(chips_after) {
+        let replay = Replay::new(hand);
+        // No chips are made or lost between steps
+        for step in 0..replay.steps() {
+            let replayed = replay.table_after(step);
+            assert_eq!(replayed.seats.iter().map(|seat| seat.chips).sum::<i32>() + replayed.pot, 1200);
+        }
+        let end = replay.table_after(replay.steps() - 1);
+        assert_eq!(end.seats.iter().map(|seat| seat.chips).collect::<Vec<i32>>(), chips);
+        assert_eq!(end.pot, 0);
+    }
+}
+
+#[test]
+fn resumed_sessions_deal_the_same_cards() {
+    let (mut table, mut controllers) = bot_table(4, 500);
+    let mut sinks: Vec<Box<dyn EventSink>> = Vec::new();
+    for _ in 0..5 {
+        table.play_hand(&mut controllers, &mut sinks);
+    }
+
+    let path = std::env::temp_dir().join(format!("texas_holdem_session_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    Session::new(table.clone(), vec![BotKind::Calling], true).save(path).unwrap();
+    let session = Session::load(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(session.table, table);
+    assert_eq!(session.bots, vec![BotKind::Calling]);
+    assert!(session.always_show);
+
+    // The saved table and the table that kept going play the next hands identically
+    let play = |mut table: Table| {
+        let recorder = Recorder::default();
+        let mut controllers: Vec<Box<dyn Controller>> = (0..4).map(|_| Box::new(CallingBot) as Box<dyn Controller>).collect();
+        let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(recorder.clone())];
+        for _ in 0..3 {
+            table.play_hand(&mut controllers, &mut sinks);
+        }
+        recorder.0.take()
+    };
+    assert_eq!(play(session.table), play(table));
+}
+
+#[test]
+fn profiles_keep_results_over_sessions() {
+    let path = std::env::temp_dir().join(format!("texas_holdem_profiles_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let _ = std::fs::remove_file(path);
+    // There are no profiles before anyone has played
+    let mut profiles = Profiles::load(path).unwrap();
+    assert!(profiles.profiles.is_empty());
+
+    profiles.record_session("Ann", 40, -300);
+    profiles.record_finish("Ann", Finish { position: 3, players: 4 });
+    profiles.record_session("Bob", 25, 150);
+    profiles.record_session("ann", 60, 800);
+    profiles.record_finish("ANN", Finish { position: 1, players: 4 });
+    profiles.record_session("Cat", 10, 900);
+    profiles.save(path).unwrap();
+    let profiles = Profiles::load(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let ann = profiles.get("ann").unwrap();
+    assert_eq!((ann.name.as_str(), ann.hands, ann.net_chips), ("Ann", 100, 500));
+    assert_eq!((ann.tournaments_won(), ann.average_finish()), (1, Some(2.0)));
+    // Games won come before chips won
+    let ranked: Vec<&str> = profiles.leaderboard().iter().map(|profile| profile.name.as_str()).collect();
+    assert_eq!(ranked, ["Ann", "Cat", "Bob"]);
+}
+
+#[test]
+fn pot_limit_raises_up_to_the_pot() {
+    let (mut table, mut controllers, maniac, recorder) = maniac_table(BettingStructure::PotLimit);
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(recorder.clone())];
+    table.play_hand(&mut controllers, &mut sinks);
+
+    // The first player calls 10 into a pot of 15 and can raise by 25 more
+    let requests = maniac.0.borrow();
+    assert_eq!((requests[0].min_raise, requests[0].max_raise), (20, 35));
+    for request in requests.iter() {
+        let pot_after_call = request.pot + request.current_bet - request.bet;
+        assert!(request.max_raise <= request.current_bet + pot_after_call);
+    }
+}
+
+#[test]
+fn fixed_limit_bets_one_size_and_caps_the_betting() {
+    let (mut table, mut controllers, maniac, recorder) = maniac_table(BettingStructure::FixedLimit);
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(recorder.clone())];
+    table.play_hand(&mut controllers, &mut sinks);
+
+    for request in maniac.0.borrow().iter() {
+        assert_eq!(request.min_raise, request.max_raise);
+    }
+    let mut street = Street::Preflop;
+    let mut bets = 1;
+    for event in recorder.0.borrow().iter() {
+        match event {
+            GameEvent::StreetDealt { street: dealt, .. } => {
+                street = *dealt;
+                bets = 0;
+            }
+            GameEvent::PlayerActed { raised_by, .. } if *raised_by > 0 => {
+                let size = if street < Street::Turn { 10 } else { 20 };
+                assert_eq!(*raised_by, size);
+                bets += 1;
+                assert!(bets <= 4);
+            }
+            _ => {}
+        }
+    }
+    // Every round was capped, so each player put in four bets on each street
+    let pot = recorder.0.borrow().iter().rev().find_map(|event| match event {
+        GameEvent::BettingRoundEnded { pot, .. } => Some(*pot),
+        _ => None,
+    });
+    assert_eq!(pot, Some(3 * (40 + 40 + 80 + 80)));
+}
+
+#[test]
+fn omaha_deals_four_cards_and_plays_exactly_two() {
+    let seats = (0..11).map(|i| Seat::new(&format!("Player {}", i + 1), 500)).collect();
+    let mut table = Table::new(seats, 5, 10);
+    table.variant = Variant::Omaha;
+    table.structure = BettingStructure::PotLimit;
+    let mut controllers: Vec<Box<dyn Controller>> = (0..11).map(|_| Box::new(CallingBot) as Box<dyn Controller>).collect();
+    let recorder = Recorder::default();
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(recorder.clone())];
+    for _ in 0..20 {
+        table.play_hand(&mut controllers, &mut sinks);
+    }
+
+    let events = recorder.0.borrow();
+    assert!(events.iter().all(|event| !matches!(event, GameEvent::HandStarted { variant, .. } if *variant != Variant::Omaha)));
+    let mut board = Vec::new();
+    let mut shown = 0;
+    for event in events.iter() {
+        match event {
+            GameEvent::HoleCards { cards, .. } => assert_eq!(cards.len(), 4),
+            GameEvent::StreetDealt { board: cards, .. } => board = cards.clone(),
+            GameEvent::HandShown { cards, best_five, .. } if !best_five.is_empty() => {
+                assert_eq!(best_five.iter().filter(|card| cards.contains(card)).count(), 2);
+                assert_eq!(best_five.iter().filter(|card| board.contains(card)).count(), 3);
+                shown += 1;
+            }
+            _ => {}
+        }
+    }
+    assert!(shown > 0);
+}
+
+#[test]
+fn omaha_hi_lo_splits_pots_between_high_and_low() {
+    let seats = (0..6).map(|i| Seat::new(&format!("Player {}", i + 1), 1000)).collect();
+    let mut table = Table::new(seats, 5, 10);
+    table.variant = Variant::OmahaHiLo;
+    table.structure = BettingStructure::PotLimit;
+    let mut controllers: Vec<Box<dyn Controller>> = (0..6).map(|_| Box::new(CallingBot) as Box<dyn Controller>).collect();
+    let recorder = Recorder::default();
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(recorder.clone())];
+    for _ in 0..30 {
+        table.play_hand(&mut controllers, &mut sinks);
+    }
+    assert_eq!(table.seats.iter().map(|seat| seat.chips).sum::<i32>(), 6000);
+
+    // Every pot is scooped, or split with the high half no smaller than the low half
+    let events = recorder.0.borrow();
+    let (mut scoops, mut splits) = (0, 0);
+    let mut shares: Vec<(usize, PotShare, i32)> = Vec::new();
+    for event in events.iter() {
+        match event {
+            GameEvent::PotAwarded { pot, share, amount, .. } => shares.push((*pot, *share, *amount)),
+            GameEvent::HandEnded { .. } => {
+                for pot in 0..=shares.iter().map(|&(pot, _, _)| pot).max().unwrap_or(0) {
+                    let half = |wanted: PotShare| shares.iter().filter(|&&(i, share, _)| i == pot && share == wanted).map(|&(_, _, amount)| amount).sum::<i32>();
+                    let (high, low) = (half(PotShare::High), half(PotShare::Low));
+                    if half(PotShare::Scoop) > 0 {
+                        assert_eq!((high, low), (0, 0));
+                        scoops += 1;
+                    } else {
+                        assert!(high >= low && high - low <= 1, "high {} and low {}", high, low);
+                        splits += 1;
+                    }
+                }
+                assert!(shares.iter().all(|&(_, share, _)| share != PotShare::Whole));
+                shares.clear();
+            }
+            _ => {}
+        }
+    }
+    assert!(scoops > 0 && splits > 0);
+}
+
+#[test]
+fn short_deck_antes_and_deals_from_thirty_six_cards() {
+    let seats = (0..4).map(|i| Seat::new(&format!("Player {}", i + 1), 1000)).collect();
+    let mut table = Table::new(seats, 5, 10);
+    table.variant = Variant::ShortDeck;
+    table.ante = 5;
+    let mut controllers: Vec<Box<dyn Controller>> = (0..4).map(|_| Box::new(CallingBot) as Box<dyn Controller>).collect();
+    let recorder = Recorder::default();
+    let mut sinks: Vec<Box<dyn EventSink>> = vec![Box::new(recorder.clone())];
+    for _ in 0..10 {
+        table.play_hand(&mut controllers, &mut sinks);
+    }
+    assert_eq!(table.seats.iter().map(|seat| seat.chips).sum::<i32>(), 4000);
+
+    let events = recorder.0.borrow();
+    for (i, event) in events.iter().enumerate() {
+        match event {
+            // Everyone antes 5, then the button posts 10 and is the last to act before the flop.
+            // There is no small blind.
+            GameEvent::HandStarted { button, small_blind, ante, .. } => {
+                assert_eq!((*small_blind, *ante), (0, 5));
+                let blinds: Vec<(usize, Blind, i32)> = events[i + 1..i + 6]
+                    .iter()
+                    .map(|event| match event {
+                        GameEvent::BlindPosted { seat, blind, amount, .. } => (*seat, *blind, *amount),
+                        _ => panic!("the antes and button blind come first, got {:?}", event),
+                    })
+                    .collect();
+                assert!(blinds[..4].iter().all(|&(_, blind, amount)| blind == Blind::Ante && amount == 5));
+                assert_eq!(blinds[4], (*button, Blind::Button, 10));
+                let first = events[i + 6..].iter().find_map(|event| match event {
+                    GameEvent::PlayerActed { seat, .. } => Some(*seat),
+                    _ => None,
+                });
+                assert_eq!(first, Some((button + 1) % 4));
+            }
+            GameEvent::HoleCards { cards, .. } | GameEvent::StreetDealt { cards, .. } => {
+                assert!(cards.iter().all(|card| card.rank >= Rank::Six));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn splits_all_ins_into_side_pots() {
+    // Seat 0 is all in for 50, seat 1 for 200, seat 2 covers them and seat 3 folded after putting in 20
+    let pots = side_pots(&[50, 200, 300, 20], &[true, true, true, false]);
+    assert_eq!(
+        pots,
+        vec![
+            Pot { amount: 170, eligible: vec![0, 1, 2] },
+            Pot { amount: 300, eligible: vec![1, 2] },
+            Pot { amount: 100, eligible: vec![2] },
+        ]
+    );
+}
+
+// Takes its time over every action and keeps the requests it was sent
+#[derive(Clone, Default)]
+struct Ponderer(Rc<RefCell<Vec<ActionRequest>>>);
+
+impl Controller for Ponderer {
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        self.0.borrow_mut().push(request.clone());
+        thread::sleep(Duration::from_millis(1100));
+        Action::Fold
+    }
+}
+
+#[test]
+fn the_shot_clock_spends_and_tops_up_the_time_bank() {
+    let (mut table, mut controllers, maniac, _) = maniac_table(BettingStructure::NoLimit);
+    table.play_hand(&mut controllers, &mut Vec::new());
+    let mut request = maniac.0.borrow()[0].clone();
+    assert_eq!((request.hand_number, request.time_limit, request.time_bank), (1, None, 0));
+
+    // Without time of its own for the action, every second is taken from the bank
+    let ponderer = Ponderer::default();
+    let mut timed = Timed::new(Box::new(ponderer.clone()), ShotClock { action: 0, bank: 3, top_up: 2 });
+    timed.act(&request);
+    timed.act(&request);
+    request.hand_number = 2;
+    timed.act(&request);
+    request.hand_number = 3;
+    timed.act(&request);
+    let asked: Vec<(Option<u32>, u32)> = ponderer.0.borrow().iter().map(|request| (request.time_limit, request.time_bank)).collect();
+    // The bank is topped up two seconds every hand, never past full
+    assert_eq!(asked, vec![(Some(3), 3), (Some(1), 1), (Some(2), 2), (Some(3), 3)]);
+
+    // A player who sat out some hands gets the top up of every hand they missed
+    let ponderer = Ponderer::default();
+    let mut timed = Timed::new(Box::new(ponderer.clone()), ShotClock { action: 0, bank: 5, top_up: 1 });
+    request.hand_number = 1;
+    timed.act(&request);
+    timed.act(&request);
+    request.hand_number = 3;
+    timed.act(&request);
+    let asked: Vec<u32> = ponderer.0.borrow().iter().map(|request| request.time_bank).collect();
+    assert_eq!(asked, vec![5, 3, 4]);
+
+    // A countdown shows the action's own time first, then the bank
+    request.time_limit = Some(12);
+    request.time_bank = 5;
+    let countdown = Countdown::start(&request).unwrap();
+    assert_eq!((countdown.describe().as_str(), countdown.in_bank(), countdown.expired()), ("7 seconds to act", false, false));
+    request.time_bank = 12;
+    assert_eq!(Countdown::start(&request).unwrap().describe(), "Time bank: 12 seconds");
+    request.time_limit = None;
+    assert_eq!(Countdown::start(&request), None);
+}
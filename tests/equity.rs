@@ -0,0 +1,169 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use texas_holdem::card::{Card, CardSet};
+use serde_json::json;
+use texas_holdem::engine::ActionRequest;
+use texas_holdem::equity::{calculate, calculate_ranges, parse_cards, EquityReport};
+use texas_holdem::eval::Hand;
+use texas_holdem::events::{Action, EventSink, GameEvent, SeatInfo};
+use texas_holdem::odds::{OddsAgainst, OddsHelper, RAISING_RANGE};
+use texas_holdem::range::Range;
+
+fn cards(text: &str) -> Vec<Card> {
+    parse_cards(text).unwrap()
+}
+
+fn equity(hands: &[&str], board: &str, dead: &str) -> Result<EquityReport, String> {
+    let hands: Vec<Vec<Card>> = hands.iter().map(|hand| cards(hand)).collect();
+    calculate(&hands, &cards(board), &cards(dead), 20_000, &mut StdRng::seed_from_u64(3))
+}
+
+#[test]
+fn reads_cards_written_together_or_apart() {
+    assert_eq!(cards("AhKd"), cards("Ah Kd"));
+    assert_eq!(cards("ah,kd"), vec![Card::from_code("Ah").unwrap(), Card::from_code("Kd").unwrap()]);
+    assert_eq!(parse_cards("AhK"), Err("AhK is not a list of cards such as AhKh".to_string()));
+    assert_eq!(parse_cards("AhKx"), Err("Kx is not a card".to_string()));
+}
+
+#[test]
+fn counts_every_board_when_there_are_few() {
+    // On the turn, Ace King wins with the nine hearts left and the three Aces and Kings that pair it
+    let report = equity(&["AhKh", "QsQd"], "2h7hTc9s", "").unwrap();
+    assert!(report.exact);
+    assert_eq!(report.boards, 44);
+    assert_eq!(report.players[0].win, 15.0 / 44.0);
+    assert_eq!(report.players[1].equity, 29.0 / 44.0);
+
+    // A royal flush on the board is shared by everyone
+    let report = equity(&["2c3d", "4c5d", "7s8s"], "AhKhQhJhTh", "").unwrap();
+    assert_eq!(report.boards, 1);
+    for player in &report.players {
+        assert_eq!((player.win, player.tie), (0.0, 1.0));
+        assert!((player.equity - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    // Dead cards can't come, without the hearts only the Aces and Kings are left to win
+    let report = equity(&["AhKh", "QsQd"], "2h7hTc9s", "3h4h5h6h8h9hThJhQh").unwrap();
+    assert_eq!(report.boards, 35);
+    assert_eq!(report.players[0].win, 6.0 / 35.0);
+}
+
+#[test]
+fn samples_boards_when_there_are_many() {
+    // Aces are about 82% against Kings before the flop
+    let report = equity(&["AsAd", "KhKc"], "", "").unwrap();
+    assert!(!report.exact);
+    assert_eq!(report.boards, 20_000);
+    let aces = report.players[0];
+    assert!(aces.margin > 0.0 && aces.margin < 0.01, "{:?}", aces);
+    assert!((aces.equity - 0.82).abs() < 2.0 * aces.margin, "{:?}", aces);
+    let total: f64 = report.players.iter().map(|player| player.equity).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn equity_errors_name_the_problem() {
+    assert_eq!(equity(&["AhKh"], "", ""), Err("equity needs the hands of at least two players".to_string()));
+    assert_eq!(equity(&["AhKh", "Qs"], "", ""), Err("a hand has two hole cards, got 1".to_string()));
+    assert_eq!(equity(&["AhKh", "QsQd"], "Ah7c2d", ""), Err("Ah is given more than once".to_string()));
+    assert_eq!(equity(&["AhKh", "QsQd"], "2c3c4c5c6c7c", ""), Err("a board has at most five cards, got 6".to_string()));
+}
+
+fn size(range: &str) -> f64 {
+    Range::parse(range).unwrap().size()
+}
+
+#[test]
+fn reads_range_notation() {
+    assert_eq!(size("QQ+"), 18.0);
+    assert_eq!(size("AKs"), 4.0);
+    assert_eq!(size("KQo"), 12.0);
+    assert_eq!(size("AK"), 16.0);
+    assert_eq!(size("A5s-A2s"), 16.0);
+    assert_eq!(size("99-JJ"), 18.0);
+    assert_eq!(size("AhKh"), 1.0);
+    // The kicker goes up to one below the high card, connectors go up together to AKs
+    assert_eq!(Range::parse("ATs+").unwrap(), Range::parse("ATs, AJs, AQs, AKs").unwrap());
+    assert_eq!(Range::parse("76s+").unwrap(), Range::parse("76s, 87s, 98s, T9s, JTs, QJs, KQs, AKs").unwrap());
+    // Hands given twice count once, with the later weight
+    assert_eq!(size("QQ+, AKs, A5s-A2s, KQo, 76s+, AKs"), 18.0 + 4.0 + 16.0 + 12.0 + 24.0 + 4.0);
+    assert_eq!(size("KK+, AA:0.5"), 6.0 + 3.0);
+    assert_eq!(size("QQ+:0"), 0.0);
+    assert_eq!(Range::parse("AA").unwrap().share(), 6.0 / 1326.0);
+
+    assert_eq!(Range::parse("QQs"), Err("QQs is not a hand such as QQ+, AKs, A5s-A2s or AhKh".to_string()));
+    assert_eq!(Range::parse("AhAh"), Err("AhAh holds the same card twice".to_string()));
+    assert!(Range::parse("A5s-K2s").unwrap_err().contains("span"));
+    assert!(Range::parse("AKs:2").unwrap_err().contains("from 0 to 1"));
+    assert!(Range::parse(" , ").is_err());
+}
+
+#[test]
+fn draws_a_range_as_a_grid() {
+    let grid = Range::parse("QQ+, AKs, AQo:0.5, 32o").unwrap().grid();
+    let rows: Vec<&str> = grid.lines().collect();
+    assert_eq!(rows.len(), 13);
+    let cells = |row: usize| rows[row].split_whitespace().collect::<Vec<_>>();
+    assert_eq!(cells(0)[..3], ["AA", "AKs", "."]);
+    assert_eq!(cells(1)[..3], [".", "KK", "."]);
+    // Offsuit hands are below the diagonal
+    assert_eq!(cells(2)[..3], ["50%", ".", "QQ"]);
+    assert_eq!(cells(12)[11], "32o");
+    assert!(rows.iter().all(|row| row.split_whitespace().count() == 13));
+}
+
+#[test]
+fn ranges_lose_the_hands_the_known_cards_block() {
+    let known: CardSet = parse_cards("AhKhAd").unwrap().into_iter().collect();
+    // Ace King is left with the other two Aces and three Kings
+    assert_eq!(Range::parse("AK").unwrap().without(known).size(), 6.0);
+
+    // Against Aces holding two of them, only one hand of Aces is left and it always ties or wins
+    let mut rng = StdRng::seed_from_u64(5);
+    let ranges = [Range::parse("AhAs").unwrap(), Range::parse("AA, KK").unwrap()];
+    let report = calculate_ranges(&ranges, &parse_cards("2c7dTh").unwrap(), &[], 1000, &mut rng).unwrap();
+    assert!(report.exact);
+    // Each of AcAd and the six Kings is dealt with every turn and river
+    assert_eq!(report.boards, 7 * 990);
+
+    // A weight makes a hand come up that much less often
+    let even = calculate_ranges(&[Range::parse("AhAs").unwrap(), Range::parse("AcAd, KK").unwrap()], &parse_cards("2c7dTh").unwrap(), &[], 1000, &mut rng).unwrap();
+    let weighted = calculate_ranges(&[Range::parse("AhAs").unwrap(), Range::parse("AcAd, KK:0.5").unwrap()], &parse_cards("2c7dTh").unwrap(), &[], 1000, &mut rng).unwrap();
+    assert_eq!(even.players[0].equity, report.players[0].equity);
+    assert!(weighted.players[0].equity < even.players[0].equity);
+
+    let error = calculate_ranges(&[Range::parse("AhAs").unwrap(), Range::parse("AA").unwrap()], &parse_cards("2c7dTh").unwrap(), &parse_cards("AcAd").unwrap(), 1000, &mut rng).unwrap_err();
+    assert_eq!(error, "the players' hands can't all be dealt together");
+    let error = calculate_ranges(&[Range::parse("AhAs").unwrap(), Range::parse("AcAd").unwrap()], &parse_cards("Ac2c7d").unwrap(), &[], 1000, &mut rng).unwrap_err();
+    assert_eq!(error, "player 2 has no hand in their range that the known cards leave");
+}
+
+#[test]
+fn the_odds_helper_counts_outs_and_pot_odds() {
+    let seat = |name: &str| json!({ "name": name, "chips": 990, "bet": 10, "folded": false, "acted": true, "all_in": false, "playing": true });
+    let request: ActionRequest = serde_json::from_value(json!({
+        "hand_number": 1, "seat": 0, "street": "flop", "hole_cards": ["Ah", "Kh"], "board": ["Qh", "7h", "2c"],
+        "seats": [seat("You"), seat("Player 2"), seat("Player 3")],
+        "pot": 30, "current_bet": 10, "bet": 0, "chips": 1000, "to_call": 10, "min_raise": 20, "max_raise": 1000,
+    }))
+    .unwrap();
+    let mut helper = OddsHelper::new(OddsAgainst::Estimated);
+    let odds = helper.odds(&request).unwrap();
+    assert_eq!(odds.hand.as_deref(), Some("High card, Ace"));
+    // Nine hearts make a flush, the other Aces and Kings a pair
+    assert_eq!(odds.outs.iter().map(|(hand, cards)| (*hand, cards.len())).collect::<Vec<_>>(), vec![(Hand::Flush, 9), (Hand::OnePair, 6)]);
+    assert_eq!(odds.out_count(), 15);
+    assert_eq!(odds.pot_odds, Some(0.25));
+    assert_eq!(odds.opponents, 2);
+    assert!(odds.equity.equity > 0.3 && odds.equity.equity < 0.7, "{:?}", odds.equity);
+
+    // A player who raised before the flop is given a raising range, until the next hand
+    let seats = (0..3).map(|seat| SeatInfo { seat, name: format!("Player {}", seat + 1), chips: 1000, playing: true }).collect();
+    helper.handle(&GameEvent::HandStarted { hand_number: 1, button: 0, small_blind: 5, big_blind: 10, ante: 0, structure: Default::default(), variant: Default::default(), seats });
+    helper.handle(&GameEvent::PlayerActed { seat: 1, action: Action::Raise(30), amount: 30, bet: 30, raised_by: 20, all_in: false });
+    assert_eq!(helper.range(1), Range::parse(RAISING_RANGE).unwrap());
+    assert_eq!(helper.range(2), Range::any());
+    assert_eq!(OddsHelper::new(OddsAgainst::Random).range(1), Range::any());
+    assert_eq!(Range::any().size(), 1326.0);
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use texas_holdem::card::{Card, CardSet, Rank};
+use texas_holdem::deck::Deck;
+use texas_holdem::eval::{evaluate, evaluate_cards, evaluate_omaha, BestHand, Hand, HandRank, LowHand, Ranking};
+
+/*
+ * Straightforward reference evaluator. It categorises the cards by counting ranks and suits
+ * and returns the category with the tie-breaking ranks (0 = Two, 12 = Ace) in order.
+ */
+fn reference(cards: &[Card]) -> (Hand, Vec<usize>) {
+    fn straight_high(present: &[bool; 13]) -> Option<usize> {
+        for high in (4..13).rev() {
+            if (high - 4..=high).all(|rank| present[rank]) {
+                return Some(high);
+            }
+        }
+        // A-2-3-4-5
+        if present[12] && (0..4).all(|rank| present[rank]) {
+            return Some(3);
+        }
+        None
+    }
+
+    let mut counts = [0; 13];
+    let mut suited = [[false; 13]; 4];
+    let mut suit_counts = [0; 4];
+    for card in cards {
+        counts[card.rank as usize] += 1;
+        suited[card.suit as usize][card.rank as usize] = true;
+        suit_counts[card.suit as usize] += 1;
+    }
+    let ranks_with = |count: usize| -> Vec<usize> { (0..13).rev().filter(|&r| counts[r] == count).collect() };
+    let top_except = |exclude: &[usize], n: usize| -> Vec<usize> {
+        (0..13).rev().filter(|r| counts[*r] > 0 && !exclude.contains(r)).take(n).collect()
+    };
+    let flush_suit = (0..4).find(|&s| suit_counts[s] >= 5);
+
+    if let Some(suit) = flush_suit {
+        if let Some(high) = straight_high(&suited[suit]) {
+            return (Hand::StraightFlush, vec![high]);
+        }
+    }
+    let quads = ranks_with(4);
+    if let Some(&quad) = quads.first() {
+        let mut kickers = vec![quad];
+        kickers.extend(top_except(&[quad], 1));
+        return (Hand::FourOfAKind, kickers);
+    }
+    let trips = ranks_with(3);
+    let pairs = ranks_with(2);
+    if let Some(&trip) = trips.first() {
+        let pair = trips.get(1).copied().max(pairs.first().copied());
+        if let Some(pair) = pair {
+            return (Hand::FullHouse, vec![trip, pair]);
+        }
+    }
+    if let Some(suit) = flush_suit {
+        return (Hand::Flush, (0..13).rev().filter(|&r| suited[suit][r]).take(5).collect());
+    }
+    let present: [bool; 13] = std::array::from_fn(|r| counts[r] > 0);
+    if let Some(high) = straight_high(&present) {
+        return (Hand::Straight, vec![high]);
+    }
+    if let Some(&trip) = trips.first() {
+        let mut kickers = vec![trip];
+        kickers.extend(top_except(&[trip], 2));
+        return (Hand::ThreeOfAKind, kickers);
+    }
+    if pairs.len() >= 2 {
+        let mut kickers = vec![pairs[0], pairs[1]];
+        kickers.extend(top_except(&[pairs[0], pairs[1]], 1));
+        return (Hand::TwoPair, kickers);
+    }
+    if let Some(&pair) = pairs.first() {
+        let mut kickers = vec![pair];
+        kickers.extend(top_except(&[pair], 3));
+        return (Hand::OnePair, kickers);
+    }
+    (Hand::HighCard, top_except(&[], 5))
+}
+
+/*
+ * Checks that the evaluator agrees with the reference: the same category, the same value for
+ * hands the reference considers equal, and values in the same order as the reference.
+ */
+#[derive(Default)]
+struct Comparison {
+    values: HashMap<(Hand, Vec<usize>), HandRank>,
+    category_counts: HashMap<Hand, u64>,
+}
+
+impl Comparison {
+    fn check(&mut self, cards: &[Card]) {
+        let rank = evaluate(cards.iter().collect());
+        let key = reference(cards);
+        assert_eq!(rank.hand(), key.0, "wrong category for {:?}", cards);
+        let expected = *self.values.entry(key).or_insert(rank);
+        assert_eq!(rank, expected, "equal hands evaluated differently: {:?}", cards);
+        *self.category_counts.entry(rank.hand()).or_insert(0) += 1;
+    }
+
+    fn assert_ordered(&self) {
+        let mut pairs: Vec<_> = self.values.iter().collect();
+        pairs.sort_by(|a, b| a.0.cmp(b.0));
+        for window in pairs.windows(2) {
+            assert!(window[0].1 < window[1].1, "{:?} should lose to {:?}", window[0].0, window[1].0);
+        }
+    }
+}
+
+// Calls f with every combination of `size` cards from the full deck
+fn for_each_hand(size: usize, f: &mut dyn FnMut(&[Card])) {
+    fn extend(start: u8, size: usize, chosen: &mut Vec<Card>, f: &mut dyn FnMut(&[Card])) {
+        if chosen.len() == size {
+            f(chosen);
+            return;
+        }
+        for index in start..=(52 - (size - chosen.len()) as u8) {
+            chosen.push(Card::from_index(index).unwrap());
+            extend(index + 1, size, chosen, f);
+            chosen.pop();
+        }
+    }
+    extend(0, size, &mut Vec::with_capacity(size), f);
+}
+
+fn cards(text: &str) -> Vec<Card> {
+    text.split_whitespace().map(|card| Card::from_code(card).unwrap()).collect()
+}
+
+#[test]
+fn ranks_known_hands() {
+    let royal = evaluate_cards(&cards("As Ks Qs Js Ts"));
+    let wheel = evaluate_cards(&cards("Ah 2c 3d 4s 5h"));
+    let six_high = evaluate_cards(&cards("2c 3d 4s 5h 6h"));
+    let ace_flush = evaluate_cards(&cards("Ah 9h 7h 4h 2h"));
+    let full_house = evaluate_cards(&cards("Kh Kd Ks 7c 7d"));
+
+    assert_eq!(royal.hand(), Hand::StraightFlush);
+    assert_eq!(wheel.hand(), Hand::Straight);
+    assert!(wheel < six_high);
+    assert!(six_high < ace_flush);
+    assert!(ace_flush < full_house);
+    assert!(full_house < royal);
+}
+
+#[test]
+fn uses_the_best_five_of_seven() {
+    let seven = evaluate_cards(&cards("Kh Kd 7s 7c 7d 2h 3c"));
+    assert_eq!(seven, evaluate_cards(&cards("7s 7c 7d Kh Kd")));
+
+    // Two trips make a full house with the higher trips
+    let two_trips = evaluate_cards(&cards("9h 9d 9s 4c 4d 4h Ac"));
+    assert_eq!(two_trips, evaluate_cards(&cards("9h 9d 9s 4c 4d")));
+
+    // Six cards to a flush use the top five
+    let six_flush = evaluate_cards(&cards("Ah Kh 9h 7h 4h 2h"));
+    assert_eq!(six_flush, evaluate_cards(&cards("Ah Kh 9h 7h 4h")));
+}
+
+#[test]
+fn suits_do_not_break_ties() {
+    let spades = evaluate_cards(&cards("As Kd Qc Jh 9s"));
+    let hearts = evaluate_cards(&cards("Ah Ks Qd Jc 9h"));
+    assert_eq!(spades, hearts);
+}
+
+#[test]
+fn card_sets_and_slices_agree() {
+    let hand = cards("Tc Jc Qc Kc Ac 2d 2h");
+    let set: CardSet = hand.iter().collect();
+    assert_eq!(evaluate(set), evaluate_cards(&hand));
+    assert_eq!(evaluate(set).hand(), Hand::StraightFlush);
+}
+
+#[test]
+fn describes_the_best_five_cards() {
+    let full_house = BestHand::new(&cards("7c Kh 2d Kd 7s Ks 3c"));
+    assert_eq!(full_house.description(), "Full house, Kings full of Sevens");
+    assert_eq!(full_house.cards, cards("Ks Kh Kd 7s 7c"));
+
+    let flush = BestHand::new(&cards("Ah 9h 7h 4h 2h Kc Qd"));
+    assert_eq!(flush.description(), "Flush, Ace-high");
+
+    let wheel = BestHand::new(&cards("Ah 2c 3d 4s 5h 9c 9d"));
+    assert_eq!(wheel.description(), "Straight, Five-high");
+    assert_eq!(wheel.cards, cards("5h 4s 3d 2c Ah"));
+
+    assert_eq!(BestHand::new(&cards("6h 6c Ts Td 2c 3c 4d")).description(), "Two pair, Tens and Sixes");
+    assert_eq!(BestHand::new(&cards("As Ks Qs Js Ts 9s")).description(), "Royal flush");
+}
+
+#[test]
+fn omaha_hands_use_exactly_two_hole_cards() {
+    // Four hearts on the board are no flush with one heart in the hand
+    let hand = BestHand::omaha(&cards("Ah Ks 7c 2d"), &cards("Qh Jh Th 3h 9c"));
+    assert_eq!(hand.rank.hand(), Hand::Straight);
+    assert_eq!(hand.description(), "Straight, Ace-high");
+    // Four of a kind in the hand plays as a pair with three board cards
+    let quads = evaluate_omaha(&cards("9s 9h 9d 9c"), &cards("2c 5d 8h Ks Jd"));
+    assert_eq!(quads.hand(), Hand::OnePair);
+    // Three of a kind on the board only fills up with a pair in the hand
+    let hand = BestHand::omaha(&cards("Qs Qd 4c 3c"), &cards("7h 7d 7s 2h Ac"));
+    assert_eq!(hand.description(), "Full house, Sevens full of Queens");
+    let hand = BestHand::omaha(&cards("Ks Qd 4c 3c"), &cards("7h 7d 7s 2h Ac"));
+    assert_eq!(hand.rank.hand(), Hand::ThreeOfAKind);
+    // Every five cards have two from the hand and three from the board
+    let hole = cards("Ah Ks 7c 2d");
+    assert_eq!(hand_cards_from(&hole, &BestHand::omaha(&hole, &cards("Qh Jh Th 3h 9c")).cards), 2);
+}
+
+#[test]
+fn omaha_lows_are_eight_or_better() {
+    // The best low takes the two lowest hole cards that don't pair the board
+    let low = LowHand::omaha(&cards("Ah 2s Kc 3d"), &cards("4h 6d 8c Qs Js")).unwrap();
+    assert_eq!(low.description(), "8-6-4-2-A low");
+    assert_eq!(low.description(), LowHand::omaha(&cards("Ah 2s 2c 3d"), &cards("4h 6d 8c Qs Js")).unwrap().description());
+    // Two low cards on the board are no low, and a Nine is too high
+    assert_eq!(LowHand::omaha(&cards("Ah 2s 3c 4d"), &cards("5h 6d Kc Qs Js")), None);
+    assert_eq!(LowHand::omaha(&cards("Ah 2s Kc Kd"), &cards("3h 9d Tc Qs 4s")), None);
+    // Pairs don't count, straights and flushes are still lows, and a lower top card is better
+    assert_eq!(LowHand::omaha(&cards("Ah As Kc Kd"), &cards("2h 3d 4c Qs Js")), None);
+    let wheel = LowHand::omaha(&cards("Ah 2h Kc Kd"), &cards("3h 4h 5h Qs Js")).unwrap();
+    assert_eq!(wheel.description(), "5-4-3-2-A low");
+    let seven = LowHand::omaha(&cards("7h 5s Kc Kd"), &cards("2c 3d 4c Qs Js")).unwrap();
+    let eight = LowHand::omaha(&cards("8h As Kc Kd"), &cards("2c 3d 4c Qs Js")).unwrap();
+    assert!(wheel.rank < seven.rank && seven.rank < eight.rank);
+}
+
+#[test]
+fn short_deck_flushes_beat_full_houses() {
+    let short = Ranking::ShortDeck;
+    let deck = Deck::for_ranking(short);
+    assert_eq!(deck.cards.len(), 36);
+    assert!(deck.cards.iter().all(|card| card.rank >= Rank::Six));
+
+    let flush = short.evaluate_cards(&cards("Ah Jh 9h 7h 6h Ks Kd"));
+    let full_house = short.evaluate_cards(&cards("Ks Kd Kc 7s 7d 8h 9h"));
+    assert!(flush > full_house && full_house > short.evaluate_cards(&cards("Ts 9d 8c 7s 6d Ah Kh")));
+    assert!(evaluate_cards(&cards("Ah Jh 9h 7h 6h Ks Kd")) < evaluate_cards(&cards("Ks Kd Kc 7s 7d 8h 9h")));
+
+    // The Ace plays low in A-6-7-8-9, the lowest straight
+    let wheel = short.best_hand(&cards("Ah 9s 8d 7c 6h Ks Qd"));
+    assert_eq!(wheel.rank.hand(), Hand::Straight);
+    assert_eq!(wheel.description(), "Straight, Nine-high");
+    assert_eq!(wheel.cards[4].rank, Rank::Ace);
+    assert!(wheel.rank < short.evaluate_cards(&cards("Ts 9d 8c 7s 6d Kh Qd")));
+    assert_eq!(short.best_hand(&cards("Ah 9h 8h 7h 6h Ks Qd")).description(), "Straight flush, Nine-high");
+}
+
+fn hand_cards_from(hole: &[Card], five: &[Card]) -> usize {
+    five.iter().filter(|card| hole.contains(card)).count()
+}
+
+#[test]
+fn all_five_card_hands_match_reference() {
+    let mut comparison = Comparison::default();
+    for_each_hand(5, &mut |hand| comparison.check(hand));
+    comparison.assert_ordered();
+
+    assert_eq!(comparison.values.len(), 7462);
+    let expected = [
+        (Hand::StraightFlush, 40),
+        (Hand::FourOfAKind, 624),
+        (Hand::FullHouse, 3744),
+        (Hand::Flush, 5108),
+        (Hand::Straight, 10200),
+        (Hand::ThreeOfAKind, 54912),
+        (Hand::TwoPair, 123552),
+        (Hand::OnePair, 1098240),
+        (Hand::HighCard, 1302540),
+    ];
+    for (hand, count) in expected {
+        assert_eq!(comparison.category_counts[&hand], count, "{:?}", hand);
+    }
+}
+
+/*
+ * Compares the evaluator with the reference on all 133,784,560 seven card hands.
+ * Takes a while, run it with `cargo test --release -- --ignored`.
+ */
+#[test]
+#[ignore]
+fn all_seven_card_hands_match_reference() {
+    let mut comparison = Comparison::default();
+    for_each_hand(7, &mut |hand| comparison.check(hand));
+    comparison.assert_ordered();
+
+    assert_eq!(comparison.values.len(), 4824);
+    let expected = [
+        (Hand::StraightFlush, 41584),
+        (Hand::FourOfAKind, 224848),
+        (Hand::FullHouse, 3473184),
+        (Hand::Flush, 4047644),
+        (Hand::Straight, 6180020),
+        (Hand::ThreeOfAKind, 6461620),
+        (Hand::TwoPair, 31433400),
+        (Hand::OnePair, 58627800),
+        (Hand::HighCard, 23294460),
+    ];
+    for (hand, count) in expected {
+        assert_eq!(comparison.category_counts[&hand], count, "{:?}", hand);
+    }
+}
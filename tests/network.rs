@@ -0,0 +1,281 @@
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use texas_holdem::bot::{BotKind, CallingBot};
+use texas_holdem::card::Card;
+use texas_holdem::client::{Client, Spectator};
+use texas_holdem::engine::{ActionRequest, Controller, Seat, Table};
+use texas_holdem::eval::{BestHand, Hand};
+use texas_holdem::events::{Action, BettingStructure, EventSink, GameEvent, SeatInfo, Variant};
+use texas_holdem::protocol::{self, ClientMessage, ServerMessage, PROTOCOL_VERSION};
+use texas_holdem::server::TableServer;
+use texas_holdem::spectators::RevealDelay;
+use tungstenite::Message;
+
+#[derive(Clone, Default)]
+struct Recorder(Rc<RefCell<Vec<GameEvent>>>);
+
+impl EventSink for Recorder {
+    fn handle(&mut self, event: &GameEvent) {
+        self.0.borrow_mut().push(event.clone());
+    }
+}
+
+// Follows a table, keeping how many events had come before each player's cards were revealed
+#[derive(Default)]
+struct Rail {
+    events: Vec<GameEvent>,
+    revealed: Vec<(u32, usize, usize)>,
+}
+
+impl EventSink for Rail {
+    fn handle(&mut self, event: &GameEvent) {
+        self.events.push(event.clone());
+    }
+}
+
+impl Spectator for Rail {
+    fn revealed(&mut self, hand_number: u32, seat: usize, cards: &[Card]) {
+        assert_eq!(cards.len(), 2);
+        self.revealed.push((hand_number, seat, self.events.len()));
+    }
+}
+
+// Shows every hand it is asked about, keeping the hands it was shown
+#[derive(Default)]
+struct Shower(Vec<Option<BestHand>>);
+
+impl Controller for Shower {
+    fn act(&mut self, request: &ActionRequest) -> Action {
+        request.check_or_fold()
+    }
+
+    fn show_hand(&mut self, _cards: &[Card], hand: Option<&BestHand>, _uncontested: bool) -> bool {
+        self.0.push(hand.cloned());
+        true
+    }
+}
+
+// Starts a server on free ports of this computer, for clients and for browsers. It returns the table once it closes.
+fn start_server(players: usize, humans: usize, hands: u32) -> (String, String, JoinHandle<Table>) {
+    start_server_with(players, humans, hands, None)
+}
+
+fn start_server_with(players: usize, humans: usize, hands: u32, god_view: Option<RevealDelay>) -> (String, String, JoinHandle<Table>) {
+    let seats = (0..players).map(|i| Seat::new(&format!("Player {}", i + 1), 1000)).collect();
+    let mut table = Table::new(seats, 5, 10);
+    table.seed = 7;
+    let mut server = TableServer::bind("127.0.0.1:0", table, humans, vec![BotKind::Calling]).unwrap();
+    server.god_view = god_view;
+    server.disconnect_bank = Duration::from_millis(200);
+    let address = server.local_addr().to_string();
+    let web = server.listen_web("127.0.0.1:0").unwrap().to_string();
+    let handle = thread::spawn(move || {
+        server.run(Some(hands), Vec::new()).unwrap();
+        server.table
+    });
+    (address, web, handle)
+}
+
+// Plays a client that always checks or calls, it returns the events it was sent and why the table closed
+fn play(mut client: Client) -> JoinHandle<(Vec<GameEvent>, String)> {
+    thread::spawn(move || {
+        let recorder = Recorder::default();
+        let reason = client.play(&mut CallingBot, &mut recorder.clone()).unwrap();
+        let events = recorder.0.take();
+        (events, reason)
+    })
+}
+
+#[test]
+fn players_only_see_their_own_hole_cards() {
+    let (address, _, server) = start_server(3, 2, 5);
+    let ann = Client::join(&address, "Ann").unwrap();
+    let bob = Client::join(&address, "Bob").unwrap();
+    assert_eq!((ann.seat, bob.seat), (0, 1));
+    let players = [play(ann), play(bob)];
+
+    for (seat, player) in players.into_iter().enumerate() {
+        let (events, reason) = player.join().unwrap();
+        assert_eq!(reason, "the last hand has been played");
+        let started: Vec<&GameEvent> = events.iter().filter(|event| matches!(event, GameEvent::HandStarted { .. })).collect();
+        assert_eq!(started.len(), 5);
+        if let GameEvent::HandStarted { seats, .. } = started[0] {
+            assert_eq!(seats[0].name, "Ann");
+            assert_eq!(seats[1].name, "Bob");
+        }
+        let hole_cards: Vec<usize> = events.iter().filter_map(|event| match event {
+            GameEvent::HoleCards { seat, .. } => Some(*seat),
+            _ => None,
+        }).collect();
+        assert_eq!(hole_cards, vec![seat; 5]);
+    }
+
+    let table = server.join().unwrap();
+    assert_eq!(table.hand_number, 5);
+    assert_eq!(table.seats.iter().map(|seat| seat.chips).sum::<i32>(), 3000);
+}
+
+#[test]
+fn refuses_names_that_are_taken() {
+    let (address, _, server) = start_server(2, 2, 1);
+    let ann = Client::join(&address, "Ann").unwrap();
+    assert_eq!(Client::join(&address, " ann ").err().unwrap(), "the name ann is taken");
+    assert_eq!(Client::join(&address, "").err().unwrap(), "a name is needed to join");
+    let bob = Client::join(&address, "Bob").unwrap();
+    assert_eq!(bob.seat, 1);
+
+    let players = [play(ann), play(bob)];
+    for player in players {
+        assert_eq!(player.join().unwrap().1, "the last hand has been played");
+    }
+    assert_eq!(server.join().unwrap().seats[1].name, "Bob");
+}
+
+#[test]
+fn a_silent_connection_does_not_hold_up_the_others() {
+    let (address, _, server) = start_server(3, 2, 1);
+    let _silent = TcpStream::connect(&address).unwrap();
+    let started = Instant::now();
+    let ann = Client::join(&address, "Ann").unwrap();
+    let bob = Client::join(&address, "Bob").unwrap();
+    assert_eq!((ann.seat, bob.seat), (0, 1));
+    let players = [play(ann), play(bob)];
+    for player in players {
+        assert_eq!(player.join().unwrap().1, "the last hand has been played");
+    }
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(server.join().unwrap().hand_number, 1);
+}
+
+#[test]
+fn browsers_get_the_page_and_play_over_a_websocket() {
+    let (address, web, server) = start_server(3, 2, 3);
+
+    let mut http = TcpStream::connect(&web).unwrap();
+    write!(http, "GET / HTTP/1.1\r\nHost: {}\r\n\r\n", web).unwrap();
+    let mut page = String::new();
+    http.read_to_string(&mut page).unwrap();
+    assert!(page.starts_with("HTTP/1.1 200 OK"));
+    assert!(page.contains("<title>Texas Hold'em</title>"));
+
+    let (mut socket, _) = tungstenite::connect(format!("ws://{}/ws", web)).unwrap();
+    let join = json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Ann" });
+    socket.send(Message::text(join.to_string())).unwrap();
+    let welcome: Value = serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+    assert_eq!((&welcome["type"], &welcome["seat"]), (&json!("welcome"), &json!(0)));
+    let bob = play(Client::join(&address, "Bob").unwrap());
+
+    // The browser checks or calls every time
+    let mut hole_cards = Vec::new();
+    loop {
+        let message: Value = serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+        let answer = match message["type"].as_str().unwrap() {
+            "action_request" if message["legal"][1]["action"] == "check" => json!({ "type": "act", "action": "check" }),
+            "action_request" => json!({ "type": "act", "action": "call" }),
+            "show_request" => json!({ "type": "show", "show": true }),
+            "event" if message["event"] == "hole_cards" => {
+                hole_cards.push(message["seat"].clone());
+                continue;
+            }
+            "table_closed" => break,
+            _ => continue,
+        };
+        socket.send(Message::text(answer.to_string())).unwrap();
+    }
+    assert_eq!(hole_cards, vec![json!(0); 3]);
+    assert_eq!(bob.join().unwrap().1, "the last hand has been played");
+    assert_eq!(server.join().unwrap().seats.iter().map(|seat| seat.chips).sum::<i32>(), 3000);
+}
+
+#[test]
+fn spectators_see_every_players_cards_once_the_hand_is_over() {
+    let (address, _, server) = start_server_with(3, 1, 3, Some(RevealDelay::Hands(1)));
+    let (mut early, god_view) = Client::watch(&address).unwrap();
+    assert!(god_view);
+    let spectator = thread::spawn(move || {
+        let mut rail = Rail::default();
+        let reason = early.follow(&mut rail).unwrap();
+        (rail, reason)
+    });
+    let ann = play(Client::join(&address, "Ann").unwrap());
+
+    // Once the game has started there are no seats left, but anyone can still watch
+    assert_eq!(Client::join(&address, "Bob").err().unwrap(), "the game has started, but you can still watch");
+    let (mut late, _) = Client::watch(&address).unwrap();
+    assert_eq!(late.follow(&mut Rail::default()).unwrap(), "the last hand has been played");
+
+    let (rail, reason) = spectator.join().unwrap();
+    assert_eq!(reason, "the last hand has been played");
+    assert!(!rail.events.iter().any(|event| matches!(event, GameEvent::HoleCards { .. })));
+    assert_eq!(rail.revealed.len(), 9);
+    for &(hand_number, _, seen) in &rail.revealed {
+        let ended = rail.events[..seen].iter().filter(|event| matches!(event, GameEvent::HandEnded { .. })).count();
+        assert!(ended >= hand_number as usize, "the cards of hand {} were revealed after {} hands", hand_number, ended);
+    }
+    assert_eq!(ann.join().unwrap().1, "the last hand has been played");
+    server.join().unwrap();
+}
+
+#[test]
+fn players_who_stay_away_sit_out() {
+    let (address, _, server) = start_server(3, 2, 4);
+    let ann = play(Client::join(&address, "Ann").unwrap());
+    drop(Client::join(&address, "Bob").unwrap());
+
+    let (events, reason) = ann.join().unwrap();
+    assert_eq!(reason, "the last hand has been played");
+    let dealt_in: Vec<Vec<bool>> = events.iter().filter_map(|event| match event {
+        GameEvent::HandStarted { seats, .. } => Some(seats.iter().map(|seat| seat.playing).collect()),
+        _ => None,
+    }).collect();
+    assert_eq!(dealt_in.len(), 4);
+    assert_eq!(dealt_in.last().unwrap(), &vec![true, false, true]);
+    let table = server.join().unwrap();
+    assert!(table.seats[1].sitting_out && table.seats[1].chips > 0);
+}
+
+#[test]
+fn show_requests_are_ranked_the_way_the_variant_ranks_hands() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let cards = |text: &str| text.split_whitespace().map(|card| Card::from_code(card).unwrap()).collect::<Vec<Card>>();
+    // A server that deals a short deck hand and asks whether to show A-6-7-8-9
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let joined: ClientMessage = protocol::receive(&mut stream).unwrap();
+        assert!(matches!(joined, ClientMessage::Join { .. }));
+        let seats = (0..2).map(|seat| SeatInfo { seat, name: format!("Player {}", seat + 1), chips: 100, playing: true }).collect();
+        let started = GameEvent::HandStarted {
+            hand_number: 1,
+            button: 0,
+            small_blind: 0,
+            big_blind: 2,
+            ante: 1,
+            structure: BettingStructure::default(),
+            variant: Variant::ShortDeck,
+            seats,
+        };
+        for message in [
+            ServerMessage::Welcome { version: PROTOCOL_VERSION, seat: 0, token: "token".to_string() },
+            ServerMessage::Event(started),
+            ServerMessage::ShowRequest { cards: cards("Ah 6c"), best_five: cards("Ah 6c 7d 8s 9h"), uncontested: false },
+        ] {
+            protocol::send(&mut stream, &message).unwrap();
+        }
+        let shown: ClientMessage = protocol::receive(&mut stream).unwrap();
+        protocol::send(&mut stream, &ServerMessage::TableClosed { reason: "done".to_string() }).unwrap();
+        shown
+    });
+
+    let mut client = Client::join(&address, "Ann").unwrap();
+    let mut shower = Shower::default();
+    assert_eq!(client.play(&mut shower, &mut Recorder::default()).unwrap(), "done");
+    assert!(matches!(server.join().unwrap(), ClientMessage::Show { show: true }));
+    let hand = shower.0[0].as_ref().unwrap();
+    assert_eq!(hand.rank.hand(), Hand::Straight);
+}
@@ -0,0 +1,282 @@
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use texas_holdem::bot::BotKind;
+use texas_holdem::clock::ShotClock;
+use texas_holdem::engine::{Seat, Table};
+use texas_holdem::protocol::PROTOCOL_VERSION;
+use texas_holdem::server::TableServer;
+
+/*
+ * A client that speaks the protocol byte by byte, as docs/protocol.md describes it, without the
+ * crate's own message types. Each test is a script of frames to send and messages to expect.
+ */
+struct ScriptedClient {
+    stream: TcpStream,
+    // Every message received, for checking afterwards
+    received: Vec<Value>,
+}
+
+impl ScriptedClient {
+    fn connect(address: &str) -> ScriptedClient {
+        ScriptedClient { stream: TcpStream::connect(address).unwrap(), received: Vec::new() }
+    }
+
+    fn send_frame(&mut self, bytes: &[u8]) {
+        self.stream.write_all(&(bytes.len() as u32).to_be_bytes()).unwrap();
+        self.stream.write_all(bytes).unwrap();
+    }
+
+    fn send(&mut self, message: Value) {
+        self.send_frame(message.to_string().as_bytes());
+    }
+
+    fn next(&mut self) -> Option<Value> {
+        let mut length = [0; 4];
+        self.stream.read_exact(&mut length).ok()?;
+        let mut json = vec![0; u32::from_be_bytes(length) as usize];
+        self.stream.read_exact(&mut json).unwrap();
+        let message: Value = serde_json::from_slice(&json).unwrap();
+        self.received.push(message.clone());
+        Some(message)
+    }
+
+    // Reads messages until one of the given type, events before it are kept
+    fn expect(&mut self, kind: &str) -> Value {
+        loop {
+            let message = self.next().unwrap_or_else(|| panic!("the connection closed while waiting for {}", kind));
+            if message["type"] == kind {
+                return message;
+            }
+            assert_eq!(message["type"], "event", "expected {}, got {}", kind, message);
+        }
+    }
+
+    // Reads messages until the given event
+    fn expect_event(&mut self, event: &str) -> Value {
+        loop {
+            let message = self.expect("event");
+            if message["event"] == event {
+                return message;
+            }
+        }
+    }
+
+    fn expect_error(&mut self, code: &str) {
+        let error = self.expect("error");
+        assert_eq!(error["code"], code, "{}", error);
+        assert!(error["message"].as_str().is_some_and(|message| !message.is_empty()));
+    }
+}
+
+// One seat for the scripted client, the others are computer players who check or call
+fn start_server(players: usize) -> (String, JoinHandle<Table>) {
+    start_server_with(players, None)
+}
+
+fn start_server_with(players: usize, shot_clock: Option<ShotClock>) -> (String, JoinHandle<Table>) {
+    let seats = (0..players).map(|i| Seat::new(&format!("Player {}", i + 1), 1000)).collect();
+    let mut table = Table::new(seats, 5, 10);
+    table.seed = 11;
+    let mut server = TableServer::bind("127.0.0.1:0", table, 1, vec![BotKind::Calling; players - 1]).unwrap();
+    // A client that goes away is waited for a second
+    server.disconnect_bank = Duration::from_secs(1);
+    server.shot_clock = shot_clock;
+    let address = server.local_addr().to_string();
+    let handle = thread::spawn(move || {
+        server.run(Some(2), Vec::new()).unwrap();
+        server.table
+    });
+    (address, handle)
+}
+
+#[test]
+fn joining_a_seat() {
+    let (address, server) = start_server(3);
+    let mut client = ScriptedClient::connect(&address);
+
+    client.send(json!({ "type": "act", "action": "fold" }));
+    client.expect_error("unexpected_message");
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION + 1, "name": "Ann" }));
+    client.expect_error("unsupported_version");
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Player 2" }));
+    client.expect_error("name_taken");
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "   " }));
+    client.expect_error("invalid_name");
+    client.send_frame(b"{\"type\": \"join\"");
+    client.expect_error("invalid_message");
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Ann" }));
+    let welcome = client.expect("welcome");
+    assert_eq!((&welcome["version"], &welcome["seat"]), (&json!(PROTOCOL_VERSION), &json!(0)));
+    assert!(welcome["token"].as_str().is_some_and(|token| token.len() >= 16));
+
+    // Folding every hand plays out the game
+    while let Some(message) = client.next() {
+        match message["type"].as_str().unwrap() {
+            "action_request" => client.send(json!({ "type": "act", "action": "fold" })),
+            "show_request" => client.send(json!({ "type": "show", "show": false })),
+            "table_closed" => break,
+            _ => {}
+        }
+    }
+    assert_eq!(client.received.last().unwrap()["type"], "table_closed");
+    assert_eq!(server.join().unwrap().hand_number, 2);
+}
+
+#[test]
+fn action_requests_list_the_legal_actions() {
+    let (address, server) = start_server(2);
+    let mut client = ScriptedClient::connect(&address);
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Ann" }));
+    client.expect("welcome");
+
+    // Heads up the first hand's button, seat 0, posts the small blind and acts first
+    let request = client.expect("action_request");
+    assert_eq!(request["seat"], 0);
+    assert_eq!(request["street"], "preflop");
+    assert_eq!(request["hole_cards"].as_array().unwrap().len(), 2);
+    assert_eq!(request["to_call"], 5);
+    assert_eq!(request["time_limit"], Value::Null);
+    assert_eq!(
+        request["legal"],
+        json!([{ "action": "fold" }, { "action": "call", "amount": 5 }, { "action": "raise", "min": 20, "max": 1000 }])
+    );
+
+    // An illegal action is refused and the request stays open
+    client.send(json!({ "type": "act", "action": "check" }));
+    client.expect_error("illegal_action");
+    client.send(json!({ "type": "act", "action": { "raise": 10_000 } }));
+    client.expect_error("illegal_action");
+    client.send(json!({ "type": "show", "show": true }));
+    client.expect_error("unexpected_message");
+    client.send(json!({ "type": "act", "action": { "raise": 40 } }));
+
+    let acted = client.expect_event("player_acted");
+    assert_eq!(acted, json!({ "type": "event", "event": "player_acted", "seat": 0, "action": { "raise": 40 }, "amount": 35, "bet": 40, "raised_by": 30, "all_in": false }));
+
+    drop(client);
+    server.join().unwrap();
+}
+
+#[test]
+fn events_carry_only_the_clients_hole_cards() {
+    let (address, server) = start_server(4);
+    let mut client = ScriptedClient::connect(&address);
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Ann" }));
+    client.expect("welcome");
+    while let Some(message) = client.next() {
+        match message["type"].as_str().unwrap() {
+            "action_request" if message["legal"][1]["action"] == "check" => client.send(json!({ "type": "act", "action": "check" })),
+            "action_request" => client.send(json!({ "type": "act", "action": "call" })),
+            "show_request" => client.send(json!({ "type": "show", "show": true })),
+            "table_closed" => break,
+            _ => {}
+        }
+    }
+
+    let events: Vec<&Value> = client.received.iter().filter(|message| message["type"] == "event").collect();
+    let hole_cards: Vec<&&Value> = events.iter().filter(|event| event["event"] == "hole_cards").collect();
+    assert_eq!(hole_cards.len(), 2);
+    assert!(hole_cards.iter().all(|event| event["seat"] == 0));
+    assert_eq!(events.iter().filter(|event| event["event"] == "hand_ended").count(), 2);
+    assert!(client.received.last().unwrap()["reason"].is_string());
+    server.join().unwrap();
+}
+
+#[test]
+fn oversized_frames_are_skipped() {
+    let (address, server) = start_server(2);
+    let mut client = ScriptedClient::connect(&address);
+    client.send_frame(&vec![b' '; 2 << 20]);
+    client.expect_error("invalid_message");
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Ann" }));
+    client.expect("welcome");
+    drop(client);
+    server.join().unwrap();
+}
+
+#[test]
+fn taking_a_seat_back_with_the_token() {
+    let (address, server) = start_server(3);
+    let mut client = ScriptedClient::connect(&address);
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Ann" }));
+    let token = client.expect("welcome")["token"].clone();
+    let dealt = client.expect_event("hole_cards");
+    let asked = client.expect("action_request");
+    drop(client);
+
+    let mut client = ScriptedClient::connect(&address);
+    client.send(json!({ "type": "rejoin", "version": PROTOCOL_VERSION, "token": "not a token" }));
+    client.expect_error("invalid_token");
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Bob" }));
+    client.expect_error("table_full");
+    client.send(json!({ "type": "rejoin", "version": PROTOCOL_VERSION, "token": token }));
+    let welcome = client.expect("welcome");
+    assert_eq!((&welcome["seat"], &welcome["token"]), (&json!(0), &token));
+
+    // The hand so far is sent again, with the player's own cards, and the request is still open
+    assert_eq!(client.expect_event("hand_started")["hand_number"], 1);
+    assert_eq!(client.expect_event("hole_cards"), dealt);
+    assert_eq!(client.expect("action_request"), asked);
+    client.send(json!({ "type": "act", "action": "fold" }));
+    let folded = client.expect_event("player_acted");
+    assert_eq!((&folded["seat"], &folded["action"]), (&json!(0), &json!("fold")));
+    drop(client);
+    server.join().unwrap();
+}
+
+#[test]
+fn a_client_out_of_time_checks_or_folds() {
+    let (address, server) = start_server_with(2, Some(ShotClock { action: 1, bank: 1, top_up: 0 }));
+    let mut client = ScriptedClient::connect(&address);
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Ann" }));
+    client.expect("welcome");
+
+    // The small blind can't check, so the seat folds once its time and time bank are spent
+    let request = client.expect("action_request");
+    assert_eq!((&request["hand_number"], &request["time_limit"], &request["time_bank"]), (&json!(1), &json!(2), &json!(1)));
+    let acted = client.expect_event("player_acted");
+    assert_eq!((&acted["seat"], &acted["action"]), (&json!(0), &json!("fold")));
+
+    // In the next hand the big blind checks, with nothing left in the bank
+    let request = client.expect("action_request");
+    assert_eq!((&request["hand_number"], &request["time_limit"], &request["time_bank"]), (&json!(2), &json!(1), &json!(0)));
+    let acted = client.expect_event("player_acted");
+    assert_eq!((&acted["seat"], &acted["action"]), (&json!(0), &json!("check")));
+
+    // An answer in time is taken as usual
+    assert_eq!(client.expect("action_request")["street"], "flop");
+    client.send(json!({ "type": "act", "action": "fold" }));
+    let acted = client.expect_event("player_acted");
+    assert_eq!((&acted["seat"], &acted["action"]), (&json!(0), &json!("fold")));
+    drop(client);
+    server.join().unwrap();
+}
+
+#[test]
+fn a_client_that_never_answers_a_show_request_mucks() {
+    let (address, server) = start_server(3);
+    let mut client = ScriptedClient::connect(&address);
+    client.send(json!({ "type": "join", "version": PROTOCOL_VERSION, "name": "Ann" }));
+    client.expect("welcome");
+
+    // Calling down to every showdown, and leaving the show requests unanswered
+    let mut asked = 0;
+    while let Some(message) = client.next() {
+        match message["type"].as_str().unwrap() {
+            "action_request" => {
+                let action = if message["to_call"] == 0 { "check" } else { "call" };
+                client.send(json!({ "type": "act", "action": action }));
+            }
+            "show_request" => asked += 1,
+            "table_closed" => break,
+            _ => {}
+        }
+    }
+    assert!(asked > 0);
+    let mucked = client.received.iter().filter(|message| message["event"] == "hand_mucked" && message["seat"] == 0).count();
+    assert_eq!(mucked, asked);
+    assert_eq!(server.join().unwrap().hand_number, 2);
+}
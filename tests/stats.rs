@@ -0,0 +1,102 @@
+use texas_holdem::events::{Action, BettingStructure, GameEvent, PotShare, SeatInfo, Street, Variant};
+use texas_holdem::stats::Stats;
+
+fn hand_started(hand_number: u32) -> GameEvent {
+    let seats = (0..3).map(|seat| SeatInfo { seat, name: format!("Player {}", seat + 1), chips: 1000, playing: true }).collect();
+    GameEvent::HandStarted { hand_number, button: 0, small_blind: 5, big_blind: 10, ante: 0, structure: BettingStructure::NoLimit, variant: Variant::Holdem, seats }
+}
+
+fn dealt() -> Vec<GameEvent> {
+    (0..3).map(|seat| GameEvent::HoleCards { seat, cards: Vec::new() }).collect()
+}
+
+fn acted(seat: usize, action: Action) -> GameEvent {
+    GameEvent::PlayerActed { seat, action, amount: 0, bet: 0, raised_by: 0, all_in: false }
+}
+
+fn street(street: Street) -> GameEvent {
+    GameEvent::StreetDealt { street, cards: Vec::new(), board: Vec::new() }
+}
+
+fn won(seat: usize) -> GameEvent {
+    GameEvent::PotAwarded { pot: 0, seat, amount: 100, hand: None, share: PotShare::Whole }
+}
+
+fn ended() -> GameEvent {
+    GameEvent::HandEnded { chips: vec![1000; 3] }
+}
+
+// Two hands: a 3-bet pot that goes to showdown, and a limped pot won on the flop
+fn two_hands() -> Vec<GameEvent> {
+    let mut events = vec![hand_started(1)];
+    events.extend(dealt());
+    events.extend([
+        acted(0, Action::Raise(30)),
+        acted(1, Action::Raise(90)),
+        acted(2, Action::Fold),
+        acted(0, Action::Call),
+        street(Street::Flop),
+        acted(1, Action::Raise(100)),
+        acted(0, Action::Call),
+        street(Street::Turn),
+        acted(1, Action::Check),
+        acted(0, Action::Raise(200)),
+        acted(1, Action::Call),
+        street(Street::River),
+        acted(1, Action::Check),
+        acted(0, Action::Check),
+        GameEvent::ShowdownStarted,
+        won(1),
+        ended(),
+        hand_started(2),
+    ]);
+    events.extend(dealt());
+    events.extend([
+        acted(0, Action::Fold),
+        acted(1, Action::Call),
+        acted(2, Action::Check),
+        street(Street::Flop),
+        acted(1, Action::Raise(20)),
+        acted(2, Action::Fold),
+        won(1),
+        ended(),
+    ]);
+    events
+}
+
+#[test]
+fn counts_each_players_statistics() {
+    let events = two_hands();
+    let stats = Stats::from_events(&events);
+    let [first, second, third] = &stats.players[..] else { panic!("three players") };
+
+    assert_eq!((first.hands, first.vpip(), first.pfr(), first.three_bet()), (2, Some(50.0), Some(50.0), None));
+    assert_eq!((first.aggression(), first.wtsd(), first.won_at_showdown()), (Some(1.0), Some(100.0), Some(0.0)));
+
+    assert_eq!((second.vpip(), second.pfr(), second.three_bet()), (Some(100.0), Some(50.0), Some(100.0)));
+    assert_eq!((second.aggression(), second.wtsd(), second.won_at_showdown()), (Some(2.0), Some(50.0), Some(100.0)));
+
+    // Facing a raise and a 3-bet is no chance to 3-bet, and a pot won without a showdown isn't counted
+    assert_eq!((third.vpip(), third.three_bet_chances, third.saw_flop), (Some(0.0), 0, 1));
+    assert_eq!((third.aggression(), third.wtsd(), third.won_at_showdown()), (None, Some(0.0), None));
+}
+
+#[test]
+fn shows_the_statistics_in_a_line_and_a_table() {
+    let events = two_hands();
+    let stats = Stats::from_events(&events);
+    assert_eq!(stats.players[1].hud(), "VPIP 100 PFR 50 3B 100 AF 2.0 WTSD 50 W$SD 100 (2 hands)");
+
+    let table = stats.table();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("Player") && lines[0].ends_with("W$SD"));
+    assert_eq!(lines[3].split_whitespace().collect::<Vec<_>>(), ["Player", "3", "2", "0", "0", "-", "-", "0", "-"]);
+}
+
+#[test]
+fn leaves_out_a_hand_the_log_starts_in_the_middle_of() {
+    let events = two_hands();
+    let stats = Stats::from_events(&events[4..]);
+    assert_eq!(stats.players[1].hands, 1);
+}